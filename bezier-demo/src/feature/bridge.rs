@@ -0,0 +1,94 @@
+use crate::ui::direct2d::{self, create_brush};
+use bezier_feature::crossing::{Crossing, Structure};
+use windows::Win32::Graphics::Direct2D::{
+    ID2D1Factory1, ID2D1HwndRenderTarget, ID2D1SolidColorBrush, ID2D1StrokeStyle,
+};
+
+const DECK: (f32, f32, f32, f32) = (0.5, 0.5, 0.52, 1.0);
+const PARAPET: (f32, f32, f32, f32) = (0.2, 0.2, 0.22, 1.0);
+const PARAPET_WIDTH: f32 = 2.0;
+/// Half the length of the culvert marker drawn across the road
+const CULVERT_MARKER_LENGTH: f32 = 8.0;
+
+/// Draws the bridges and culverts where roads cross rivers. The structures
+/// are drawn after every feature so a bridge deck is above the water.
+pub(crate) struct Bridge<'a> {
+    deck_brush: Option<ID2D1SolidColorBrush>,
+    parapet_brush: Option<ID2D1SolidColorBrush>,
+    factory: &'a ID2D1Factory1,
+    line_style: ID2D1StrokeStyle,
+}
+
+impl<'a> Bridge<'a> {
+    pub(crate) fn new(factory: &'a ID2D1Factory1) -> Self {
+        let line_style =
+            direct2d::create_style(factory).expect("unable to create stroke style");
+        Bridge {
+            deck_brush: None,
+            parapet_brush: None,
+            factory,
+            line_style,
+        }
+    }
+
+    pub(crate) fn create_resources(
+        &mut self,
+        target: &ID2D1HwndRenderTarget,
+    ) -> windows::core::Result<()> {
+        self.deck_brush = Some(create_brush(target, DECK.0, DECK.1, DECK.2, DECK.3)?);
+        self.parapet_brush = Some(create_brush(
+            target, PARAPET.0, PARAPET.1, PARAPET.2, PARAPET.3,
+        )?);
+        Ok(())
+    }
+
+    pub(crate) fn release_resources(&mut self) {
+        self.deck_brush = None;
+        self.parapet_brush = None;
+    }
+
+    pub(crate) fn draw(
+        &self,
+        crossing: &Crossing,
+        target: &ID2D1HwndRenderTarget,
+    ) -> windows::core::Result<()> {
+        let parapet_brush = self.parapet_brush.as_ref().unwrap();
+        match &crossing.structure {
+            Structure::Bridge { deck } => {
+                direct2d::fill_polygon(
+                    self.factory,
+                    target,
+                    deck.points(),
+                    self.deck_brush.as_ref().unwrap(),
+                )?;
+                // the deck runs down one side and back up the other
+                let points = deck.points();
+                let (left, right) = points.split_at(points.len() / 2);
+                for side in [left, right] {
+                    direct2d::draw_line(
+                        target,
+                        &side.to_vec(),
+                        parapet_brush,
+                        &self.line_style,
+                        PARAPET_WIDTH,
+                    );
+                }
+            }
+            Structure::Culvert => {
+                let across = geometry::Point {
+                    x: crossing.direction.y,
+                    y: -crossing.direction.x,
+                } * CULVERT_MARKER_LENGTH;
+                let marker = vec![crossing.position + across, crossing.position - across];
+                direct2d::draw_line(
+                    target,
+                    &marker,
+                    parapet_brush,
+                    &self.line_style,
+                    PARAPET_WIDTH,
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use crate::ui::direct2d::{self, create_brush};
+use bezier_feature::railroad::Railroad as Track;
+use windows::Win32::Graphics::Direct2D::{
+    ID2D1Factory1, ID2D1HwndRenderTarget, ID2D1SolidColorBrush, ID2D1StrokeStyle,
+};
+
+use super::BezierFeature;
+
+/// Map units per metre used to scale the track dimensions
+pub(crate) const TRACK_SCALE: f32 = 10.0;
+const BALLAST: (f32, f32, f32, f32) = (0.55, 0.52, 0.5, 1.0);
+const TIE: (f32, f32, f32, f32) = (0.4, 0.26, 0.13, 1.0);
+const RAIL: (f32, f32, f32, f32) = (0.3, 0.3, 0.32, 1.0);
+const RAIL_WIDTH: f32 = 1.5;
+
+pub(crate) struct Railroad<'a> {
+    ballast_brush: Option<ID2D1SolidColorBrush>,
+    tie_brush: Option<ID2D1SolidColorBrush>,
+    rail_brush: Option<ID2D1SolidColorBrush>,
+    factory: &'a ID2D1Factory1,
+    line_style: ID2D1StrokeStyle,
+}
+
+impl<'a> Railroad<'a> {
+    pub(crate) fn new(factory: &'a ID2D1Factory1) -> Self {
+        let line_style =
+            direct2d::create_style(factory).expect("unable to create stroke style");
+        Railroad {
+            ballast_brush: None,
+            tie_brush: None,
+            rail_brush: None,
+            factory,
+            line_style,
+        }
+    }
+
+    pub(crate) fn create_resources(
+        &mut self,
+        target: &ID2D1HwndRenderTarget,
+    ) -> windows::core::Result<()> {
+        self.ballast_brush = Some(create_brush(
+            target, BALLAST.0, BALLAST.1, BALLAST.2, BALLAST.3,
+        )?);
+        self.tie_brush = Some(create_brush(target, TIE.0, TIE.1, TIE.2, TIE.3)?);
+        self.rail_brush = Some(create_brush(target, RAIL.0, RAIL.1, RAIL.2, RAIL.3)?);
+        Ok(())
+    }
+
+    pub(crate) fn release_resources(&mut self) {
+        self.ballast_brush = None;
+        self.tie_brush = None;
+        self.rail_brush = None;
+    }
+
+    pub(crate) fn draw(
+        &self,
+        track: &Track,
+        feature: &mut BezierFeature,
+        target: &ID2D1HwndRenderTarget,
+    ) -> windows::core::Result<()> {
+        let ballast = track.ballast(feature);
+        direct2d::fill_polygon(
+            self.factory,
+            target,
+            ballast.points(),
+            self.ballast_brush.as_ref().unwrap(),
+        )?;
+        for tie in track.ties(feature) {
+            direct2d::fill_polygon(
+                self.factory,
+                target,
+                tie.points(),
+                self.tie_brush.as_ref().unwrap(),
+            )?;
+        }
+        for rail in track.rails(feature).iter() {
+            direct2d::draw_line(
+                target,
+                rail,
+                self.rail_brush.as_ref().unwrap(),
+                &self.line_style,
+                RAIL_WIDTH,
+            );
+        }
+        Ok(())
+    }
+}
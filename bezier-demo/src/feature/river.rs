@@ -1,73 +1,83 @@
-use geometry::Point;
+use crate::ui::direct2d::{self, create_brush};
+use bezier_feature::river::River as Channel;
 use windows::Win32::Graphics::Direct2D::{
-    ID2D1Factory1, ID2D1HwndRenderTarget, ID2D1PathGeometry, ID2D1SolidColorBrush,
+    ID2D1Factory1, ID2D1HwndRenderTarget, ID2D1SolidColorBrush, ID2D1StrokeStyle,
 };
 
 use super::BezierFeature;
 
+const WATER: (f32, f32, f32, f32) = (0.0, 0.65, 0.93, 1.0);
+const BANK: (f32, f32, f32, f32) = (0.2, 0.45, 0.2, 1.0);
+pub(crate) const SOURCE_WIDTH: f32 = 10.0;
+pub(crate) const MOUTH_WIDTH: f32 = 40.0;
+const FLOW_MARKER_SPACING: f32 = 60.0;
+const FLOW_MARKER_LENGTH: f32 = 6.0;
+
 pub(crate) struct River<'a> {
-    modified: bool,
-    feature: Option<BezierFeature>,
     surface_brush: Option<ID2D1SolidColorBrush>,
-    edgeline: Option<[Vec<Vec<Point>>; 2]>,
-    surface: Option<ID2D1PathGeometry>,
+    bank_brush: Option<ID2D1SolidColorBrush>,
     factory: &'a ID2D1Factory1,
+    line_style: ID2D1StrokeStyle,
 }
 
 impl<'a> River<'a> {
     pub(crate) fn new(factory: &'a ID2D1Factory1) -> Self {
+        let line_style =
+            direct2d::create_style(factory).expect("unable to create stroke style");
         River {
-            modified: true,
-            feature: None,
             surface_brush: None,
-            edgeline: None,
-            surface: None,
+            bank_brush: None,
             factory,
+            line_style,
         }
     }
 
-    pub(crate) fn feature(&self) -> Option<&BezierFeature> {
-        self.feature.as_ref()
-    }
-
-    pub(crate) fn feature_mut(&mut self) -> Option<&mut BezierFeature> {
-        self.feature.as_mut()
+    pub(crate) fn create_resources(
+        &mut self,
+        target: &ID2D1HwndRenderTarget,
+    ) -> windows::core::Result<()> {
+        self.surface_brush = Some(create_brush(target, WATER.0, WATER.1, WATER.2, WATER.3)?);
+        self.bank_brush = Some(create_brush(target, BANK.0, BANK.1, BANK.2, BANK.3)?);
+        Ok(())
     }
 
-    pub(crate) fn set_feature(&mut self, feature: BezierFeature) {
-        self.feature = Some(feature);
+    pub(crate) fn release_resources(&mut self) {
+        self.surface_brush = None;
+        self.bank_brush = None;
     }
 
-    pub(crate) fn take_feature(&mut self) -> Option<BezierFeature> {
-        self.feature.take()
-    }
-
-    pub(crate) fn modified(&self) -> bool {
-        self.modified | match &self.feature {
-            Some(feature) => feature.modified(),
-            _ => false,
+    /// Draws the river surface, banks and flow markers
+    pub(crate) fn draw(
+        &self,
+        channel: &Channel,
+        feature: &mut BezierFeature,
+        target: &ID2D1HwndRenderTarget,
+    ) -> windows::core::Result<()> {
+        let surface = channel.surface(feature);
+        direct2d::fill_polygon(
+            self.factory,
+            target,
+            surface.points(),
+            self.surface_brush.as_ref().unwrap(),
+        )?;
+        let bank_brush = self.bank_brush.as_ref().unwrap();
+        for bank in channel.banks(feature).iter() {
+            direct2d::draw_line(target, bank, bank_brush, &self.line_style, 1.5);
+        }
+        for (p, direction) in channel.flow_markers(feature, FLOW_MARKER_SPACING) {
+            let side = geometry::Point {
+                x: direction.y,
+                y: -direction.x,
+            };
+            let tip = p + direction * FLOW_MARKER_LENGTH;
+            let tail = p - direction * FLOW_MARKER_LENGTH;
+            let arrow = vec![
+                tail + side * FLOW_MARKER_LENGTH,
+                tip,
+                tail - side * FLOW_MARKER_LENGTH,
+            ];
+            direct2d::draw_line(target, &arrow, bank_brush, &self.line_style, 1.0);
         }
+        Ok(())
     }
-
-    // pub(crate) fn draw(&mut self, target: ID2D1HwndRenderTarget) {
-    //     if self.feature.is_none() {
-    //         return;
-    //     }
-    //     let feature = self.feature.as_mut().unwrap();
-    //     let rebuild_geom = self.modified | feature.modified();
-    //     if rebuild_geom {
-    //         self.surface = Some(super::rebuild_geometry(
-    //             self.feature.as_mut().unwrap(),
-    //             self.factory,
-    //         ));            
-    //     }
-    //     unsafe {
-    //         target.FillGeometry(
-    //             self.surface.as_ref().unwrap(),
-    //             self.surface_brush.as_ref().unwrap(),
-    //             None,
-    //         )
-    //     };
-    //     self.modified = false;
-    // }
 }
@@ -1,29 +1,32 @@
 use crate::ui::direct2d::{self, create_brush};
-use geometry::Point;
 use windows::Win32::Graphics::Direct2D::{
     ID2D1Factory1, ID2D1HwndRenderTarget, ID2D1PathGeometry, ID2D1SolidColorBrush, ID2D1StrokeStyle,
 };
 
 use super::BezierFeature;
 
-pub const DEFAULT_ROAD_WIDTH: f32 = 50.0;
+use bezier_feature::{
+    cross_section::{PartKind, RoadLayout},
+    junction::Junction,
+    map::Map,
+};
+pub use bezier_feature::road::{CenterLine, DEFAULT_ROAD_WIDTH};
+
 const ASPHALT_GRAY: f32 = 0.65;
+const SHOULDER_GRAY: f32 = 0.55;
+const SIDEWALK_GRAY: f32 = 0.85;
+const MEDIAN: (f32, f32, f32, f32) = (0.45, 0.65, 0.35, 1.0);
 const CENTERLINE: (f32, f32, f32, f32) = (0.98, 0.665, 0.0, 1.0);
-
-#[derive(Debug, Clone)]
-pub(crate) enum CenterLine {
-    Solid,
-    DoubleSolid,
-    Stripe,
-    StripeSolid,
-}
+const EDGELINE: (f32, f32, f32, f32) = (0.98, 0.98, 0.98, 1.0);
 
 pub(crate) struct Road<'a> {
     modified: bool,
     pub(crate) surface_brush: Option<ID2D1SolidColorBrush>,
     pub(crate) centerline_brush: Option<ID2D1SolidColorBrush>,
-    centerline: Option<[Vec<Vec<Point>>; 2]>,
-    edgeline: Option<[Vec<Vec<Point>>; 2]>,
+    pub(crate) edgeline_brush: Option<ID2D1SolidColorBrush>,
+    shoulder_brush: Option<ID2D1SolidColorBrush>,
+    sidewalk_brush: Option<ID2D1SolidColorBrush>,
+    median_brush: Option<ID2D1SolidColorBrush>,
     surface: Option<ID2D1PathGeometry>,
     factory: &'a ID2D1Factory1,
     pub(crate) line_style: ID2D1StrokeStyle,
@@ -32,13 +35,15 @@ pub(crate) struct Road<'a> {
 impl<'a> Road<'a> {
     pub(crate) fn new(factory: &'a ID2D1Factory1) -> Self {
         let line_style =
-            direct2d::create_style(factory, None).expect("unable to create stroke style");
+            direct2d::create_style(factory).expect("unable to create stroke style");
         Road {
             modified: false,
             surface_brush: None,
             centerline_brush: None,
-            centerline: None,
-            edgeline: None,
+            edgeline_brush: None,
+            shoulder_brush: None,
+            sidewalk_brush: None,
+            median_brush: None,
             surface: None,
             factory,
             line_style,
@@ -71,6 +76,34 @@ impl<'a> Road<'a> {
             CENTERLINE.2,
             CENTERLINE.3,
         )?);
+        self.edgeline_brush = Some(create_brush(
+            target,
+            EDGELINE.0,
+            EDGELINE.1,
+            EDGELINE.2,
+            EDGELINE.3,
+        )?);
+        self.shoulder_brush = Some(create_brush(
+            target,
+            SHOULDER_GRAY,
+            SHOULDER_GRAY,
+            SHOULDER_GRAY,
+            1.0,
+        )?);
+        self.sidewalk_brush = Some(create_brush(
+            target,
+            SIDEWALK_GRAY,
+            SIDEWALK_GRAY,
+            SIDEWALK_GRAY,
+            1.0,
+        )?);
+        self.median_brush = Some(create_brush(
+            target,
+            MEDIAN.0,
+            MEDIAN.1,
+            MEDIAN.2,
+            MEDIAN.3,
+        )?);
 
         Ok(())
     }
@@ -78,7 +111,60 @@ impl<'a> Road<'a> {
     pub(crate) fn release_resources(&mut self) {
         self.surface_brush = None;
         self.centerline_brush = None;
+        self.edgeline_brush = None;
+        self.shoulder_brush = None;
+        self.sidewalk_brush = None;
+        self.median_brush = None;
         self.surface = None;
     }
 
+    /// Draws each part of a road with lanes, shoulders and sidewalks and the
+    /// painted lines over them
+    pub(crate) fn draw_lanes(
+        &self,
+        layout: &RoadLayout,
+        feature: &mut BezierFeature,
+        target: &ID2D1HwndRenderTarget,
+    ) -> windows::core::Result<()> {
+        for part in layout.parts(feature) {
+            let brush = match part.kind {
+                PartKind::Median => &self.median_brush,
+                PartKind::Lane => &self.surface_brush,
+                PartKind::Shoulder => &self.shoulder_brush,
+                PartKind::Sidewalk => &self.sidewalk_brush,
+            };
+            direct2d::fill_polygon(
+                self.factory,
+                target,
+                part.surface.points(),
+                brush.as_ref().unwrap(),
+            )?;
+        }
+        let line_width = layout.markings.line_width;
+        for line in layout.markings(feature) {
+            direct2d::draw_line(
+                target,
+                line.points(),
+                self.edgeline_brush.as_ref().unwrap(),
+                &self.line_style,
+                line_width,
+            );
+        }
+        Ok(())
+    }
+
+    /// Fills the core of the junction where the surfaces of its roads meet
+    pub(crate) fn draw_junction(
+        &self,
+        junction: &Junction,
+        map: &Map,
+        target: &ID2D1HwndRenderTarget,
+    ) -> windows::core::Result<()> {
+        direct2d::fill_polygon(
+            self.factory,
+            target,
+            junction.core(map).points(),
+            self.surface_brush.as_ref().unwrap(),
+        )
+    }
 }
@@ -1,15 +1,27 @@
 mod feature;
 mod ui;
 
+use std::path::PathBuf;
+
+use clap::Parser;
 use ui::direct2d;
 use windows::Win32::{
     Foundation::HWND,
     UI::WindowsAndMessaging::{DispatchMessageW, GetMessageW, MSG},
 };
 
+#[derive(Parser)]
+#[clap(about = "Draws and edits road, river and railroad features")]
+struct Args {
+    /// Map file to open. Ctrl+S saves the map to this file.
+    #[clap(long)]
+    map: Option<PathBuf>,
+}
+
 fn main() -> windows::core::Result<()> {
+    let args = Args::parse();
     let factory = direct2d::create_factory()?;
-    let _m = ui::MainWindow::new("Bézier Demo", &factory);
+    let _m = ui::MainWindow::new("Bézier Demo", &factory, args.map);
     let mut message = MSG::default();
     unsafe {
         while GetMessageW(&mut message, HWND(0), 0, 0).into() {
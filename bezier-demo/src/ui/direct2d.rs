@@ -15,20 +15,15 @@ pub(crate) fn create_factory() -> Result<ID2D1Factory1> {
     unsafe { D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, Some(&options)) }
 }
 
-/// Create a stroke style with the specified dash pattern
-pub(crate) fn create_style(
-    factory: &ID2D1Factory1,
-    dashes: Option<&[f32]>,
-) -> Result<ID2D1StrokeStyle> {
-    let mut props = D2D1_STROKE_STYLE_PROPERTIES {
+/// Create a solid stroke style with round caps. Dashed lines are split into
+/// dashes with `geometry::dash::DashPattern` and stroked with this style.
+pub(crate) fn create_style(factory: &ID2D1Factory1) -> Result<ID2D1StrokeStyle> {
+    let props = D2D1_STROKE_STYLE_PROPERTIES {
         startCap: D2D1_CAP_STYLE_ROUND,
         endCap: D2D1_CAP_STYLE_ROUND,
         ..Default::default()
     };
-    if dashes.is_some() {
-        props.dashStyle = D2D1_DASH_STYLE_CUSTOM;
-    }
-    unsafe { factory.CreateStrokeStyle(&props, dashes) }
+    unsafe { factory.CreateStrokeStyle(&props, None) }
 }
 
 pub(crate) fn create_brush(
@@ -48,7 +43,7 @@ pub(crate) fn create_brush(
 
 pub(crate) fn draw_line(
     target: &ID2D1HwndRenderTarget,
-    points: &Vec<Point>,
+    points: &[Point],
     brush: &ID2D1SolidColorBrush,
     style: &ID2D1StrokeStyle,
     width: f32,
@@ -67,3 +62,28 @@ pub(crate) fn draw_line(
         p1 = p2;
     }
 }
+
+/// Fills a closed polygon using the winding fill mode
+pub(crate) fn fill_polygon(
+    factory: &ID2D1Factory1,
+    target: &ID2D1HwndRenderTarget,
+    points: &[Point],
+    brush: &ID2D1SolidColorBrush,
+) -> Result<()> {
+    if points.len() < 3 {
+        return Ok(());
+    }
+    let geometry = unsafe { factory.CreatePathGeometry()? };
+    unsafe {
+        let sink = geometry.Open()?;
+        sink.SetFillMode(D2D1_FILL_MODE_WINDING);
+        sink.BeginFigure(points[0].into(), D2D1_FIGURE_BEGIN_FILLED);
+        for point in points.iter().skip(1) {
+            sink.AddLine((*point).into());
+        }
+        sink.EndFigure(D2D1_FIGURE_END_CLOSED);
+        sink.Close()?;
+        target.FillGeometry(&geometry, brush, None);
+    }
+    Ok(())
+}
@@ -1,7 +1,25 @@
-use crate::feature::{road::Road, BezierFeature, BezierFeatureType};
-use geometry::Point;
+use crate::feature::{
+    bridge::Bridge,
+    railroad::{Railroad, TRACK_SCALE},
+    river::{River, MOUTH_WIDTH, SOURCE_WIDTH},
+    road::Road,
+    BezierFeature, BezierFeatureType,
+};
+use bezier_feature::{
+    cross_section::{CrossSection, RoadLayout, DEFAULT_SIDEWALK_WIDTH},
+    crossing::CrossingRules,
+    history::{Edit, History},
+    junction::{Approach, Location},
+    map::{FeatureId, Map, Selection, Style},
+    map_file,
+    railroad::Railroad as Track,
+    river::{Flow, River as Channel},
+    road::RoadMarkings,
+    Closure,
+};
+use geometry::{dash::DashPattern, polyline::Polyline, Point};
 
-use std::sync::Once;
+use std::{path::PathBuf, sync::Once};
 use windows::{
     core::HRESULT,
     Win32::{
@@ -15,10 +33,11 @@ use windows::{
             Gdi::{InvalidateRect},
         },
         System::{LibraryLoader::GetModuleHandleW, SystemServices::MK_LBUTTON},
+        UI::Input::KeyboardAndMouse::{GetKeyState, SetFocus, VK_CONTROL},
         UI::WindowsAndMessaging::{
             GetClientRect, GetWindowLongPtrA, SetWindowLongPtrA, CREATESTRUCTA, GWLP_USERDATA,
-            WM_CREATE, WM_LBUTTONDOWN, WM_LBUTTONUP, WS_CHILDWINDOW, WS_CLIPSIBLINGS, WS_HSCROLL,
-            WS_VSCROLL,
+            WM_CREATE, WM_KEYDOWN, WM_LBUTTONDOWN, WM_LBUTTONUP, WS_CHILDWINDOW, WS_CLIPSIBLINGS,
+            WS_HSCROLL, WS_VSCROLL,
         },
     },
 };
@@ -32,9 +51,9 @@ use windows::{
             Gdi::{BeginPaint, CreateSolidBrush, EndPaint, PAINTSTRUCT},
         },
         UI::WindowsAndMessaging::{
-            CreateWindowExW, DefWindowProcW, LoadCursorW, PostQuitMessage, RegisterClassW,
-            CS_HREDRAW, CS_VREDRAW, CW_USEDEFAULT, HMENU, IDC_ARROW, 
-            WINDOW_EX_STYLE, WM_DESTROY, WM_MOUSEMOVE, WM_PAINT, WM_SIZE, WNDCLASSW, WS_VISIBLE,
+            CreateWindowExW, DefWindowProcW, LoadCursorW, MessageBoxW, PostQuitMessage,
+            RegisterClassW, CS_HREDRAW, CS_VREDRAW, CW_USEDEFAULT, HMENU, IDC_ARROW, MB_ICONERROR,
+            MB_OK, WINDOW_EX_STYLE, WM_DESTROY, WM_MOUSEMOVE, WM_PAINT, WM_SIZE, WNDCLASSW, WS_VISIBLE,
         },
     },
 };
@@ -45,19 +64,62 @@ use super::direct2d;
 
 const RENDER_CTRL_HANDLE_RADIUS: f32 = 5.0;
 
+/// Dash and gap lengths of the lines from the joints to the handles
+const RENDER_CTRL_HANDLE_DASH: [f32; 2] = [4.0, 2.0];
+
 static REGISTER_FEATURE_WINDOW_CLASS: Once = Once::new();
 static FEATURE_WINDOW_CLASS_NAME: &HSTRING = w!("bytetrail.window.bezier-demo");
 
 pub(crate) struct RenderState<'a> {
-    pub hover: Option<usize>,
-    pub selected: Option<usize>,
-    pub feature_type: BezierFeatureType,
-    pub feature: BezierFeature,
-    pub road_visual: Road<'a>
+    pub map: Map,
+    /// The file the map was opened from and is saved to
+    pub map_path: Option<PathBuf>,
+    pub history: History,
+    pub crossing_rules: CrossingRules,
+    pub road_visual: Road<'a>,
+    pub railroad_visual: Railroad<'a>,
+    pub river_visual: River<'a>,
+    pub bridge_visual: Bridge<'a>,
 }
 
 impl<'a> RenderState<'a> {
-    pub(crate) fn new(factory: &'a ID2D1Factory1) -> Self {
+    /// Creates the render state with the map in the file. The example map is
+    /// used when there is no file or the file can not be read, in which case
+    /// the error is shown in a message box owned by the window.
+    pub(crate) fn new(
+        factory: &'a ID2D1Factory1,
+        map_path: Option<PathBuf>,
+        owner: HWND,
+    ) -> Self {
+        let map = match map_path.as_deref().filter(|path| path.exists()) {
+            Some(path) => map_file::load(path).unwrap_or_else(|e| {
+                show_error(owner, &format!("Unable to open {}: {e}", path.display()));
+                Self::example_map()
+            }),
+            None => Self::example_map(),
+        };
+
+        let road_visual = Road::new(factory);
+        let railroad_visual = Railroad::new(factory);
+        let river_visual = River::new(factory);
+        let bridge_visual = Bridge::new(factory);
+
+        RenderState {
+            map,
+            map_path,
+            history: History::default(),
+            crossing_rules: CrossingRules::default(),
+            road_visual,
+            railroad_visual,
+            river_visual,
+            bridge_visual,
+        }
+    }
+
+    /// Creates a map with a road crossing a river and an avenue that widens
+    /// from one to two lanes in each direction. A side street joins the end
+    /// of the road to the avenue.
+    fn example_map() -> Map {
         let mut feature = BezierFeature::new_with_attributes(30.0, false);
         feature.set_ctrl_point(0, Point { x: 10.0, y: 10.0 });
         feature.set_ctrl_point(1, Point { x: 100.0, y: 10.0 });
@@ -65,24 +127,82 @@ impl<'a> RenderState<'a> {
         feature.set_ctrl_point(3, Point { x: 200.0, y: 200.0 });
         feature.add_segment(Point { x: 300.0, y: 300.0 }, Point { x: 300.0, y: 400.0 });
 
-        let mut road_visual = Road::new(factory);
+        let mut river = BezierFeature::new_with_attributes(MOUTH_WIDTH, false);
+        river.set_ctrl_point(0, Point { x: 50.0, y: 350.0 });
+        river.set_ctrl_point(1, Point { x: 150.0, y: 250.0 });
+        river.set_ctrl_point(2, Point { x: 300.0, y: 200.0 });
+        river.set_ctrl_point(3, Point { x: 450.0, y: 250.0 });
+        let channel = Channel::tapered(&river, SOURCE_WIDTH, MOUTH_WIDTH, Flow::Downstream);
 
-        RenderState {
-            hover: None,
-            selected: None,
-            feature_type: BezierFeatureType::Road,
-            feature,
-            road_visual,
+        let mut map = Map::new();
+        let layer = map.layers()[0].id();
+        map.add_feature("River", river, Style::River(channel), layer);
+        let road = map.add_feature("Road", feature, Style::Road(RoadMarkings::default()), layer);
+
+        let mut avenue = BezierFeature::new_with_attributes(30.0, false);
+        avenue.set_ctrl_point(0, Point { x: 50.0, y: 500.0 });
+        avenue.set_ctrl_point(1, Point { x: 150.0, y: 500.0 });
+        avenue.set_ctrl_point(2, Point { x: 200.0, y: 500.0 });
+        avenue.set_ctrl_point(3, Point { x: 300.0, y: 500.0 });
+        avenue.add_segment(Point { x: 450.0, y: 500.0 }, Point { x: 550.0, y: 500.0 });
+        let mut layout = RoadLayout::new(vec![CrossSection::default()]);
+        layout.set_section(
+            1,
+            CrossSection {
+                lanes: 2,
+                median_width: 4.0,
+                sidewalk_width: DEFAULT_SIDEWALK_WIDTH,
+                ..Default::default()
+            },
+        );
+        let avenue = map.add_feature("Avenue", avenue, Style::Lanes(layout), layer);
+
+        let mut side = BezierFeature::new_with_attributes(20.0, false);
+        side.set_ctrl_point(0, Point { x: 300.0, y: 400.0 });
+        side.set_ctrl_point(1, Point { x: 300.0, y: 430.0 });
+        side.set_ctrl_point(2, Point { x: 300.0, y: 470.0 });
+        side.set_ctrl_point(3, Point { x: 300.0, y: 500.0 });
+        let side = map.add_feature("Side Street", side, Style::Road(RoadMarkings::default()), layer);
+        if let (Some(road), Some(avenue), Some(side)) = (road, avenue, side) {
+            let end = map.add_junction(Point { x: 300.0, y: 400.0 });
+            map.attach(end, Approach { feature: road, location: Location::End });
+            map.attach(end, Approach { feature: side, location: Location::Start });
+            let corner = map.add_junction(Point { x: 300.0, y: 500.0 });
+            map.attach(corner, Approach { feature: side, location: Location::End });
+            map.attach(
+                corner,
+                Approach {
+                    feature: avenue,
+                    location: Location::Crossing { segment: 0, t: 1.0 },
+                },
+            );
         }
+        map
     }
 
-    fn in_control_point(&self, x: f32, y: f32) -> Option<usize> {
-        for (idx, ctrl) in self.feature.into_iter().enumerate() {
-            if ctrl.dist_to_xy(x, y) <= RENDER_CTRL_HANDLE_RADIUS {
-                return Some(idx);
+    /// Saves the map to the file it was opened from. A failure is shown in a
+    /// message box owned by the window.
+    fn save(&self, owner: HWND) {
+        if let Some(path) = self.map_path.as_deref() {
+            if let Err(e) = map_file::save(&self.map, path) {
+                show_error(owner, &format!("Unable to save {}: {e}", path.display()));
             }
         }
-        None
+    }
+
+    /// Finds the control point under the mouse on the top most feature
+    fn in_control_point(&mut self, x: f32, y: f32) -> Option<Selection> {
+        self.map
+            .hit_test(&Point { x, y }, RENDER_CTRL_HANDLE_RADIUS)
+            .filter(|hit| hit.ctrl_point.is_some())
+    }
+
+    /// Gets the position of the selected control point
+    fn ctrl_point(&self, selection: &Selection) -> Option<Point> {
+        self.map
+            .feature(selection.feature)?
+            .feature
+            .ctrl_point(selection.ctrl_point?)
     }
 }
 
@@ -90,7 +210,7 @@ pub(crate) struct FeatureWindow<'a> {
     pub(crate) handle: HWND,
     factory: &'a ID2D1Factory1,
     line_style: ID2D1StrokeStyle,
-    ctrl_style: ID2D1StrokeStyle,
+    handle_dash: DashPattern,
     target: Option<ID2D1HwndRenderTarget>,
     line_brush: Option<ID2D1SolidColorBrush>,
     selected_brush: Option<ID2D1SolidColorBrush>,
@@ -101,9 +221,12 @@ pub(crate) struct FeatureWindow<'a> {
 }
 
 impl<'a> FeatureWindow<'a> {
-    pub(crate) fn new(parent: HWND, factory: &'a ID2D1Factory1) -> Result<Box<Self>> {
-        let line_style = create_style(&factory, None)?;
-        let ctrl_style = create_style(&factory, Some(&[4.0, 2.0, 4.0, 2.0]))?;
+    pub(crate) fn new(
+        parent: HWND,
+        factory: &'a ID2D1Factory1,
+        map_path: Option<PathBuf>,
+    ) -> Result<Box<Self>> {
+        let line_style = create_style(&factory)?;
         let instance = unsafe { GetModuleHandleW(None)? };
         // synchronization for a one time initialization of FFI call
         REGISTER_FEATURE_WINDOW_CLASS.call_once(|| {
@@ -124,13 +247,13 @@ impl<'a> FeatureWindow<'a> {
         let mut dpiy = 0.0;
         unsafe { factory.GetDesktopDpi(&mut dpix, &mut dpiy) };
 
-        let render_state = RenderState::new(&factory);
+        let render_state = RenderState::new(&factory, map_path, parent);
         let mut window_internal = Box::new(Self {
             handle: HWND(0),
             render_state,
             factory,
             line_style,
-            ctrl_style,
+            handle_dash: DashPattern::new(&RENDER_CTRL_HANDLE_DASH, 0.0),
             line_brush: None,
             selected_brush: None,
             control_brush: None,
@@ -160,13 +283,55 @@ impl<'a> FeatureWindow<'a> {
         Ok(window_internal)
     }
 
+    /// Changes the type of the selected feature, or of the top most feature
+    /// when no feature is selected
     pub(crate) fn set_feature_type(&mut self, feature_type: BezierFeatureType) {
-        self.render_state.feature_type = feature_type;
+        let state = &mut self.render_state;
+        let map = &state.map;
+        let id = map
+            .selected
+            .map(|s| s.feature)
+            .or_else(|| map.draw_order().last().copied());
+        let style = id
+            .and_then(|id| map.feature(id))
+            .filter(|map_feature| map_feature.feature_type() != feature_type)
+            .map(|map_feature| match feature_type {
+                BezierFeatureType::Railroad => Style::Railroad(Track::scaled(TRACK_SCALE)),
+                BezierFeatureType::River => Style::River(Channel::tapered(
+                    &map_feature.feature,
+                    SOURCE_WIDTH,
+                    MOUTH_WIDTH,
+                    Flow::Downstream,
+                )),
+                _ => Style::new(feature_type, &map_feature.feature),
+            });
+        if let (Some(feature), Some(style)) = (id, style) {
+            state
+                .history
+                .apply(&mut state.map, Edit::SetStyle { feature, style });
+        }
         unsafe {
             InvalidateRect(self.handle, None, false);
         }
     }
 
+    /// Closes the selected feature, or opens it when it is closed. A river is
+    /// closed around the area of a lake and other features into a ring.
+    fn toggle_closure(&mut self) -> Option<FeatureId> {
+        let state = &mut self.render_state;
+        let feature = state.map.selected?.feature;
+        let map_feature = state.map.feature(feature)?;
+        let closure = match (map_feature.feature.closure(), &map_feature.style) {
+            (Closure::Open, Style::River(_)) => Closure::Area,
+            (Closure::Open, _) => Closure::Ring,
+            _ => Closure::Open,
+        };
+        state
+            .history
+            .apply(&mut state.map, Edit::SetClosure { feature, closure })
+            .then_some(feature)
+    }
+
     pub(crate) fn create_render_target(&mut self) -> Result<()> {
         unsafe {
             let mut rect: RECT = RECT::default();
@@ -193,6 +358,9 @@ impl<'a> FeatureWindow<'a> {
 
     fn release_device_resources(&mut self) {
         self.render_state.road_visual.release_resources();
+        self.render_state.railroad_visual.release_resources();
+        self.render_state.river_visual.release_resources();
+        self.render_state.bridge_visual.release_resources();
         self.line_brush = None;
         self.control_brush = None;
         self.selected_brush = None;
@@ -206,6 +374,9 @@ impl<'a> FeatureWindow<'a> {
             let target = self.target.as_ref().unwrap();
             unsafe { target.SetDpi(self.dpi, self.dpi) };
             self.render_state.road_visual.create_resources(target)?;
+            self.render_state.railroad_visual.create_resources(target)?;
+            self.render_state.river_visual.create_resources(target)?;
+            self.render_state.bridge_visual.create_resources(target)?;
             self.control_brush = create_brush(target, 0.25, 0.25, 0.25, 1.0).ok();
             self.line_brush = create_brush(target, 0.0, 0.0, 0.0, 1.0).ok();
             self.selected_brush = create_brush(target, 0.75, 0.0, 0.0, 1.0).ok();
@@ -220,7 +391,6 @@ impl<'a> FeatureWindow<'a> {
     }
 
     fn draw(&mut self) -> Result<()> {
-        let centerline = self.render_state.feature.curve();
         let target = self.target.as_ref().unwrap();
         unsafe {
             target.Clear(Some(&D2D1_COLOR_F {
@@ -230,7 +400,39 @@ impl<'a> FeatureWindow<'a> {
                 a: 1.0,
             }));
         }
-        draw_road(&mut self.render_state.road_visual, &mut self.render_state.feature, self.factory, target);
+        let state = &mut self.render_state;
+        let order = state.map.draw_order();
+        for id in order.iter() {
+            let map_feature = state.map.feature_mut(*id).unwrap();
+            let feature = &mut map_feature.feature;
+            match &map_feature.style {
+                Style::Railroad(track) => state.railroad_visual.draw(track, feature, target)?,
+                Style::Lanes(layout) => state.road_visual.draw_lanes(layout, feature, target)?,
+                Style::River(channel) => state.river_visual.draw(channel, feature, target)?,
+                Style::Road(markings) => {
+                    draw_road(&state.road_visual, markings, feature, self.factory, target)
+                }
+            }
+        }
+        for junction in state.map.junctions() {
+            state.road_visual.draw_junction(junction, &state.map, target)?;
+        }
+        // bridges are drawn above the water of every river
+        for (_, crossing) in state.map.crossings(&state.crossing_rules) {
+            state.bridge_visual.draw(&crossing, target)?;
+        }
+        for id in order.iter() {
+            self.draw_controls(*id);
+        }
+        Ok(())
+    }
+
+    /// Draws the centerline, control points and handles of the feature
+    fn draw_controls(&mut self, id: FeatureId) {
+        let target = self.target.as_ref().unwrap();
+        let hover = self.render_state.map.hover;
+        let feature = &mut self.render_state.map.feature_mut(id).unwrap().feature;
+        let centerline = feature.curve();
         direct2d::draw_line(
             target,
             &centerline,
@@ -243,21 +445,19 @@ impl<'a> FeatureWindow<'a> {
             radiusY: RENDER_CTRL_HANDLE_RADIUS,
             ..Default::default()
         };
-        for (idx, ctrl) in self
-            .render_state
-            .feature
-            .into_iter()
-            .enumerate()
-        {
+        for (idx, ctrl) in feature.into_iter().enumerate() {
             ellipse.point = D2D_POINT_2F {
                 x: ctrl.x,
                 y: ctrl.y,
             };
-            if let Some(select_idx) = self.render_state.hover {
-                if select_idx == idx {
-                    unsafe {
-                        target.FillEllipse(&ellipse, self.selected_brush.as_ref().unwrap());
-                    }
+            if hover
+                == Some(Selection {
+                    feature: id,
+                    ctrl_point: Some(idx),
+                })
+            {
+                unsafe {
+                    target.FillEllipse(&ellipse, self.selected_brush.as_ref().unwrap());
                 }
             }
             unsafe {
@@ -268,28 +468,25 @@ impl<'a> FeatureWindow<'a> {
                     &self.line_style,
                 );
             }
-            let ctrl_brush = self.control_brush.as_ref().unwrap();
-            for segment in self.render_state.feature.segments() {
-                let ctrl_points = segment.ctrl_points();
-                unsafe {
-                    target.DrawLine(
-                        ctrl_points[0].into(),
-                        ctrl_points[1].into(),
-                        ctrl_brush,
-                        1.0,
-                        &self.ctrl_style,
-                    );
-                    target.DrawLine(
-                        ctrl_points[2].into(),
-                        ctrl_points[3].into(),
+        }
+        let ctrl_brush = self.control_brush.as_ref().unwrap();
+        for segment in feature.segments() {
+            let ctrl_points = segment.ctrl_points();
+            for handle in [
+                Polyline::new(vec![ctrl_points[0], ctrl_points[1]]),
+                Polyline::new(vec![ctrl_points[2], ctrl_points[3]]),
+            ] {
+                for dash in self.handle_dash.dash_polyline(&handle) {
+                    direct2d::draw_line(
+                        target,
+                        dash.points(),
                         ctrl_brush,
+                        &self.line_style,
                         1.0,
-                        &self.ctrl_style,
                     );
                 }
             }
         }
-        Ok(())
     }
 
     fn message_handler(&mut self, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
@@ -319,56 +516,49 @@ impl<'a> FeatureWindow<'a> {
                 LRESULT(hresult.0 as isize)
             },
             WM_LBUTTONDOWN => {
+                // take the keyboard focus for the save shortcut
+                unsafe { SetFocus(self.handle) };
                 let (x, y) = mouse_position(lparam);
-                if let Some(idx) = self.render_state.in_control_point(x, y) {
-                    self.render_state.selected = Some(idx);
-                }
+                let map = &mut self.render_state.map;
+                map.selected = map.hit_test(&Point { x, y }, RENDER_CTRL_HANDLE_RADIUS);
                 LRESULT(0)
             }
             WM_LBUTTONUP => {
-                // always deselct any selected control point
-                self.render_state.selected = None;
+                // the drag is one undo step
+                self.render_state.history.end_drag();
+                // the feature stays selected but the control point is released
+                if let Some(selected) = self.render_state.map.selected.as_mut() {
+                    selected.ctrl_point = None;
+                }
                 LRESULT(0)
             }
             WM_MOUSEMOVE => {
                 let (x, y) = mouse_position(lparam);
-                let idx = self.render_state.in_control_point(x, y);
+                let hit = self.render_state.in_control_point(x, y);
                 if wparam.0 == MK_LBUTTON.0 as usize {
-                    if let Some(selected) = self.render_state.selected {
-                        let current = self
-                            .render_state
-                            .feature
-                            .ctrl_point(selected)
-                            .unwrap();
-                        self.render_state
-                        .feature
-                            .set_ctrl_point(selected, Point { x, y });
-                        let top = (current.y.min(y) - RENDER_CTRL_HANDLE_RADIUS) as i32;
-                        let bottom = (current.y.max(y) + RENDER_CTRL_HANDLE_RADIUS) as i32;
-                        let left = (current.x.min(x) - RENDER_CTRL_HANDLE_RADIUS) as i32;
-                        let right = (current.x.max(x) + RENDER_CTRL_HANDLE_RADIUS) as i32;
+                    if let Some(Selection {
+                        feature,
+                        ctrl_point: Some(idx),
+                    }) = self.render_state.map.selected
+                    {
+                        let state = &mut self.render_state;
+                        state
+                            .history
+                            .drag(&mut state.map, feature, idx, Point { x, y });
+                        // the surfaces of other features and any bridges
+                        // may change so the whole window is redrawn
                         unsafe {
-                            InvalidateRect(
-                                self.handle,
-                                Some(&RECT {
-                                    left,
-                                    top,
-                                    right,
-                                    bottom,
-                                }),
-                                false,
-                            );
+                            InvalidateRect(self.handle, None, false);
                         }
                     }
                 }
-                if let Some(idx) = idx {
-                    let ctrl = &self
-                        .render_state
-                        .feature
-                        .ctrl_point(idx)
-                        .unwrap();
-                    if self.render_state.hover.is_none() {
-                        self.render_state.hover = Some(idx);
+                if hit != self.render_state.map.hover {
+                    // redraw the control point that was left and the one entered
+                    let state = &self.render_state;
+                    let left = state.map.hover.and_then(|s| state.ctrl_point(&s));
+                    let entered = hit.and_then(|s| state.ctrl_point(&s));
+                    self.render_state.map.hover = hit;
+                    for ctrl in [left, entered].into_iter().flatten() {
                         unsafe {
                             InvalidateRect(
                                 self.handle,
@@ -382,28 +572,25 @@ impl<'a> FeatureWindow<'a> {
                             );
                         }
                     }
-                } else {
-                    // last state was hover
-                    if let Some(hover) = self.render_state.hover {
-                        let ctrl = &self
-                            .render_state
-                            .feature
-                            .ctrl_point(hover)
-                            .unwrap();
-                        // left the active control point boundary
-                        self.render_state.hover = None;
-                        unsafe {
-                            InvalidateRect(
-                                self.handle,
-                                Some(&RECT {
-                                    left: (ctrl.x - 5.0) as i32,
-                                    top: (ctrl.y - 5.0) as i32,
-                                    right: (ctrl.x + 5.0) as i32,
-                                    bottom: (ctrl.y + 5.0) as i32,
-                                }),
-                                false,
-                            );
-                        }
+                }
+                LRESULT(0)
+            }
+            WM_KEYDOWN => {
+                let control = unsafe { GetKeyState(VK_CONTROL.0 as i32) } < 0;
+                let state = &mut self.render_state;
+                let changed = match char::from_u32(wparam.0 as u32) {
+                    Some('S') if control => {
+                        state.save(self.handle);
+                        None
+                    }
+                    Some('Z') if control => state.history.undo(&mut state.map),
+                    Some('Y') if control => state.history.redo(&mut state.map),
+                    Some('L') if control => self.toggle_closure(),
+                    _ => None,
+                };
+                if changed.is_some() {
+                    unsafe {
+                        InvalidateRect(self.handle, None, false);
                     }
                 }
                 LRESULT(0)
@@ -440,6 +627,13 @@ impl<'a> FeatureWindow<'a> {
     }
 }
 
+/// Shows an error message in a message box owned by the window
+fn show_error(owner: HWND, message: &str) {
+    unsafe {
+        MessageBoxW(owner, &HSTRING::from(message), w!("Bézier Demo"), MB_OK | MB_ICONERROR);
+    }
+}
+
 fn mouse_position(lparam: LPARAM) -> (f32, f32) {
     (
         (lparam.0 & 0x0000_FFFF) as f32,
@@ -448,8 +642,13 @@ fn mouse_position(lparam: LPARAM) -> (f32, f32) {
 }
 
 
-fn draw_road(road: &mut Road, feature: &mut BezierFeature, factory: &ID2D1Factory1, target: &ID2D1HwndRenderTarget) {
-    let centerline = feature.curve();
+fn draw_road(
+    road: &Road,
+    markings: &RoadMarkings,
+    feature: &mut BezierFeature,
+    factory: &ID2D1Factory1,
+    target: &ID2D1HwndRenderTarget,
+) {
     let surface = Some(rebuild_geometry(
             feature,
             factory,
@@ -462,13 +661,25 @@ fn draw_road(road: &mut Road, feature: &mut BezierFeature, factory: &ID2D1Factor
         )
     };
 
-    direct2d::draw_line(
-        &target,
-        &centerline,
-        road.centerline_brush.as_ref().unwrap(),
-        &road.line_style,
-        2.0,
-    );
+    let line_width = markings.line_width;
+    for line in markings.centerline(feature) {
+        direct2d::draw_line(
+            &target,
+            &line.into_points(),
+            road.centerline_brush.as_ref().unwrap(),
+            &road.line_style,
+            line_width,
+        );
+    }
+    for line in markings.edge_lines(feature) {
+        direct2d::draw_line(
+            &target,
+            &line.into_points(),
+            road.edgeline_brush.as_ref().unwrap(),
+            &road.line_style,
+            line_width,
+        );
+    }
 }
 
 pub(crate) fn rebuild_geometry(
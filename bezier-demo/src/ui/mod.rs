@@ -1,7 +1,7 @@
 pub mod direct2d;
 mod feature;
 
-use std::sync::Once;
+use std::{path::PathBuf, sync::Once};
 use windows::{
     core::HSTRING,
     w,
@@ -38,12 +38,14 @@ pub(crate) struct MainWindow<'a> {
     river_rb: Option<HWND>,
     railroad_rb: Option<HWND>,
     factory: &'a ID2D1Factory1,
+    map_path: Option<PathBuf>,
 }
 
 impl<'a> MainWindow<'a> {
     pub(crate) fn new(
         title: &'static str,
         factory: &'a ID2D1Factory1,
+        map_path: Option<PathBuf>,
     ) -> windows::core::Result<Box<Self>> {
         let instance = unsafe { GetModuleHandleW(None)? };
         // synchronization for a one time initialization of FFI call
@@ -68,6 +70,7 @@ impl<'a> MainWindow<'a> {
             river_rb: None,
             railroad_rb: None,
             factory,
+            map_path,
         });
 
         // create the window using Self reference
@@ -110,7 +113,11 @@ impl<'a> MainWindow<'a> {
                 let result = unsafe { GetModuleHandleW(None) };
                 match result {
                     Ok(instance) => {
-                        let feature_wnd = FeatureWindow::new(self.handle, self.factory);
+                        let feature_wnd = FeatureWindow::new(
+                            self.handle,
+                            self.factory,
+                            self.map_path.clone(),
+                        );
                         // TODO manage errors
                         self.feature_wnd = Some(feature_wnd.unwrap());
 
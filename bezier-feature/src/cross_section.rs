@@ -0,0 +1,384 @@
+use geometry::{polygon::Polygon, polyline::Polyline, Point};
+
+use crate::road::RoadMarkings;
+use crate::{BezierFeature, Station};
+
+pub const DEFAULT_LANE_WIDTH: f32 = 12.0;
+pub const DEFAULT_SHOULDER_WIDTH: f32 = 4.0;
+pub const DEFAULT_SIDEWALK_WIDTH: f32 = 6.0;
+/// Length along the centerline over which the cross section changes at a
+/// segment joint
+pub const DEFAULT_TAPER_LENGTH: f32 = 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartKind {
+    Median,
+    Lane,
+    Shoulder,
+    Sidewalk,
+}
+
+/// The side of the centerline looking along the centerline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// The layout of a road across its centerline. The road is symmetric with
+/// the same number of lanes in each direction and the median centered on the
+/// centerline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrossSection {
+    pub lanes: usize,
+    pub lane_width: f32,
+    pub median_width: f32,
+    pub shoulder_width: f32,
+    pub sidewalk_width: f32,
+}
+
+impl Default for CrossSection {
+    fn default() -> Self {
+        CrossSection {
+            lanes: 1,
+            lane_width: DEFAULT_LANE_WIDTH,
+            median_width: 0.0,
+            shoulder_width: DEFAULT_SHOULDER_WIDTH,
+            sidewalk_width: 0.0,
+        }
+    }
+}
+
+impl CrossSection {
+    /// Gets the total width of the road including sidewalks
+    pub fn width(&self) -> f32 {
+        2.0 * self.half_widths(self.lanes).iter().sum::<f32>()
+    }
+
+    /// Gets the width of each part on one side of the road from the
+    /// centerline outwards: half of the median, the lanes, the shoulder and
+    /// the sidewalk. Lanes beyond the number of lanes of this cross section
+    /// have no width.
+    fn half_widths(&self, lanes: usize) -> Vec<f32> {
+        let mut widths = Vec::<f32>::with_capacity(lanes + 3);
+        widths.push(self.median_width / 2.0);
+        widths.extend((0..lanes).map(|i| if i < self.lanes { self.lane_width } else { 0.0 }));
+        widths.push(self.shoulder_width);
+        widths.push(self.sidewalk_width);
+        widths
+    }
+}
+
+/// A part of the road surface on one side of the centerline. Lanes are
+/// numbered from the centerline outwards starting at 0.
+#[derive(Debug, Clone)]
+pub struct RoadPart {
+    pub kind: PartKind,
+    pub side: Side,
+    pub lane: usize,
+    pub surface: Polygon,
+}
+
+/// A road whose cross section is set for each segment of the centerline. A
+/// change of cross section at a joint is spread over the taper length
+/// centered on the joint with a smooth step, so lanes that are added or
+/// dropped widen from or narrow to nothing.
+#[derive(Debug, Clone)]
+pub struct RoadLayout {
+    sections: Vec<CrossSection>,
+    pub taper_length: f32,
+    pub markings: RoadMarkings,
+}
+
+impl RoadLayout {
+    /// Creates a layout with the cross section of each segment. Segments
+    /// without a cross section have the cross section of the last segment
+    /// with a cross section.
+    pub fn new(sections: Vec<CrossSection>) -> Self {
+        assert!(!sections.is_empty());
+        RoadLayout {
+            sections,
+            taper_length: DEFAULT_TAPER_LENGTH,
+            markings: RoadMarkings::default(),
+        }
+    }
+
+    /// Gets the cross section set for each segment
+    pub fn sections(&self) -> &[CrossSection] {
+        &self.sections
+    }
+
+    pub fn section(&self, segment: usize) -> &CrossSection {
+        self.sections
+            .get(segment)
+            .unwrap_or(self.sections.last().unwrap())
+    }
+
+    pub fn set_section(&mut self, segment: usize, section: CrossSection) {
+        if segment >= self.sections.len() {
+            let last = *self.sections.last().unwrap();
+            self.sections.resize(segment + 1, last);
+        }
+        self.sections[segment] = section;
+    }
+
+    /// Gets the surface of each part of the road. Parts that have no width
+    /// anywhere along the road are left out.
+    pub fn parts(&self, feature: &mut BezierFeature) -> Vec<RoadPart> {
+        let stations = feature.stations();
+        let boundaries = self.boundaries(&stations);
+        let lanes = self.max_lanes();
+        let mut parts = Vec::<RoadPart>::new();
+        for side in [Side::Left, Side::Right] {
+            for part in 0..lanes + 3 {
+                let (kind, lane) = match part {
+                    0 => (PartKind::Median, 0),
+                    p if p <= lanes => (PartKind::Lane, p - 1),
+                    p if p == lanes + 1 => (PartKind::Shoulder, 0),
+                    _ => (PartKind::Sidewalk, 0),
+                };
+                let inner = boundary_line(&stations, &boundaries, part, side);
+                let outer = boundary_line(&stations, &boundaries, part + 1, side);
+                let has_width = inner
+                    .iter()
+                    .zip(outer.iter())
+                    .any(|(a, b)| a.distance(b) > f32::EPSILON);
+                if !has_width {
+                    continue;
+                }
+                let surface = outer.into_iter().chain(inner.into_iter().rev()).collect();
+                parts.push(RoadPart {
+                    kind,
+                    side,
+                    lane,
+                    surface,
+                });
+            }
+        }
+        parts
+    }
+
+    /// Gets the painted lines of the road. The lanes are divided by stripes,
+    /// the outer edge of the outer lanes has an edge line when edge lines
+    /// are on and the directions of traffic are divided by the centerline
+    /// style, or by a solid line on either side of a median.
+    pub fn markings(&self, feature: &mut BezierFeature) -> Vec<Polyline> {
+        let stations = feature.stations();
+        let boundaries = self.boundaries(&stations);
+        let lanes = self.max_lanes();
+        let mut lines = Vec::<Polyline>::new();
+
+        let has_median = self.sections.iter().any(|s| s.median_width > 0.0);
+        if has_median {
+            for side in [Side::Left, Side::Right] {
+                lines.push(boundary_line(&stations, &boundaries, 1, side).into());
+            }
+        } else {
+            lines.append(&mut self.markings.centerline(feature));
+        }
+
+        for side in [Side::Left, Side::Right] {
+            // lane dividers between lanes that both have width
+            for lane in 1..lanes {
+                let outer_lane = lane + 1;
+                let line = boundary_line(&stations, &boundaries, outer_lane, side);
+                for run in runs(&line, &boundaries, |b| b[outer_lane] > f32::EPSILON) {
+                    lines.append(&mut self.markings.stripe.dash_polyline(&run));
+                }
+            }
+            if self.markings.edge_lines {
+                lines.push(boundary_line(&stations, &boundaries, lanes + 1, side).into());
+            }
+        }
+        lines
+    }
+
+    fn max_lanes(&self) -> usize {
+        self.sections.iter().map(|s| s.lanes).max().unwrap_or(0)
+    }
+
+    /// Gets the widths of the parts on one side of the road at each station
+    fn boundaries(&self, stations: &[Station]) -> Vec<Vec<f32>> {
+        let lanes = self.max_lanes();
+        // the distance of each joint along the centerline
+        let mut joints = Vec::<f32>::new();
+        for (i, station) in stations.iter().enumerate() {
+            let next = stations.get(i + 1);
+            if next.is_some_and(|next| next.segment != station.segment) {
+                joints.push(station.distance);
+            }
+        }
+
+        let half_taper = self.taper_length / 2.0;
+        let sections: Vec<Vec<f32>> = (0..=joints.len())
+            .map(|segment| self.section(segment).half_widths(lanes))
+            .collect();
+        stations
+            .iter()
+            .map(|station| {
+                // each joint steps from the section before to the section
+                // after over the taper length centered on the joint, so a
+                // station near the joints at both ends of a short segment
+                // blends all three sections
+                let mut widths = sections[0].clone();
+                for (joint, distance) in joints.iter().enumerate() {
+                    let x = ((station.distance - distance + half_taper) / self.taper_length)
+                        .clamp(0.0, 1.0);
+                    let step = x * x * (3.0 - 2.0 * x);
+                    if step <= 0.0 {
+                        break;
+                    }
+                    for (w, (before, after)) in widths
+                        .iter_mut()
+                        .zip(sections[joint].iter().zip(sections[joint + 1].iter()))
+                    {
+                        *w += (after - before) * step;
+                    }
+                }
+                widths
+            })
+            .collect()
+    }
+}
+
+/// Gets the line along the inner edge of the part on one side of the road
+fn boundary_line(
+    stations: &[Station],
+    boundaries: &[Vec<f32>],
+    part: usize,
+    side: Side,
+) -> Vec<Point> {
+    let sign = match side {
+        Side::Left => 1.0,
+        Side::Right => -1.0,
+    };
+    stations
+        .iter()
+        .zip(boundaries.iter())
+        .map(|(station, widths)| {
+            let offset: f32 = widths[..part].iter().sum();
+            station.point + station.normal * (offset * sign)
+        })
+        .collect()
+}
+
+/// Splits a line into the runs of consecutive points where the widths at
+/// the point pass the test
+fn runs<F: Fn(&[f32]) -> bool>(line: &[Point], boundaries: &[Vec<f32>], test: F) -> Vec<Polyline> {
+    let mut runs = Vec::<Polyline>::new();
+    let mut run = Vec::<Point>::new();
+    for (p, widths) in line.iter().zip(boundaries.iter()) {
+        if test(widths) {
+            run.push(*p);
+        } else if !run.is_empty() {
+            runs.push(Polyline::new(std::mem::take(&mut run)));
+        }
+    }
+    if run.len() > 1 {
+        runs.push(Polyline::new(run));
+    }
+    runs.retain(|r| r.len() > 1);
+    runs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{line_feature, straight_feature};
+    const TEST_EPSILON: f32 = 0.01;
+
+    fn two_lanes() -> CrossSection {
+        CrossSection {
+            lanes: 2,
+            median_width: 4.0,
+            sidewalk_width: DEFAULT_SIDEWALK_WIDTH,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_width() {
+        assert!((CrossSection::default().width() - 32.0).abs() < TEST_EPSILON);
+        assert!((two_lanes().width() - 72.0).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_parts() {
+        let mut feature = straight_feature(30.0);
+        let layout = RoadLayout::new(vec![two_lanes()]);
+        let parts = layout.parts(&mut feature);
+        // median, 2 lanes, shoulder and sidewalk on each side
+        assert_eq!(10, parts.len());
+        let area: f32 = parts.iter().map(|p| p.surface.area()).sum();
+        assert!((area - 600.0 * 72.0).abs() < 1.0);
+        let lane = parts
+            .iter()
+            .find(|p| p.kind == PartKind::Lane && p.side == Side::Right && p.lane == 1)
+            .unwrap();
+        let bounds = lane.surface.bounds().unwrap();
+        assert!((bounds.y - 14.0).abs() < TEST_EPSILON);
+        assert!((bounds.height - 12.0).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_taper() {
+        let mut feature = straight_feature(30.0);
+        let layout = RoadLayout::new(vec![CrossSection::default(), two_lanes()]);
+        let parts = layout.parts(&mut feature);
+        // the second lane only exists after the taper
+        let lane = parts
+            .iter()
+            .find(|p| p.kind == PartKind::Lane && p.side == Side::Left && p.lane == 1)
+            .unwrap();
+        // full width after the taper and half width on average in the taper
+        assert!((lane.surface.area() - 12.0 * 300.0).abs() < 30.0);
+        // the outer edge of the road is continuous through the taper
+        let stations = feature.stations();
+        let boundaries = layout.boundaries(&stations);
+        let edge = boundary_line(&stations, &boundaries, 5, Side::Left);
+        for w in edge.windows(2) {
+            assert!((w[1].y - w[0].y).abs() < 5.0);
+        }
+        assert!((edge[0].y + 16.0).abs() < TEST_EPSILON);
+        assert!((edge.last().unwrap().y + 36.0).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_short_taper() {
+        // a middle segment shorter than the taper length
+        let mut feature = line_feature(Point::default(), Point { x: 270.0, y: 0.0 }, 30.0);
+        feature.set_ctrl_point(2, Point { x: 260.0, y: 0.0 });
+        feature.add_segment(Point { x: 285.0, y: 0.0 }, Point { x: 290.0, y: 0.0 });
+        feature.add_segment(Point { x: 400.0, y: 0.0 }, Point { x: 600.0, y: 0.0 });
+        let layout = RoadLayout::new(vec![
+            CrossSection::default(),
+            two_lanes(),
+            CrossSection::default(),
+        ]);
+        let stations = feature.stations();
+        let boundaries = layout.boundaries(&stations);
+        let edge = boundary_line(&stations, &boundaries, 5, Side::Left);
+        // the edge changes no faster than both tapers together and never
+        // leaves the widths of the sections
+        for (w, s) in edge.windows(2).zip(stations.windows(2)) {
+            let run = s[1].distance - s[0].distance;
+            assert!((w[1].y - w[0].y).abs() <= run + TEST_EPSILON);
+        }
+        for p in edge.iter() {
+            assert!(p.y <= -16.0 + TEST_EPSILON && p.y >= -36.0 - TEST_EPSILON);
+        }
+        assert!((edge[0].y + 16.0).abs() < TEST_EPSILON);
+        assert!((edge.last().unwrap().y + 16.0).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_markings() {
+        let mut feature = straight_feature(30.0);
+        let layout = RoadLayout::new(vec![two_lanes()]);
+        let lines = layout.markings(&mut feature);
+        // 2 median lines, 20 dashes for each lane divider and 2 edge lines
+        assert_eq!(44, lines.len());
+        let single = RoadLayout::new(vec![CrossSection::default()]);
+        assert_eq!(3, single.markings(&mut feature).len());
+    }
+}
@@ -0,0 +1,219 @@
+use geometry::{
+    polygon::{FillRule, Polygon},
+    polyline::Polyline,
+    Point,
+};
+
+use crate::{river::River, BezierFeature};
+
+/// Distance a bridge deck extends past each bank of the river
+pub const DEFAULT_BRIDGE_MARGIN: f32 = 10.0;
+/// Widest water crossing that is carried by a culvert instead of a bridge
+pub const DEFAULT_CULVERT_WIDTH: f32 = 15.0;
+
+/// The structure carrying a road over a river
+#[derive(Debug, Clone)]
+pub enum Structure {
+    /// A bridge with a deck that is drawn above the water
+    Bridge { deck: Polygon },
+    /// A culvert under the road, drawn as a marker at the crossing
+    Culvert,
+}
+
+/// A place where a road crosses a river. The start and end are the
+/// distances along the road centerline where it enters and leaves the water.
+#[derive(Debug, Clone)]
+pub struct Crossing {
+    pub structure: Structure,
+    pub start: f32,
+    pub end: f32,
+    /// The middle of the crossing on the road centerline
+    pub position: Point,
+    /// The unit direction of the road at the middle of the crossing
+    pub direction: Point,
+}
+
+impl Crossing {
+    /// Gets the length of road over the water
+    pub fn span(&self) -> f32 {
+        self.end - self.start
+    }
+}
+
+/// The rules for choosing and sizing the structure at each crossing
+#[derive(Debug, Clone, Copy)]
+pub struct CrossingRules {
+    pub bridge_margin: f32,
+    pub culvert_width: f32,
+}
+
+impl Default for CrossingRules {
+    fn default() -> Self {
+        CrossingRules {
+            bridge_margin: DEFAULT_BRIDGE_MARGIN,
+            culvert_width: DEFAULT_CULVERT_WIDTH,
+        }
+    }
+}
+
+impl CrossingRules {
+    /// Finds each place where the road centerline runs over the surface of
+    /// the river. A crossing shorter than the culvert width gets a culvert
+    /// and any other crossing gets a bridge deck the width of the road that
+    /// spans the water and the margin on both banks.
+    pub fn crossings(
+        &self,
+        road: &mut BezierFeature,
+        river_feature: &mut BezierFeature,
+        river: &River,
+    ) -> Vec<Crossing> {
+        let surface = river.surface(river_feature);
+        let centerline = Polyline::from(road.offset(0.0));
+        let length = centerline.length();
+        water_spans(&centerline, &surface)
+            .into_iter()
+            .filter_map(|(start, end)| {
+                let middle = (start + end) / 2.0;
+                let position = centerline.point_at_distance(middle)?;
+                let direction = centerline.tangent_at_distance(middle)?;
+                let structure = if end - start < self.culvert_width {
+                    Structure::Culvert
+                } else {
+                    let deck = centerline.slice(
+                        (start - self.bridge_margin).max(0.0),
+                        (end + self.bridge_margin).min(length),
+                    );
+                    Structure::Bridge {
+                        deck: strip(deck.points(), road.width() / 2.0),
+                    }
+                };
+                Some(Crossing {
+                    structure,
+                    start,
+                    end,
+                    position,
+                    direction,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Gets the distances along the polyline where it enters and leaves the
+/// polygon
+fn water_spans(line: &Polyline, polygon: &Polygon) -> Vec<(f32, f32)> {
+    let points = line.points();
+    if points.len() < 2 || polygon.len() < 3 {
+        return Vec::new();
+    }
+    let mut spans = Vec::<(f32, f32)>::new();
+    let mut entry = polygon
+        .contains(&points[0], FillRule::NonZero)
+        .then_some(0.0);
+    let mut distance = 0.0;
+    for s in points.windows(2) {
+        let d = s[1] - s[0];
+        let len = d.length();
+        // the parameters where the line segment crosses the polygon edges
+        let mut hits: Vec<f32> = polygon
+            .edges()
+            .filter_map(|(a, b)| {
+                let e = *b - *a;
+                let denominator = d.cross(&e);
+                if denominator.abs() < f32::EPSILON {
+                    return None;
+                }
+                let t = (*a - s[0]).cross(&e) / denominator;
+                let u = (*a - s[0]).cross(&d) / denominator;
+                ((0.0..1.0).contains(&t) && (0.0..1.0).contains(&u)).then_some(t)
+            })
+            .collect();
+        hits.sort_by(f32::total_cmp);
+        for t in hits {
+            let at = distance + t * len;
+            match entry.take() {
+                Some(start) => spans.push((start, at)),
+                None => entry = Some(at),
+            }
+        }
+        distance += len;
+    }
+    if let Some(start) = entry {
+        spans.push((start, distance));
+    }
+    spans
+}
+
+/// Gets the polygon covering the distance on both sides of the line
+fn strip(points: &[Point], half_width: f32) -> Polygon {
+    if points.len() < 2 {
+        return Polygon::default();
+    }
+    let mut left = Vec::<Point>::with_capacity(points.len());
+    let mut right = Vec::<Point>::with_capacity(points.len());
+    for (idx, p) in points.iter().enumerate() {
+        let prev = points[idx.saturating_sub(1)];
+        let next = points[(idx + 1).min(points.len() - 1)];
+        let t = (next - prev).normalize();
+        let n = Point { x: t.y, y: -t.x };
+        left.push(*p + n * half_width);
+        right.push(*p - n * half_width);
+    }
+    left.into_iter().chain(right.into_iter().rev()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{line_feature, river::Flow};
+    const TEST_EPSILON: f32 = 0.01;
+
+    /// A road running south across a river flowing east
+    fn features(river_width: f32) -> (BezierFeature, BezierFeature, River) {
+        let road = line_feature(
+            Point { x: 0.0, y: -200.0 },
+            Point { x: 0.0, y: 200.0 },
+            20.0,
+        );
+        let river = line_feature(Point { x: -300.0, y: 0.0 }, Point { x: 300.0, y: 0.0 }, 0.0);
+        (road, river, River::new(vec![river_width], Flow::Downstream))
+    }
+
+    #[test]
+    fn test_bridge() {
+        let (mut road, mut river_feature, river) = features(40.0);
+        let crossings = CrossingRules::default().crossings(&mut road, &mut river_feature, &river);
+        assert_eq!(1, crossings.len());
+        let crossing = &crossings[0];
+        assert!((crossing.start - 180.0).abs() < TEST_EPSILON);
+        assert!((crossing.span() - 40.0).abs() < TEST_EPSILON);
+        assert!(crossing.position.distance(&Point::default()) < TEST_EPSILON);
+        assert!(crossing.direction.distance(&Point { x: 0.0, y: 1.0 }) < TEST_EPSILON);
+        let Structure::Bridge { deck } = &crossing.structure else {
+            panic!("expected a bridge");
+        };
+        // the deck spans the river and a margin on each bank
+        let b = deck.bounds().unwrap();
+        assert!((b.height - 60.0).abs() < TEST_EPSILON);
+        assert!((b.width - 20.0).abs() < TEST_EPSILON);
+        assert!((deck.area() - 1200.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_culvert() {
+        let (mut road, mut river_feature, river) = features(10.0);
+        let crossings = CrossingRules::default().crossings(&mut road, &mut river_feature, &river);
+        assert_eq!(1, crossings.len());
+        assert!(matches!(crossings[0].structure, Structure::Culvert));
+
+        // a road along the bank never crosses
+        let mut road = line_feature(
+            Point { x: -200.0, y: 50.0 },
+            Point { x: 200.0, y: 50.0 },
+            20.0,
+        );
+        assert!(CrossingRules::default()
+            .crossings(&mut road, &mut river_feature, &river)
+            .is_empty());
+    }
+}
@@ -0,0 +1,604 @@
+//! An edit history for the features of a map. Every edit is applied through
+//! the history, which records the state of the feature before and after the
+//! edit so it can be undone and redone exactly. The continuity rules applied
+//! when a control point moves can adjust other handles, so a snapshot is
+//! used rather than the inverse of the edit.
+
+use std::collections::VecDeque;
+
+use geometry::{bezier::Bezier, Point};
+
+use crate::{
+    map::{FeatureId, Map, Style},
+    BezierFeature, Closure,
+};
+
+/// The number of edits kept by default
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// An edit of a feature in a map
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// Moves a control point of the feature
+    MovePoint {
+        feature: FeatureId,
+        ctrl_point: usize,
+        point: Point,
+    },
+    /// Adds a segment to the end of an open feature
+    AddSegment {
+        feature: FeatureId,
+        p2: Point,
+        p3: Point,
+    },
+    /// Removes the last segment of the feature
+    DeleteSegment { feature: FeatureId },
+    /// Changes the width of the feature
+    SetWidth { feature: FeatureId, width: f32 },
+    /// Changes the style of the feature
+    SetStyle { feature: FeatureId, style: Style },
+    /// Inserts a segment before the segment at the index
+    InsertSegment {
+        feature: FeatureId,
+        idx: usize,
+        segment: Bezier,
+    },
+    /// Splits the segment at the parameter t
+    SplitSegment {
+        feature: FeatureId,
+        segment: usize,
+        t: f32,
+    },
+    /// Removes the joint at the end of the segment
+    RemoveJoint { feature: FeatureId, joint: usize },
+    /// Reverses the direction of the feature
+    Reverse { feature: FeatureId },
+    /// Sets how the ends of the feature are joined
+    SetClosure {
+        feature: FeatureId,
+        closure: Closure,
+    },
+}
+
+impl Edit {
+    pub fn feature(&self) -> FeatureId {
+        match self {
+            Edit::MovePoint { feature, .. }
+            | Edit::AddSegment { feature, .. }
+            | Edit::DeleteSegment { feature }
+            | Edit::SetWidth { feature, .. }
+            | Edit::SetStyle { feature, .. }
+            | Edit::InsertSegment { feature, .. }
+            | Edit::SplitSegment { feature, .. }
+            | Edit::RemoveJoint { feature, .. }
+            | Edit::Reverse { feature }
+            | Edit::SetClosure { feature, .. } => *feature,
+        }
+    }
+}
+
+/// The part of a feature changed by an edit. Edits of the nodes change the
+/// style as well, since the widths of a river are set at the nodes.
+#[derive(Debug, Clone)]
+enum State {
+    Feature(BezierFeature),
+    Style(Style),
+    Both(BezierFeature, Style),
+}
+
+/// An applied edit with the state of the feature before and after it
+#[derive(Debug, Clone)]
+struct Record {
+    feature: FeatureId,
+    before: State,
+    after: State,
+    /// The control point dragged by the edit while the drag is in progress
+    drag: Option<usize>,
+}
+
+/// The undo and redo stacks of the edits applied to a map. The oldest edits
+/// are dropped when the history is deeper than its depth.
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: VecDeque<Record>,
+    redo: Vec<Record>,
+    depth: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_DEPTH)
+    }
+}
+
+impl History {
+    pub fn new(depth: usize) -> Self {
+        History {
+            undo: VecDeque::with_capacity(depth),
+            redo: Vec::new(),
+            depth,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Sets the number of edits kept, dropping the oldest edits
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.truncate();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Applies the edit to the map and records it as one undo step. Returns
+    /// false and records nothing if the feature is not in the map or the
+    /// edit can not be applied.
+    pub fn apply(&mut self, map: &mut Map, edit: Edit) -> bool {
+        self.end_drag();
+        match Self::execute(map, &edit) {
+            Some(record) => {
+                map.update_junctions(Some(record.feature));
+                self.push(record);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves a control point as part of a drag. Consecutive moves of the same
+    /// control point are coalesced into one undo step until the drag ends.
+    pub fn drag(
+        &mut self,
+        map: &mut Map,
+        feature: FeatureId,
+        ctrl_point: usize,
+        point: Point,
+    ) -> bool {
+        match self.undo.back_mut() {
+            Some(last) if last.feature == feature && last.drag == Some(ctrl_point) => {
+                // the state before the drag is already recorded, so only the
+                // state after the move is taken
+                let Some(map_feature) = map.feature_mut(feature) else {
+                    return false;
+                };
+                map_feature.feature.set_ctrl_point(ctrl_point, point);
+                last.after = State::Feature(map_feature.feature.clone());
+                map.update_junctions(Some(feature));
+            }
+            _ => {
+                let edit = Edit::MovePoint {
+                    feature,
+                    ctrl_point,
+                    point,
+                };
+                let Some(record) = Self::execute(map, &edit) else {
+                    return false;
+                };
+                map.update_junctions(Some(feature));
+                self.end_drag();
+                self.push(Record {
+                    drag: Some(ctrl_point),
+                    ..record
+                });
+            }
+        }
+        true
+    }
+
+    /// Ends the drag so the next move is a new undo step
+    pub fn end_drag(&mut self) {
+        if let Some(last) = self.undo.back_mut() {
+            last.drag = None;
+        }
+    }
+
+    /// Undoes the last edit and returns the feature it changed. Edits of a
+    /// feature that was removed from the map are discarded.
+    pub fn undo(&mut self, map: &mut Map) -> Option<FeatureId> {
+        self.end_drag();
+        while let Some(record) = self.undo.pop_back() {
+            if Self::restore(map, record.feature, &record.before) {
+                let feature = record.feature;
+                map.update_junctions(Some(feature));
+                self.redo.push(record);
+                return Some(feature);
+            }
+        }
+        None
+    }
+
+    /// Redoes the last undone edit and returns the feature it changed
+    pub fn redo(&mut self, map: &mut Map) -> Option<FeatureId> {
+        while let Some(record) = self.redo.pop() {
+            if Self::restore(map, record.feature, &record.after) {
+                let feature = record.feature;
+                map.update_junctions(Some(feature));
+                self.undo.push_back(record);
+                return Some(feature);
+            }
+        }
+        None
+    }
+
+    fn push(&mut self, record: Record) {
+        self.redo.clear();
+        self.undo.push_back(record);
+        self.truncate();
+    }
+
+    fn truncate(&mut self) {
+        while self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+
+    fn execute(map: &mut Map, edit: &Edit) -> Option<Record> {
+        let map_feature = map.feature_mut(edit.feature())?;
+        let before = match edit {
+            Edit::SetStyle { .. } => State::Style(map_feature.style.clone()),
+            Edit::InsertSegment { .. }
+            | Edit::SplitSegment { .. }
+            | Edit::RemoveJoint { .. }
+            | Edit::Reverse { .. } => {
+                State::Both(map_feature.feature.clone(), map_feature.style.clone())
+            }
+            _ => State::Feature(map_feature.feature.clone()),
+        };
+        let feature = &mut map_feature.feature;
+        let river = match &mut map_feature.style {
+            Style::River(river) => Some(river),
+            _ => None,
+        };
+        match edit {
+            Edit::MovePoint {
+                ctrl_point, point, ..
+            } => {
+                feature.ctrl_point(*ctrl_point)?;
+                feature.set_ctrl_point(*ctrl_point, *point);
+            }
+            Edit::AddSegment { .. } if feature.is_closed() => return None,
+            Edit::AddSegment { p2, p3, .. } => feature.add_segment(*p2, *p3),
+            Edit::DeleteSegment { .. } => {
+                feature.remove_last_segment()?;
+            }
+            Edit::SetWidth { width, .. } => feature.set_width(*width),
+            Edit::SetStyle { style, .. } => map_feature.style = style.clone(),
+            Edit::InsertSegment { idx, segment, .. } => {
+                if *idx > feature.segments().len() {
+                    return None;
+                }
+                feature.insert_segment(*idx, segment);
+                if let Some(r) = river {
+                    r.insert_segment(*idx);
+                }
+            }
+            Edit::SplitSegment { segment, t, .. } => {
+                if *segment >= feature.segments().len() || !(*t > 0.0 && *t < 1.0) {
+                    return None;
+                }
+                feature.split_segment(*segment, *t);
+                if let Some(r) = river {
+                    r.split_segment(*segment, *t);
+                }
+            }
+            Edit::RemoveJoint { joint, .. } => {
+                let joints = feature.node_types().len();
+                if *joint >= joints {
+                    return None;
+                }
+                let closing = feature.is_closed() && *joint + 1 == joints;
+                if !feature.remove_joint(*joint) {
+                    return None;
+                }
+                if let Some(r) = river {
+                    r.remove_joint(*joint, closing);
+                }
+            }
+            Edit::SetClosure { closure, .. } => {
+                // a closed feature has at least two segments
+                if *closure != Closure::Open && feature.segments().len() < 2 {
+                    return None;
+                }
+                feature.set_closure(*closure);
+            }
+            Edit::Reverse { .. } => {
+                feature.reverse();
+                if let Some(r) = river {
+                    r.reverse(feature.segments().len());
+                }
+            }
+        }
+        let after = match before {
+            State::Style(_) => State::Style(map_feature.style.clone()),
+            State::Feature(_) => State::Feature(map_feature.feature.clone()),
+            State::Both(..) => State::Both(map_feature.feature.clone(), map_feature.style.clone()),
+        };
+        Some(Record {
+            feature: edit.feature(),
+            before,
+            after,
+            drag: None,
+        })
+    }
+
+    fn restore(map: &mut Map, feature: FeatureId, state: &State) -> bool {
+        match map.feature_mut(feature) {
+            Some(map_feature) => {
+                match state {
+                    State::Feature(feature) => map_feature.feature = feature.clone(),
+                    State::Style(style) => map_feature.style = style.clone(),
+                    State::Both(feature, style) => {
+                        map_feature.feature = feature.clone();
+                        map_feature.style = style.clone();
+                    }
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        junction::{Approach, Location},
+        line_feature,
+        river::{Flow, River},
+        road::RoadMarkings,
+        BezierFeatureType,
+    };
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn map() -> (Map, FeatureId) {
+        let feature = line_feature(Point::default(), Point { x: 300.0, y: 0.0 }, 20.0);
+        let mut map = Map::new();
+        let layer = map.layers()[0].id();
+        let id = map
+            .add_feature("Road", feature, Style::Road(RoadMarkings::default()), layer)
+            .unwrap();
+        (map, id)
+    }
+
+    fn ctrl_point(map: &Map, feature: FeatureId, idx: usize) -> Point {
+        map.feature(feature)
+            .unwrap()
+            .feature
+            .ctrl_point(idx)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let (mut map, id) = map();
+        let mut history = History::default();
+        assert!(!history.can_undo());
+
+        let edit = Edit::AddSegment {
+            feature: id,
+            p2: Point { x: 500.0, y: 0.0 },
+            p3: Point { x: 600.0, y: 0.0 },
+        };
+        assert!(history.apply(&mut map, edit));
+        let edit = Edit::SetWidth {
+            feature: id,
+            width: 40.0,
+        };
+        assert!(history.apply(&mut map, edit));
+        assert_eq!(2, map.feature(id).unwrap().feature.segments().len());
+
+        assert_eq!(Some(id), history.undo(&mut map));
+        assert!((20.0 - map.feature(id).unwrap().feature.width()).abs() < TEST_EPSILON);
+        assert_eq!(Some(id), history.undo(&mut map));
+        assert_eq!(1, map.feature(id).unwrap().feature.segments().len());
+        assert_eq!(None, history.undo(&mut map));
+
+        assert_eq!(Some(id), history.redo(&mut map));
+        assert_eq!(2, map.feature(id).unwrap().feature.segments().len());
+
+        // a new edit discards the edits that were undone
+        let edit = Edit::DeleteSegment { feature: id };
+        assert!(history.apply(&mut map, edit));
+        assert!(!history.can_redo());
+        assert_eq!(1, map.feature(id).unwrap().feature.segments().len());
+        // the only segment can not be deleted
+        assert!(!history.apply(&mut map, Edit::DeleteSegment { feature: id }));
+
+        let style = Style::new(BezierFeatureType::River, &map.feature(id).unwrap().feature);
+        assert!(history.apply(&mut map, Edit::SetStyle { feature: id, style }));
+        assert_eq!(
+            BezierFeatureType::River,
+            map.feature(id).unwrap().feature_type()
+        );
+        history.undo(&mut map);
+        assert_eq!(
+            BezierFeatureType::Road,
+            map.feature(id).unwrap().feature_type()
+        );
+    }
+
+    #[test]
+    fn test_drag() {
+        let (mut map, id) = map();
+        let mut history = History::default();
+        for x in 0..10 {
+            let point = Point {
+                x: 300.0 + x as f32,
+                y: 50.0,
+            };
+            assert!(history.drag(&mut map, id, 3, point));
+        }
+        history.end_drag();
+        // a second drag of the same point is a new step
+        history.drag(&mut map, id, 3, Point { x: 400.0, y: 0.0 });
+        history.end_drag();
+
+        history.undo(&mut map);
+        let p = ctrl_point(&map, id, 3);
+        assert!((309.0 - p.x).abs() < TEST_EPSILON);
+        assert!((50.0 - p.y).abs() < TEST_EPSILON);
+        history.undo(&mut map);
+        let p = ctrl_point(&map, id, 3);
+        assert!((300.0 - p.x).abs() < TEST_EPSILON);
+        assert!(p.y.abs() < TEST_EPSILON);
+        assert!(!history.can_undo());
+
+        history.redo(&mut map);
+        let p = ctrl_point(&map, id, 3);
+        assert!((309.0 - p.x).abs() < TEST_EPSILON);
+        // a point that is not in the feature is not moved
+        assert!(!history.drag(&mut map, id, 4, Point { x: 0.0, y: 0.0 }));
+    }
+
+    #[test]
+    fn test_junction() {
+        let (mut map, id) = map();
+        let layer = map.layers()[0].id();
+        let feature = line_feature(
+            Point { x: 300.0, y: 0.0 },
+            Point { x: 300.0, y: 300.0 },
+            20.0,
+        );
+        let other = map
+            .add_feature("Road", feature, Style::Road(RoadMarkings::default()), layer)
+            .unwrap();
+        let junction = map.add_junction(Point { x: 300.0, y: 0.0 });
+        for (feature, location) in [(id, Location::End), (other, Location::Start)] {
+            assert!(map.attach(junction, Approach { feature, location }));
+        }
+
+        // the start of the other road follows the dragged end of the road
+        let mut history = History::default();
+        history.drag(&mut map, id, 3, Point { x: 300.0, y: 50.0 });
+        history.drag(&mut map, id, 3, Point { x: 320.0, y: 50.0 });
+        history.end_drag();
+        let p = ctrl_point(&map, other, 0);
+        assert!(p.distance(&Point { x: 320.0, y: 50.0 }) < TEST_EPSILON);
+        assert!(map.junctions()[0].center().distance(&p) < TEST_EPSILON);
+
+        history.undo(&mut map);
+        let p = ctrl_point(&map, other, 0);
+        assert!(p.distance(&Point { x: 300.0, y: 0.0 }) < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_node_edits() {
+        let (mut map, id) = map();
+        let mut history = History::default();
+        let river = River::new(vec![10.0, 30.0], Flow::Downstream);
+        let edit = Edit::SetStyle {
+            feature: id,
+            style: Style::River(river),
+        };
+        history.apply(&mut map, edit);
+        let widths = |map: &Map| match &map.feature(id).unwrap().style {
+            Style::River(r) => r.widths().to_vec(),
+            _ => panic!("not a river"),
+        };
+
+        let edit = Edit::SplitSegment {
+            feature: id,
+            segment: 0,
+            t: 0.5,
+        };
+        assert!(history.apply(&mut map, edit));
+        assert_eq!(2, map.feature(id).unwrap().feature.segments().len());
+        assert_eq!(vec![10.0, 20.0, 30.0], widths(&map));
+        assert!(history.apply(&mut map, Edit::Reverse { feature: id }));
+        assert_eq!(vec![30.0, 20.0, 10.0], widths(&map));
+        let p = ctrl_point(&map, id, 0);
+        assert!((300.0 - p.x).abs() < TEST_EPSILON);
+
+        // the split and the reversal are undone with the widths
+        history.undo(&mut map);
+        history.undo(&mut map);
+        assert_eq!(1, map.feature(id).unwrap().feature.segments().len());
+        assert_eq!(vec![10.0, 30.0], widths(&map));
+
+        history.redo(&mut map);
+        let edit = Edit::RemoveJoint {
+            feature: id,
+            joint: 0,
+        };
+        assert!(history.apply(&mut map, edit));
+        assert_eq!(vec![10.0, 30.0], widths(&map));
+        let edit = Edit::RemoveJoint {
+            feature: id,
+            joint: 0,
+        };
+        assert!(!history.apply(&mut map, edit));
+        let edit = Edit::InsertSegment {
+            feature: id,
+            idx: 3,
+            segment: Bezier::new(0.1),
+        };
+        assert!(!history.apply(&mut map, edit));
+    }
+
+    #[test]
+    fn test_closure() {
+        let (mut map, id) = map();
+        let mut history = History::default();
+        let edit = Edit::SetClosure {
+            feature: id,
+            closure: Closure::Ring,
+        };
+        // a single segment can not be closed
+        assert!(!history.apply(&mut map, edit.clone()));
+        let add = Edit::AddSegment {
+            feature: id,
+            p2: Point { x: 300.0, y: 200.0 },
+            p3: Point { x: 100.0, y: 100.0 },
+        };
+        history.apply(&mut map, add);
+        assert!(history.apply(&mut map, edit));
+        let feature = &map.feature(id).unwrap().feature;
+        assert!(feature.is_closed());
+        assert!(ctrl_point(&map, id, 7).distance(&Point::default()) < TEST_EPSILON);
+
+        history.undo(&mut map);
+        assert!(!map.feature(id).unwrap().feature.is_closed());
+        let p = ctrl_point(&map, id, 7);
+        assert!(p.distance(&Point { x: 100.0, y: 100.0 }) < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_depth() {
+        let (mut map, id) = map();
+        let mut history = History::new(3);
+        for width in 1..=5 {
+            let edit = Edit::SetWidth {
+                feature: id,
+                width: width as f32,
+            };
+            history.apply(&mut map, edit);
+        }
+        let mut undone = 0;
+        while history.undo(&mut map).is_some() {
+            undone += 1;
+        }
+        assert_eq!(3, undone);
+        assert!((2.0 - map.feature(id).unwrap().feature.width()).abs() < TEST_EPSILON);
+
+        history.set_depth(1);
+        assert!(history.can_redo());
+        map.remove_feature(id);
+        // edits of a removed feature are discarded
+        assert_eq!(None, history.redo(&mut map));
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,670 @@
+use geometry::{
+    boolean::{boolean, BooleanOp},
+    polygon::{FillRule, Polygon},
+    Point,
+};
+
+use crate::{
+    map::{FeatureId, Map},
+    BezierFeature,
+};
+
+pub const DEFAULT_FILLET_RADIUS: f32 = 10.0;
+/// Number of straight lines used to draw each fillet arc
+const FILLET_SEGMENTS: usize = 8;
+/// Angle in radians within which two arms are treated as a straight road
+const STRAIGHT_TOLERANCE: f32 = 0.01;
+/// Distance within which a crossing road passes through the center
+const CENTER_TOLERANCE: f32 = 0.01;
+/// Number of points sampled along each segment to find a crossing
+const CROSSING_SAMPLES: usize = 16;
+/// Number of Newton iterations used to refine a crossing
+const CROSSING_ITERATIONS: usize = 8;
+
+/// Where a road meets a junction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Location {
+    /// The first control point of the road
+    Start,
+    /// The last control point of the road
+    End,
+    /// The road crosses the junction at the parameter t of the segment
+    Crossing { segment: usize, t: f32 },
+}
+
+/// A road of a map meeting a junction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Approach {
+    pub feature: FeatureId,
+    pub location: Location,
+}
+
+/// A road leaving the junction in a straight line
+#[derive(Debug, Clone, Copy)]
+struct Arm {
+    direction: Point,
+    half_width: f32,
+}
+
+/// A junction where road ends and crossing roads meet. The junction keeps the
+/// road ends at its center and generates a surface that joins the roads with
+/// fillets at the corners between them.
+#[derive(Debug, Clone)]
+pub struct Junction {
+    center: Point,
+    approaches: Vec<Approach>,
+    pub fillet_radius: f32,
+}
+
+impl Junction {
+    pub fn new(center: Point) -> Self {
+        Junction {
+            center,
+            approaches: Vec::new(),
+            fillet_radius: DEFAULT_FILLET_RADIUS,
+        }
+    }
+
+    /// Creates a junction read from a file without moving its roads
+    pub(crate) fn from_parts(center: Point, approaches: Vec<Approach>, fillet_radius: f32) -> Self {
+        Junction {
+            center,
+            approaches,
+            fillet_radius,
+        }
+    }
+
+    pub fn center(&self) -> Point {
+        self.center
+    }
+
+    pub fn approaches(&self) -> &[Approach] {
+        &self.approaches
+    }
+
+    /// Attaches a road to the junction. A road end is moved to the center of
+    /// the junction and a crossing road moves the junction to the crossing.
+    pub fn attach(&mut self, map: &mut Map, approach: Approach) {
+        self.approaches.push(approach);
+        let moved = match approach.location {
+            Location::Crossing { .. } => Some(approach.feature),
+            _ => None,
+        };
+        self.update(map, moved);
+    }
+
+    /// Detaches every approach of the road from the junction
+    pub fn detach(&mut self, feature: FeatureId) {
+        self.approaches.retain(|a| a.feature != feature);
+    }
+
+    /// Keeps the roads attached after a road has been edited. The junction
+    /// follows the edited road and the other roads follow the junction.
+    /// Without an edited road the junction follows the first crossing road.
+    ///
+    /// When the junction follows a crossing road it moves to where that road
+    /// crosses the next crossing road. Any other crossing road that no longer
+    /// passes through the center has its nearest joint moved to the center,
+    /// and road ends are moved to the center.
+    pub fn update(&mut self, map: &mut Map, moved: Option<FeatureId>) {
+        // a crossing past the last segment of a shortened road moves to its end
+        for approach in self.approaches.iter_mut() {
+            let segments = road(map, approach.feature).map_or(0, |r| r.segments().len());
+            if let Location::Crossing { segment, .. } = approach.location {
+                if segment >= segments && segments > 0 {
+                    approach.location = Location::Crossing {
+                        segment: segments - 1,
+                        t: 1.0,
+                    };
+                }
+            }
+        }
+        let leader = self
+            .approaches
+            .iter()
+            .position(|a| Some(a.feature) == moved)
+            .or_else(|| {
+                self.approaches
+                    .iter()
+                    .position(|a| matches!(a.location, Location::Crossing { .. }))
+            });
+        let Some((leader, lead_road)) =
+            leader.and_then(|leader| Some((leader, road(map, self.approaches[leader].feature)?)))
+        else {
+            self.follow_center(map, None);
+            return;
+        };
+        let lead = self.approaches[leader];
+        self.center = location_point(lead_road, lead.location);
+        if let Location::Crossing { .. } = lead.location {
+            let other = self.approaches.iter().position(|a| {
+                a.feature != lead.feature && matches!(a.location, Location::Crossing { .. })
+            });
+            // a crossing that still passes through the center keeps its place
+            let other = other.and_then(|other| {
+                let a = self.approaches[other];
+                let other_road = road(map, a.feature)?;
+                (location_point(other_road, a.location).distance(&self.center) > CENTER_TOLERANCE)
+                    .then_some((other, other_road))
+            });
+            if let Some((other, other_road)) = other {
+                let crossing = crossing(lead_road, other_road, self.center);
+                if let Some((lead_location, other_location, p)) = crossing {
+                    self.approaches[leader].location = lead_location;
+                    self.approaches[other].location = other_location;
+                    self.center = p;
+                }
+            }
+        }
+        self.follow_center(map, Some(lead.feature));
+    }
+
+    /// Moves every road except the leading road to the center of the junction
+    fn follow_center(&mut self, map: &mut Map, leader: Option<FeatureId>) {
+        let center = self.center;
+        for approach in self.approaches.iter_mut() {
+            if Some(approach.feature) == leader {
+                continue;
+            }
+            let Some(feature) = map
+                .feature_mut(approach.feature)
+                .map(|f| &mut f.feature)
+                .filter(|f| !f.segments().is_empty())
+            else {
+                continue;
+            };
+            match approach.location {
+                Location::Start => move_knot(feature, 0, center),
+                Location::End => move_knot(feature, feature.segments().len(), center),
+                Location::Crossing { .. } => {
+                    if location_point(feature, approach.location).distance(&center)
+                        <= CENTER_TOLERANCE
+                    {
+                        continue;
+                    }
+                    let (location, p) = nearest(feature, center);
+                    approach.location = if p.distance(&center) <= CENTER_TOLERANCE {
+                        location
+                    } else {
+                        let knot = nearest_knot(feature, center);
+                        move_knot(feature, knot, center);
+                        knot_location(feature, knot)
+                    };
+                }
+            }
+        }
+    }
+
+    /// Gets the polygon covering the junction. The sides of the roads are
+    /// joined by a fillet arc in each corner between two roads.
+    pub fn core(&self, map: &Map) -> Polygon {
+        let mut arms = self.arms(map);
+        if arms.len() < 2 {
+            return Polygon::default();
+        }
+        arms.sort_by(|a, b| angle(&a.direction).total_cmp(&angle(&b.direction)));
+        let c = self.center;
+        let r = self.fillet_radius;
+
+        // the corner points between each arm and the next arm
+        let mut corners = Vec::<Vec<Point>>::with_capacity(arms.len());
+        let mut trim = vec![r; arms.len()];
+        for i in 0..arms.len() {
+            let j = (i + 1) % arms.len();
+            let (a, b) = (arms[i], arms[j]);
+            let (pa, pb) = (perp(&a.direction), perp(&b.direction));
+            let turn =
+                (angle(&b.direction) - angle(&a.direction)).rem_euclid(std::f32::consts::TAU);
+            let mut corner = Vec::<Point>::new();
+            if turn < std::f32::consts::PI - STRAIGHT_TOLERANCE {
+                // the fillet circle touches the side of both arms
+                let p1 = c + pa * (a.half_width + r);
+                let p2 = c - pb * (b.half_width + r);
+                if let Some(s) = intersect(p1, a.direction, p2, b.direction) {
+                    let f = p1 + a.direction * s;
+                    let t1 = f - pa * r;
+                    let t2 = f + pb * r;
+                    trim[i] = trim[i].max((t1 - c).dot(&a.direction));
+                    trim[j] = trim[j].max((t2 - c).dot(&b.direction));
+                    let start = angle(&(t1 - f));
+                    let sweep = (angle(&(t2 - f)) - start).rem_euclid(std::f32::consts::TAU)
+                        - std::f32::consts::TAU;
+                    for k in 0..=FILLET_SEGMENTS {
+                        let a = start + sweep * k as f32 / FILLET_SEGMENTS as f32;
+                        corner.push(
+                            f + Point {
+                                x: a.cos() * r,
+                                y: a.sin() * r,
+                            },
+                        );
+                    }
+                }
+            } else if turn > std::f32::consts::PI + STRAIGHT_TOLERANCE {
+                // the outside of a bend, where the sides meet at a point
+                let p1 = c + pa * a.half_width;
+                let p2 = c - pb * b.half_width;
+                if let Some(s) = intersect(p1, a.direction, p2, b.direction) {
+                    corner.push(p1 + a.direction * s);
+                }
+            }
+            corners.push(corner);
+        }
+
+        let mut points = Vec::<Point>::new();
+        for (i, arm) in arms.iter().enumerate() {
+            let p = perp(&arm.direction);
+            let end = c + arm.direction * trim[i];
+            points.push(end - p * arm.half_width);
+            points.push(end + p * arm.half_width);
+            points.extend(corners[i].iter());
+        }
+        Polygon::new(points)
+    }
+
+    /// Gets the surface of the road trimmed at the boundary of the junction
+    pub fn trimmed_surface(&self, map: &mut Map, feature: FeatureId) -> Vec<Polygon> {
+        let core = self.core(map);
+        let Some(road) = map.feature_mut(feature) else {
+            return Vec::new();
+        };
+        let surface: Polygon = road.feature.surface().into_iter().collect();
+        boolean(
+            &[surface],
+            &[core],
+            BooleanOp::Difference,
+            FillRule::NonZero,
+        )
+    }
+
+    /// Gets the merged surface of the junction and every road attached to it
+    pub fn surface(&self, map: &mut Map) -> Vec<Polygon> {
+        let core = self.core(map);
+        let mut roads = Vec::<Polygon>::new();
+        for approach in self.approaches.iter() {
+            if let Some(road) = map.feature_mut(approach.feature) {
+                roads.push(road.feature.surface().into_iter().collect());
+            }
+        }
+        boolean(&roads, &[core], BooleanOp::Union, FillRule::NonZero)
+    }
+
+    /// Gets the roads leaving the junction. A crossing road leaves the
+    /// junction in both directions.
+    fn arms(&self, map: &Map) -> Vec<Arm> {
+        let mut arms = Vec::<Arm>::new();
+        for approach in self.approaches.iter() {
+            let Some(feature) = road(map, approach.feature) else {
+                continue;
+            };
+            let half_width = feature.width() / 2.0;
+            let segments = feature.segments();
+            let mut push = |direction: Point| {
+                arms.push(Arm {
+                    direction,
+                    half_width,
+                })
+            };
+            match approach.location {
+                Location::Start => push(segments[0].tangent(0.0)),
+                Location::End => push(segments.last().unwrap().tangent(1.0) * -1.0),
+                Location::Crossing { segment, t } => {
+                    let tangent = segments[segment].tangent(t);
+                    push(tangent);
+                    push(tangent * -1.0);
+                }
+            }
+        }
+        arms
+    }
+}
+
+/// Gets the centerline of the road in the map if it has any segments
+fn road(map: &Map, feature: FeatureId) -> Option<&BezierFeature> {
+    map.feature(feature)
+        .map(|f| &f.feature)
+        .filter(|f| !f.segments().is_empty())
+}
+
+fn location_point(feature: &BezierFeature, location: Location) -> Point {
+    let segments = feature.segments();
+    match location {
+        Location::Start => segments[0].ctrl_point(0),
+        Location::End => segments.last().unwrap().ctrl_point(3),
+        Location::Crossing { segment, t } => segments[segment].point_at(t),
+    }
+}
+
+/// Gets the location of the joint between two segments, or of an end of
+/// the road. Knot k is at the start of segment k and the last knot is at the
+/// end of the last segment.
+fn knot_location(feature: &BezierFeature, knot: usize) -> Location {
+    let segments = feature.segments().len();
+    if knot < segments {
+        Location::Crossing {
+            segment: knot,
+            t: 0.0,
+        }
+    } else {
+        Location::Crossing {
+            segment: segments - 1,
+            t: 1.0,
+        }
+    }
+}
+
+/// Gets the knot of the road nearest to the point
+fn nearest_knot(feature: &BezierFeature, p: Point) -> usize {
+    let segments = feature.segments();
+    // the last knot of a closed road is the first knot
+    let knots = if feature.is_closed() {
+        segments.len()
+    } else {
+        segments.len() + 1
+    };
+    (0..knots)
+        .min_by(|a, b| {
+            let distance = |k: &usize| {
+                let location = knot_location(feature, *k);
+                location_point(feature, location).distance(&p)
+            };
+            distance(a).total_cmp(&distance(b))
+        })
+        .unwrap()
+}
+
+/// Moves a knot of the road to the point together with its handles, so the
+/// road keeps its direction through the knot. Moving an end of the road
+/// moves its single handle.
+fn move_knot(feature: &mut BezierFeature, knot: usize, to: Point) {
+    let offset = to - location_point(feature, knot_location(feature, knot));
+    if offset.length() <= f32::EPSILON {
+        return;
+    }
+    let closed = feature.is_closed();
+    let segments = feature.mut_segments();
+    let count = segments.len();
+    let before = match knot {
+        0 if closed => Some(count - 1),
+        0 => None,
+        _ => Some(knot - 1),
+    };
+    let after = (knot < count).then_some(knot);
+    if let Some(segment) = before {
+        segments[segment].translate_point(offset.x, offset.y, 2);
+        segments[segment].translate_point(offset.x, offset.y, 3);
+    }
+    if let Some(segment) = after {
+        segments[segment].translate_point(offset.x, offset.y, 0);
+        segments[segment].translate_point(offset.x, offset.y, 1);
+    }
+}
+
+/// Gets the point on the centerline of the road nearest to the point. The
+/// nearest sample is refined with Newton's method.
+fn nearest(feature: &BezierFeature, p: Point) -> (Location, Point) {
+    let mut best = (0, 0.0, f32::MAX);
+    for (segment, b) in feature.segments().iter().enumerate() {
+        for i in 0..=CROSSING_SAMPLES {
+            let t = i as f32 / CROSSING_SAMPLES as f32;
+            let d = b.point_at(t).distance(&p);
+            if d < best.2 {
+                best = (segment, t, d);
+            }
+        }
+    }
+    let (segment, mut t, _) = best;
+    let b = &feature.segments()[segment];
+    for _ in 0..CROSSING_ITERATIONS {
+        // the derivative of the squared distance is zero at the nearest point
+        let r = b.point_at(t) - p;
+        let d1 = b.derivative(t);
+        let slope = d1.dot(&d1) + r.dot(&b.second_derivative(t));
+        if slope.abs() <= f32::EPSILON {
+            break;
+        }
+        t = (t - r.dot(&d1) / slope).clamp(0.0, 1.0);
+    }
+    (Location::Crossing { segment, t }, b.point_at(t))
+}
+
+/// Gets the crossing of the centerlines of two roads nearest to the point.
+/// The crossing of the sampled centerlines is refined with Newton's method.
+fn crossing(a: &BezierFeature, b: &BezierFeature, p: Point) -> Option<(Location, Location, Point)> {
+    let samples = |feature: &BezierFeature| {
+        let mut points = Vec::<(usize, f32, Point)>::new();
+        for (segment, curve) in feature.segments().iter().enumerate() {
+            for i in 0..=CROSSING_SAMPLES {
+                let t = i as f32 / CROSSING_SAMPLES as f32;
+                points.push((segment, t, curve.point_at(t)));
+            }
+        }
+        points
+    };
+    let (sa, sb) = (samples(a), samples(b));
+    let mut best: Option<(usize, f32, usize, f32, f32)> = None;
+    for ea in sa.windows(2).filter(|e| e[0].0 == e[1].0) {
+        for eb in sb.windows(2).filter(|e| e[0].0 == e[1].0) {
+            let (da, db) = (ea[1].2 - ea[0].2, eb[1].2 - eb[0].2);
+            let denominator = da.cross(&db);
+            if denominator.abs() <= f32::EPSILON {
+                continue;
+            }
+            let q = eb[0].2 - ea[0].2;
+            let (s, u) = (q.cross(&db) / denominator, q.cross(&da) / denominator);
+            if !(0.0..=1.0).contains(&s) || !(0.0..=1.0).contains(&u) {
+                continue;
+            }
+            let d = (ea[0].2 + da * s).distance(&p);
+            if best.is_none_or(|best| d < best.4) {
+                let ta = ea[0].1 + (ea[1].1 - ea[0].1) * s;
+                let tb = eb[0].1 + (eb[1].1 - eb[0].1) * u;
+                best = Some((ea[0].0, ta, eb[0].0, tb, d));
+            }
+        }
+    }
+    let (segment_a, mut ta, segment_b, mut tb, _) = best?;
+    let (ca, cb) = (&a.segments()[segment_a], &b.segments()[segment_b]);
+    for _ in 0..CROSSING_ITERATIONS {
+        // solve a'(ta) dta - b'(tb) dtb = b(tb) - a(ta)
+        let r = cb.point_at(tb) - ca.point_at(ta);
+        let (d1, d2) = (ca.derivative(ta), cb.derivative(tb) * -1.0);
+        let denominator = d1.cross(&d2);
+        if denominator.abs() <= f32::EPSILON {
+            break;
+        }
+        ta = (ta + r.cross(&d2) / denominator).clamp(0.0, 1.0);
+        tb = (tb + d1.cross(&r) / denominator).clamp(0.0, 1.0);
+    }
+    Some((
+        Location::Crossing {
+            segment: segment_a,
+            t: ta,
+        },
+        Location::Crossing {
+            segment: segment_b,
+            t: tb,
+        },
+        ca.point_at(ta),
+    ))
+}
+
+fn angle(p: &Point) -> f32 {
+    p.y.atan2(p.x)
+}
+
+/// Gets the vector rotated a quarter turn counterclockwise
+fn perp(p: &Point) -> Point {
+    Point { x: -p.y, y: p.x }
+}
+
+/// Gets the distance along the first line to its intersection with the second
+fn intersect(p1: Point, d1: Point, p2: Point, d2: Point) -> Option<f32> {
+    let denominator = d1.cross(&d2);
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+    Some((p2 - p1).cross(&d2) / denominator)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{line_feature, map::Style, road::RoadMarkings};
+    const TEST_EPSILON: f32 = 0.01;
+
+    /// Adds a straight road from the point in the direction with the length
+    /// 300 to the map
+    fn road(map: &mut Map, start: Point, direction: Point) -> FeatureId {
+        let layer = map.layers()[0].id();
+        let feature = line_feature(start, start + direction * 300.0, 20.0);
+        map.add_feature("road", feature, Style::Road(RoadMarkings::default()), layer)
+            .unwrap()
+    }
+
+    fn ctrl_point(map: &Map, feature: FeatureId, idx: usize) -> Point {
+        map.feature(feature)
+            .unwrap()
+            .feature
+            .ctrl_point(idx)
+            .unwrap()
+    }
+
+    fn translate(map: &mut Map, feature: FeatureId, offset: Point) {
+        let feature = &mut map.feature_mut(feature).unwrap().feature;
+        for idx in 0..4 {
+            let p = feature.ctrl_point(idx).unwrap();
+            feature.set_ctrl_point(idx, p + offset);
+        }
+    }
+
+    fn tee() -> (Map, Vec<FeatureId>, Junction) {
+        let east = Point { x: 1.0, y: 0.0 };
+        let north = Point { x: 0.0, y: -1.0 };
+        let mut map = Map::new();
+        let roads = vec![
+            road(&mut map, Point::default(), east),
+            road(&mut map, Point::default(), east * -1.0),
+            road(&mut map, Point { x: 0.0, y: 300.0 }, north),
+        ];
+        let mut junction = Junction::new(Point::default());
+        let locations = [Location::Start, Location::Start, Location::End];
+        for (feature, location) in roads.iter().zip(locations) {
+            junction.attach(
+                &mut map,
+                Approach {
+                    feature: *feature,
+                    location,
+                },
+            );
+        }
+        (map, roads, junction)
+    }
+
+    #[test]
+    fn test_core() {
+        let (map, _, junction) = tee();
+        let core = junction.core(&map);
+        // two fillets of 9 points and 2 points for each of the 3 roads
+        assert_eq!(24, core.len());
+        let b = core.bounds().unwrap();
+        // the fillets reach the radius beyond the side of the road
+        assert!((b.height - 30.0).abs() < TEST_EPSILON);
+        assert!((b.width - 40.0).abs() < TEST_EPSILON);
+        // the area is the square where the roads cross plus the fillets
+        let fillet = (1.0 - std::f32::consts::FRAC_PI_4) * 100.0;
+        let expected = 20.0 * 40.0 + 10.0 * 20.0 + 2.0 * fillet;
+        // the fillet arcs are drawn with chords, so the area is slightly larger
+        assert!((core.area() - expected).abs() < 2.0);
+    }
+
+    #[test]
+    fn test_surface() {
+        let (mut map, roads, junction) = tee();
+        let surface = junction.surface(&mut map);
+        assert_eq!(1, surface.len());
+        let trimmed = junction.trimmed_surface(&mut map, roads[2]);
+        assert_eq!(1, trimmed.len());
+        // the road to the south is trimmed at the end of the fillets
+        assert!((trimmed[0].area() - 20.0 * 280.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_attached() {
+        let (mut map, roads, mut junction) = tee();
+        // drag the start of the east road and the other roads follow
+        let p = Point { x: 5.0, y: 5.0 };
+        map.feature_mut(roads[0])
+            .unwrap()
+            .feature
+            .set_ctrl_point(0, p);
+        junction.update(&mut map, Some(roads[0]));
+        assert!(junction.center().distance(&p) < TEST_EPSILON);
+        assert!(ctrl_point(&map, roads[1], 0).distance(&p) < TEST_EPSILON);
+        assert!(ctrl_point(&map, roads[2], 3).distance(&p) < TEST_EPSILON);
+        // the handle moves with the end
+        let h = ctrl_point(&map, roads[2], 2);
+        assert!(h.distance(&Point { x: 5.0, y: 105.0 }) < TEST_EPSILON);
+
+        // a removed road is skipped
+        map.remove_feature(roads[1]);
+        junction.update(&mut map, Some(roads[0]));
+        assert_eq!(2, junction.arms(&map).len());
+    }
+
+    #[test]
+    fn test_crossing() {
+        let mut map = Map::new();
+        let roads = [
+            road(
+                &mut map,
+                Point { x: -150.0, y: 0.0 },
+                Point { x: 1.0, y: 0.0 },
+            ),
+            road(
+                &mut map,
+                Point { x: 0.0, y: -150.0 },
+                Point { x: 0.0, y: 1.0 },
+            ),
+        ];
+        let mut junction = Junction::new(Point::default());
+        for feature in roads {
+            junction.attach(
+                &mut map,
+                Approach {
+                    feature,
+                    location: Location::Crossing { segment: 0, t: 0.5 },
+                },
+            );
+        }
+        let on_center = |map: &Map, junction: &Junction| {
+            junction.approaches().iter().all(|a| {
+                let feature = &map.feature(a.feature).unwrap().feature;
+                location_point(feature, a.location).distance(&junction.center()) < TEST_EPSILON
+            })
+        };
+        assert!(on_center(&map, &junction));
+
+        // drag the east west road south and the junction slides along the
+        // north south road
+        translate(&mut map, roads[0], Point { x: 0.0, y: 30.0 });
+        junction.update(&mut map, Some(roads[0]));
+        assert!(junction.center().distance(&Point { x: 0.0, y: 30.0 }) < TEST_EPSILON);
+        assert!(on_center(&map, &junction));
+        assert!(ctrl_point(&map, roads[1], 0).x.abs() < TEST_EPSILON);
+
+        // drag the north south road east and the junction slides along the
+        // east west road
+        translate(&mut map, roads[1], Point { x: 40.0, y: 0.0 });
+        junction.update(&mut map, Some(roads[1]));
+        assert!(junction.center().distance(&Point { x: 40.0, y: 30.0 }) < TEST_EPSILON);
+        assert!(on_center(&map, &junction));
+
+        // drag the north south road clear of the east west road and the
+        // nearest end of the east west road follows the junction
+        translate(&mut map, roads[1], Point { x: 200.0, y: 0.0 });
+        junction.update(&mut map, Some(roads[1]));
+        assert!(on_center(&map, &junction));
+        let end = ctrl_point(&map, roads[0], 3);
+        assert!(end.distance(&junction.center()) < TEST_EPSILON);
+    }
+}
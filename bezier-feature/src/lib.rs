@@ -0,0 +1,1171 @@
+pub mod cross_section;
+pub mod crossing;
+pub mod history;
+pub mod junction;
+pub mod map;
+pub mod map_file;
+pub mod network;
+pub mod railroad;
+pub mod river;
+pub mod road;
+pub mod validation;
+
+use geometry::{
+    bezier::Bezier,
+    clothoid::Clothoid,
+    continuity::{self, Continuity, JointSide, NodeType},
+    mesh::Mesh,
+    polygon::{FillRule, Polygon},
+    polyline::Polyline,
+    ribbon, triangulate, Point,
+};
+
+const DEFAULT_RESOLUTION: f32 = 0.025;
+
+const DEFAULT_WIDTH: f32 = 30.0;
+
+/// Maximum distance between a clothoid and its Bézier approximation
+const CLOTHOID_TOLERANCE: f32 = 0.25;
+
+/// Largest gap between the edges of consecutive segments that is not joined
+const SEAM_TOLERANCE: f32 = 0.01;
+
+/// Largest angle between the points of the round join at a seam
+const JOIN_ANGLE: f32 = 0.2;
+
+/// Longest loop trimmed from an edge as a multiple of the offset distance.
+/// The loop on the inside of a joint grows with the angle of the joint so
+/// this allows for joints that turn by up to about 160 degrees.
+const MAX_LOOP_FACTOR: f32 = 4.0 * std::f32::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BezierFeatureType {
+    Road,
+    River,
+    Railroad,
+}
+
+/// How the ends of a feature are joined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Closure {
+    /// The feature has a free start and end
+    #[default]
+    Open,
+    /// The last segment joins the first and the surface is the ring between
+    /// the edges, such as a ring road
+    Ring,
+    /// The last segment joins the first and the surface is the area inside
+    /// the outer edge, such as a lake or an island
+    Area,
+}
+
+#[derive(Debug, Clone)]
+pub struct BezierFeature {
+    resolution: f32,
+    pub centerline: Vec<Bezier>,
+    edge_curve: Vec<[Vec<Point>; 2]>,
+    /// The left and right edges of the surface joined at the seams between
+    /// segments and with the loops on tight bends trimmed
+    edges: [Vec<Point>; 2],
+    /// The node type of each joint between two segments. The joint between
+    /// the last and the first segment of a closed feature is the last node.
+    nodes: Vec<NodeType>,
+    closure: Closure,
+    width: f32,
+    edgeline_visible: bool,
+}
+
+impl BezierFeature {
+    pub fn new() -> Self {
+        Self::new_with_attributes(DEFAULT_WIDTH, false)
+    }
+
+    pub fn new_with_attributes(width: f32, edgeline_visible: bool) -> Self {
+        let b = Bezier::new_with_ctrl_point(
+            [
+                Point { x: 10.0, y: 10.0 },
+                Point { x: 50.0, y: 10.0 },
+                Point { x: 100.0, y: 10.0 },
+                Point { x: 150.0, y: 100.0 },
+            ],
+            DEFAULT_RESOLUTION,
+        );
+
+        let r0 = Vec::<Point>::new();
+        let r_pi = Vec::<Point>::new();
+        let edge_curve = vec![[r0, r_pi]];
+
+        let mut road = BezierFeature {
+            resolution: DEFAULT_RESOLUTION,
+            centerline: Vec::<Bezier>::new(),
+            edge_curve,
+            edges: [Vec::<Point>::new(), Vec::<Point>::new()],
+            nodes: Vec::<NodeType>::new(),
+            closure: Closure::Open,
+            width,
+            edgeline_visible,
+        };
+        road.centerline.push(b);
+        road
+    }
+
+    /// Creates a feature from an existing compound Bézier curve such as the
+    /// segments created by the interpolating splines in `geometry::spline`.
+    /// The segments must share their end and start control points. The node
+    /// type of each joint is the strongest type the joint already satisfies.
+    pub fn new_with_segments(segments: Vec<Bezier>, width: f32) -> Self {
+        assert!(!segments.is_empty());
+        let nodes = continuity::analyze(&segments)
+            .iter()
+            .map(NodeType::from_continuity)
+            .collect();
+        let mut edge_curve = Vec::<[Vec<Point>; 2]>::with_capacity(segments.len());
+        for _ in 0..segments.len() {
+            edge_curve.push([Vec::<Point>::new(), Vec::<Point>::new()]);
+        }
+
+        let mut feature = BezierFeature {
+            resolution: DEFAULT_RESOLUTION,
+            nodes,
+            centerline: segments,
+            closure: Closure::Open,
+            edge_curve,
+            edges: [Vec::<Point>::new(), Vec::<Point>::new()],
+            width,
+            edgeline_visible: false,
+        };
+        feature.set_resolution(DEFAULT_RESOLUTION);
+        feature
+    }
+
+    pub fn modified(&self) -> bool {
+        self.centerline.iter().any(|b| b.modified())
+    }
+
+    pub fn segments(&self) -> &Vec<Bezier> {
+        &self.centerline
+    }
+
+    pub fn mut_segments(&mut self) -> &mut Vec<Bezier> {
+        &mut self.centerline
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Sets the width of the feature and recalculates the edges
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width;
+        for idx in 0..self.centerline.len() {
+            self.edge_curve[idx] = self.calc_edge_curve(idx);
+        }
+    }
+
+    pub fn edgeline_visible(&self) -> bool {
+        self.edgeline_visible
+    }
+
+    pub fn resolution(&self) -> f32 {
+        self.resolution
+    }
+
+    pub fn set_resolution(&mut self, resolution: f32) {
+        self.resolution = resolution;
+        for b in self.centerline.iter_mut() {
+            b.set_resolution(resolution);
+        }
+    }
+
+    pub fn curve(&mut self) -> Vec<Point> {
+        self.update_edges();
+        self.centerline
+            .iter_mut()
+            .flat_map(|b| b.curve())
+            .copied()
+            .collect()
+    }
+
+    /// Recalculates the edges of the modified segments. Flattening a segment
+    /// clears its modified flag so this runs before any segment is flattened.
+    fn update_edges(&mut self) {
+        let recalculate: Vec<bool> = self.centerline.iter().map(|b| b.modified()).collect();
+        for (idx, r) in recalculate.iter().enumerate() {
+            if *r {
+                self.centerline[idx].curve();
+                self.edge_curve[idx] = self.calc_edge_curve(idx);
+            }
+        }
+    }
+
+    /// Gets the polygon path representing the surface of the road feature
+    pub fn surface(&mut self) -> Vec<&geometry::Point> {
+        self.update_edges();
+        let half_width = self.width / 2.0;
+        let left = self.join_edge(self.edge_curve.iter().map(|v| v[0].as_slice()), half_width);
+        let right = self.join_edge(self.edge_curve.iter().map(|v| v[1].as_slice()), -half_width);
+        self.edges = [left, right];
+
+        if self.closure == Closure::Area {
+            return self.outer_edge();
+        }
+        let mut points_pi2: Vec<&geometry::Point> = self.edges[0].iter().collect();
+        let mut points_2pi: Vec<&geometry::Point> = self.edges[1].iter().rev().collect();
+
+        let mut polygon = Vec::<&geometry::Point>::with_capacity(points_2pi.len() * 2);
+        polygon.append(&mut points_pi2);
+        polygon.append(&mut points_2pi);
+
+        polygon
+    }
+
+    /// Gets the edge that encloses the area of a closed feature
+    fn outer_edge(&self) -> Vec<&geometry::Point> {
+        let edge = |side: usize| -> Vec<&geometry::Point> { self.edges[side].iter().collect() };
+        let area = |points: &Vec<&geometry::Point>| -> f32 {
+            points.iter().copied().collect::<Polygon>().area()
+        };
+        let (left, right) = (edge(0), edge(1));
+        if area(&left) >= area(&right) {
+            left
+        } else {
+            right
+        }
+    }
+
+    /// Gets the centerline of the feature offset by the distance. A positive
+    /// distance offsets to the left of the centerline and a negative distance
+    /// to the right. The joint between two segments appears once. The
+    /// offsets of the segments are joined and trimmed like the edges of the
+    /// surface.
+    pub fn offset(&mut self, distance: f32) -> Vec<Point> {
+        self.update_edges();
+        let curves: Vec<Vec<Point>> = self
+            .centerline
+            .iter_mut()
+            .map(|b| b.offset_curve(distance))
+            .collect();
+        self.join_edge(curves.iter().map(|c| c.as_slice()), distance)
+    }
+
+    /// Joins the offset curves of the segments into one edge. The end normal
+    /// of a segment and the start normal of the next differ at a joint that
+    /// is not smooth, so a round join fills the gap on the outside of the
+    /// joint. The edges on the inside of the joint, and the inner edge of a
+    /// bend tighter than the offset distance, fold back and cross. The loop
+    /// between the crossings is trimmed.
+    fn join_edge<'a>(
+        &self,
+        curves: impl Iterator<Item = &'a [Point]>,
+        distance: f32,
+    ) -> Vec<Point> {
+        let mut points = Vec::<Point>::new();
+        let mut middle = 0;
+        for (segment, curve) in curves.enumerate() {
+            let mut skip = 0;
+            match (points.last().copied(), curve.first()) {
+                (Some(last), Some(first)) => {
+                    let joint = self.centerline[segment].ctrl_point(0);
+                    points.extend(round_join(joint, last, *first));
+                    // a joined seam has the point once
+                    if last.distance(first) <= SEAM_TOLERANCE {
+                        skip = 1;
+                    }
+                }
+                _ => middle = curve.len() / 2,
+            }
+            points.extend(curve.iter().skip(skip));
+        }
+        let max_loop = MAX_LOOP_FACTOR * distance.abs();
+        if !self.is_closed() || points.is_empty() {
+            return Polyline::from(points).trim_loops(max_loop).into_points();
+        }
+        // join the seam that closes the feature and start in the middle of
+        // the first segment so the loops at the closing seam are trimmed
+        let (first, last) = (points[0], points[points.len() - 1]);
+        let joint = self.centerline[0].ctrl_point(0);
+        if last.distance(&first) <= SEAM_TOLERANCE {
+            points.pop();
+        }
+        points.extend(round_join(joint, last, first));
+        points.rotate_left(middle);
+        let mut edge = Polyline::from(points).trim_loops(max_loop).into_points();
+        edge.push(edge[0]);
+        edge
+    }
+
+    /// Gets a station for each point of the centerline. The joint between two
+    /// segments appears once as the last station of the first segment.
+    pub fn stations(&mut self) -> Vec<Station> {
+        self.update_edges();
+        let mut stations = Vec::<Station>::new();
+        let mut distance = 0.0;
+        for (segment, b) in self.centerline.iter_mut().enumerate() {
+            let skip = if segment == 0 { 0 } else { 1 };
+            let params = b.params();
+            let normals: Vec<Point> = params.iter().map(|t| b.normal(*t)).collect();
+            let curve = b.curve();
+            for i in skip..curve.len() {
+                if let Some(prev) = stations.last() {
+                    distance += prev.point.distance(&curve[i]);
+                }
+                stations.push(Station {
+                    point: curve[i],
+                    normal: normals[i],
+                    distance,
+                    segment,
+                    t: params[i],
+                });
+            }
+        }
+        stations
+    }
+
+    /// Tests if the point is on the surface of the feature using the same
+    /// winding fill rule as the rendered surface geometry.
+    pub fn contains(&mut self, p: &Point) -> bool {
+        let surface: Polygon = self.surface().into_iter().collect();
+        surface.contains(p, FillRule::NonZero)
+    }
+
+    /// Gets the surface of the feature as an indexed triangle mesh for
+    /// export or software rendering.
+    pub fn surface_mesh(&mut self) -> Mesh {
+        let surface: Polygon = self.surface().into_iter().collect();
+        triangulate::triangulate_contours(&[surface], FillRule::NonZero)
+    }
+
+    /// Gets the surface of the feature as a ribbon mesh with texture
+    /// coordinates that repeat every texture length along the centerline.
+    pub fn ribbon_mesh(&mut self, texture_length: f32) -> Mesh {
+        self.update_edges();
+        ribbon::ribbon(&mut self.centerline, self.width, texture_length)
+    }
+
+    fn calc_edge_curve(&mut self, idx: usize) -> [Vec<Point>; 2] {
+        let width = self.width / 2.0;
+        let b = &mut self.centerline[idx];
+        [b.offset_curve(width), b.offset_curve(-width)]
+    }
+
+    /// Adds a new Bézier segment to an existing feature. Control points 0 and
+    /// 1 are control points 3 and control point 2 reflected around control
+    /// point 3 of the last segment currently in the feature. The feature
+    /// must be open.
+    pub fn add_segment(&mut self, p2: Point, p3: Point) {
+        assert!(!self.is_closed());
+        let p1 = self.centerline.last().unwrap().ctrl_point(2);
+        let p0 = self.centerline.last().unwrap().ctrl_point(3);
+
+        let b = Bezier::new_with_ctrl_point([p0, p1.reflect(p0), p2, p3], self.resolution);
+        self.centerline.push(b);
+        self.nodes.push(NodeType::default());
+
+        let r0 = Vec::<Point>::new();
+        let r_pi = Vec::<Point>::new();
+        self.edge_curve.push([r0, r_pi]);
+    }
+
+    /// Removes the last segment of an open feature. A feature always keeps
+    /// at least one segment so the only segment is not removed.
+    pub fn remove_last_segment(&mut self) -> Option<Bezier> {
+        if self.centerline.len() < 2 || self.is_closed() {
+            return None;
+        }
+        self.nodes.pop();
+        self.edge_curve.pop();
+        self.centerline.pop()
+    }
+
+    /// Inserts a segment before the segment at the index. An index equal to
+    /// the number of segments adds the segment to the end. The start of the
+    /// segment is moved to the end of the previous segment and the start of
+    /// the following segment is moved with its handle to the end of the
+    /// segment. The node type of each new joint is the strongest type the
+    /// joint satisfies.
+    pub fn insert_segment(&mut self, idx: usize, segment: &Bezier) {
+        assert!(idx <= self.centerline.len());
+        let mut segment = Bezier::new_with_ctrl_point(*segment.ctrl_points(), self.resolution);
+        if let Some(joint) = self.joint_before(idx) {
+            segment.set_ctrl_point(self.centerline[joint].ctrl_point(3), 0);
+        }
+        // the segment after the last segment of a closed feature is the first
+        let next = match self.is_closed() {
+            true => idx % self.centerline.len(),
+            false => idx,
+        };
+        if let Some(next) = self.centerline.get_mut(next) {
+            let offset = segment.ctrl_point(3) - next.ctrl_point(0);
+            next.translate_point(offset.x, offset.y, 0);
+            next.translate_point(offset.x, offset.y, 1);
+        }
+        self.centerline.insert(idx, segment);
+        self.edge_curve
+            .insert(idx, [Vec::<Point>::new(), Vec::<Point>::new()]);
+        self.nodes
+            .insert(idx.min(self.nodes.len()), NodeType::Corner);
+
+        let continuity = self.continuity();
+        for joint in [self.joint_before(idx), self.joint_after(idx)]
+            .into_iter()
+            .flatten()
+        {
+            self.nodes[joint] = NodeType::from_continuity(&continuity[joint]);
+        }
+    }
+
+    /// Splits the segment at the parameter t into two segments with the same
+    /// shape. The new joint is a curvature continuous node.
+    pub fn split_segment(&mut self, segment: usize, t: f32) {
+        assert!(segment < self.centerline.len());
+        assert!(t > 0.0 && t < 1.0);
+        let (head, tail) = self.centerline[segment].split(t);
+        self.centerline[segment] = head;
+        self.centerline.insert(segment + 1, tail);
+        self.edge_curve
+            .insert(segment, [Vec::<Point>::new(), Vec::<Point>::new()]);
+        self.nodes.insert(segment, NodeType::Curvature);
+    }
+
+    /// Removes the joint at the end of the segment and merges the segments
+    /// on either side into one segment that approximates both. The merged
+    /// segment keeps the outer end points and tangent directions. Its handles
+    /// are scaled by the ratio of the handles at the removed joint, which
+    /// restores a segment that was split. The neighbouring handles are
+    /// adjusted to satisfy the node types of the remaining joints.
+    ///
+    /// A closed feature keeps at least two segments, so false is returned
+    /// and the feature is unchanged when a joint of a closed feature with two
+    /// segments is removed. Removing the joint between the last and the first
+    /// segment moves the start of a closed feature to the following joint.
+    pub fn remove_joint(&mut self, mut joint: usize) -> bool {
+        assert!(joint < self.nodes.len());
+        if self.is_closed() {
+            if self.centerline.len() < 3 {
+                return false;
+            }
+            if joint + 1 == self.nodes.len() {
+                self.centerline.rotate_left(1);
+                self.edge_curve.rotate_left(1);
+                self.nodes.rotate_left(1);
+                joint -= 1;
+            }
+        }
+        let a = self.centerline[joint].ctrl_points();
+        let b = self.centerline[joint + 1].ctrl_points();
+        let incoming = a[3].distance(&a[2]);
+        let outgoing = b[0].distance(&b[1]);
+        let t = if incoming > f32::EPSILON && outgoing > f32::EPSILON {
+            incoming / (incoming + outgoing)
+        } else {
+            0.5
+        };
+        let merged = Bezier::new_with_ctrl_point(
+            [
+                a[0],
+                a[0] + (a[1] - a[0]) * (1.0 / t),
+                b[3] + (b[2] - b[3]) * (1.0 / (1.0 - t)),
+                b[3],
+            ],
+            self.resolution,
+        );
+        self.centerline[joint] = merged;
+        self.centerline.remove(joint + 1);
+        self.edge_curve.remove(joint + 1);
+        self.nodes.remove(joint);
+
+        if let Some(previous) = self.joint_before(joint) {
+            self.enforce_node(previous, JointSide::Outgoing);
+        }
+        if let Some(next) = self.joint_after(joint) {
+            self.enforce_node(next, JointSide::Incoming);
+        }
+        true
+    }
+
+    /// Reverses the direction of the feature so the start becomes the end.
+    /// The shape and the node type of each joint are unchanged.
+    pub fn reverse(&mut self) {
+        self.centerline = self.centerline.iter().rev().map(|b| b.reversed()).collect();
+        // the joint that closes a feature stays between the last and first
+        // segment
+        let interior = self.centerline.len() - 1;
+        self.nodes[..interior].reverse();
+        for edges in self.edge_curve.iter_mut() {
+            *edges = [Vec::<Point>::new(), Vec::<Point>::new()];
+        }
+    }
+
+    /// Adds a clothoid to the end of the feature as Bézier segments that
+    /// approximate it. The segments are moved to start at the end of the
+    /// feature, so a clothoid starting elsewhere leaves no gap in the
+    /// centerline. The joints are curvature continuous nodes so the
+    /// transition keeps its shape when the handles are edited. The feature
+    /// must be open.
+    pub fn add_clothoid(&mut self, clothoid: &Clothoid) {
+        assert!(!self.is_closed());
+        let end = self.centerline.last().unwrap().ctrl_point(3);
+        let offset = end - clothoid.start();
+        for mut b in clothoid.to_beziers(CLOTHOID_TOLERANCE, self.resolution) {
+            for idx in 0..4 {
+                b.translate_point(offset.x, offset.y, idx);
+            }
+            self.centerline.push(b);
+            self.nodes.push(NodeType::Curvature);
+            self.edge_curve
+                .push([Vec::<Point>::new(), Vec::<Point>::new()]);
+        }
+    }
+
+    /// Adds a spiral transition to the end of the feature that continues with
+    /// the heading and curvature of the last segment and changes linearly to
+    /// the end curvature over the length. A transition from a straight road
+    /// into a curve of radius r has an end curvature of 1 / r.
+    pub fn add_spiral(&mut self, end_curvature: f32, length: f32) {
+        let last = self.centerline.last().unwrap();
+        let tangent = last.tangent(1.0);
+        let clothoid = Clothoid::new(
+            last.ctrl_point(3),
+            tangent.y.atan2(tangent.x),
+            last.curvature(1.0),
+            end_curvature,
+            length,
+        );
+        self.add_clothoid(&clothoid);
+    }
+
+    pub fn ctrl_point(&self, idx: usize) -> Option<geometry::Point> {
+        let segment = idx / 4;
+        let ctrl_idx = idx % 4;
+        if segment >= self.centerline.len() {
+            return None;
+        }
+        Some(self.centerline[segment].ctrl_point(ctrl_idx))
+    }
+
+    /// Gets the node type of the joint at the end of the segment
+    pub fn node_type(&self, joint: usize) -> Option<NodeType> {
+        self.nodes.get(joint).copied()
+    }
+
+    /// Sets the node type of the joint at the end of the segment. The handle
+    /// of the following segment is adjusted to satisfy the new node type.
+    pub fn set_node_type(&mut self, joint: usize, node: NodeType) {
+        assert!(joint < self.nodes.len());
+        self.nodes[joint] = node;
+        self.enforce_node(joint, JointSide::Incoming);
+    }
+
+    /// Gets the node type of every joint
+    pub fn node_types(&self) -> &[NodeType] {
+        &self.nodes
+    }
+
+    /// Restores the node types of the joints without adjusting any handle
+    pub(crate) fn restore_node_types(&mut self, nodes: &[NodeType]) {
+        for (node, restored) in self.nodes.iter_mut().zip(nodes) {
+            *node = *restored;
+        }
+    }
+
+    /// Gets the continuity of each joint between two segments
+    pub fn continuity(&self) -> Vec<Continuity> {
+        let mut joints = continuity::analyze(&self.centerline);
+        if let (true, Some(first), Some(last)) = (
+            self.is_closed(),
+            self.centerline.first(),
+            self.centerline.last(),
+        ) {
+            joints.push(continuity::joint_continuity(
+                last,
+                first,
+                continuity::DEFAULT_TOLERANCE,
+            ));
+        }
+        joints
+    }
+
+    pub fn closure(&self) -> Closure {
+        self.closure
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closure != Closure::Open
+    }
+
+    /// Sets how the ends of the feature are joined. Closing a feature moves
+    /// the end of the last segment to the start of the first and adds the
+    /// node of the closing joint with the strongest type the joint satisfies.
+    /// A closed feature has at least two segments.
+    pub fn set_closure(&mut self, closure: Closure) {
+        match (self.is_closed(), closure != Closure::Open) {
+            (false, true) => {
+                assert!(self.centerline.len() > 1);
+                let start = self.centerline[0].ctrl_point(0);
+                self.centerline.last_mut().unwrap().set_ctrl_point(start, 3);
+                self.closure = closure;
+                let continuity = self.continuity();
+                self.nodes
+                    .push(NodeType::from_continuity(continuity.last().unwrap()));
+            }
+            (true, false) => {
+                self.nodes.pop();
+            }
+            _ => (),
+        }
+        self.closure = closure;
+    }
+
+    /// Validates the centerline of the feature against the design criteria.
+    /// The joint that closes a closed feature is validated last.
+    pub fn validate(&self, criteria: &validation::DesignCriteria) -> Vec<validation::Diagnostic> {
+        let mut diagnostics = validation::validate(&self.centerline, criteria);
+        if let (true, Some(first), Some(last)) = (
+            self.is_closed(),
+            self.centerline.first(),
+            self.centerline.last(),
+        ) {
+            let g1 = self.continuity().last().is_some_and(|c| c.g1);
+            let segment = self.centerline.len() - 1;
+            diagnostics.extend(validation::validate_joint(
+                last, first, segment, g1, criteria,
+            ));
+        }
+        diagnostics
+    }
+
+    /// Sets a control point in the compound Beziér curve that defines the feature.
+    /// A control point shared by two segments is set in both segments and the
+    /// handle opposite a moved handle is adjusted to satisfy the node type of
+    /// the joint.
+    pub fn set_ctrl_point(&mut self, idx: usize, point: geometry::Point) {
+        let segment = idx / 4;
+        let ctrl_point = idx % 4;
+        self.centerline[segment].set_ctrl_point(point, ctrl_point);
+        let len = self.centerline.len();
+        match ctrl_point {
+            // overlapped control point
+            0 => {
+                if let Some(joint) = self.joint_before(segment) {
+                    self.centerline[joint].set_ctrl_point(point, 3);
+                }
+            }
+            3 => {
+                if let Some(joint) = self.joint_after(segment) {
+                    self.centerline[(joint + 1) % len].set_ctrl_point(point, 0);
+                }
+            }
+            // handle of the joint at the start or end of this segment
+            1 => {
+                if let Some(joint) = self.joint_before(segment) {
+                    self.enforce_node(joint, JointSide::Outgoing);
+                }
+            }
+            _ => {
+                if let Some(joint) = self.joint_after(segment) {
+                    self.enforce_node(joint, JointSide::Incoming);
+                }
+            }
+        }
+    }
+
+    /// Gets the joint at the start of the segment. The joint at the end of
+    /// the last segment is at the start of the first segment of a closed
+    /// feature.
+    fn joint_before(&self, segment: usize) -> Option<usize> {
+        match segment {
+            0 if self.is_closed() => Some(self.nodes.len() - 1),
+            0 => None,
+            _ => Some(segment - 1),
+        }
+    }
+
+    /// Gets the joint at the end of the segment
+    fn joint_after(&self, segment: usize) -> Option<usize> {
+        (segment < self.nodes.len()).then_some(segment)
+    }
+
+    fn enforce_node(&mut self, joint: usize, moved: JointSide) {
+        let node = self.nodes[joint];
+        if joint + 1 == self.centerline.len() {
+            // the joint between the last and first segment of a closed feature
+            let (head, tail) = self.centerline.split_at_mut(joint);
+            continuity::enforce(node, &mut tail[0], &mut head[0], moved);
+        } else {
+            let (head, tail) = self.centerline.split_at_mut(joint + 1);
+            continuity::enforce(node, &mut head[joint], &mut tail[0], moved);
+        }
+    }
+}
+
+/// Gets the points of the arc around the joint from the end of one offset
+/// curve to the start of the next, excluding the end points. The arc turns
+/// the short way around the joint.
+fn round_join(joint: Point, from: Point, to: Point) -> Vec<Point> {
+    if from.distance(&to) <= SEAM_TOLERANCE {
+        return Vec::new();
+    }
+    let radius = joint.distance(&from);
+    let start = (from.y - joint.y).atan2(from.x - joint.x);
+    let end = (to.y - joint.y).atan2(to.x - joint.x);
+    let mut sweep = end - start;
+    if sweep > std::f32::consts::PI {
+        sweep -= std::f32::consts::TAU;
+    } else if sweep < -std::f32::consts::PI {
+        sweep += std::f32::consts::TAU;
+    }
+    let steps = (sweep.abs() / JOIN_ANGLE).ceil() as usize;
+    (1..steps)
+        .map(|i| {
+            let angle = start + sweep * i as f32 / steps as f32;
+            joint
+                + Point {
+                    x: angle.cos(),
+                    y: angle.sin(),
+                } * radius
+        })
+        .collect()
+}
+
+/// A point on the centerline of a feature with the unit normal pointing to
+/// the left of the centerline, the arc length from the start of the
+/// centerline and the segment and curve parameter of the point.
+#[derive(Debug, Clone, Copy)]
+pub struct Station {
+    pub point: Point,
+    pub normal: Point,
+    pub distance: f32,
+    pub segment: usize,
+    pub t: f32,
+}
+
+impl Default for BezierFeature {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> IntoIterator for &'a BezierFeature {
+    type Item = &'a geometry::Point;
+    type IntoIter = ControlPointIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ControlPointIterator::new(self)
+    }
+}
+
+pub struct ControlPointIterator<'a> {
+    points: Vec<&'a geometry::Point>,
+    index: usize,
+}
+
+impl<'a> ControlPointIterator<'a> {
+    pub fn new(feature: &'a BezierFeature) -> Self {
+        let points: Vec<&geometry::Point> = feature
+            .centerline
+            .iter()
+            .flat_map(|b| b.ctrl_points())
+            .collect();
+
+        Self { points, index: 0 }
+    }
+}
+
+impl<'a> Iterator for ControlPointIterator<'a> {
+    type Item = &'a Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.points.len() {
+            let result = self.points[self.index];
+            self.index += 1;
+            return Some(result);
+        }
+        None
+    }
+}
+
+/// Creates a single segment feature along the straight line from start to
+/// end with the handles at the thirds of the line. Shared by the tests of
+/// every module in the crate.
+#[cfg(test)]
+pub(crate) fn line_feature(start: Point, end: Point, width: f32) -> BezierFeature {
+    let mut feature = BezierFeature::new_with_attributes(width, false);
+    for i in 0..4 {
+        feature.set_ctrl_point(i, start + (end - start) * (i as f32 / 3.0));
+    }
+    feature
+}
+
+/// Creates a feature running along the x axis from the origin to (600, 0)
+/// with two segments joined at (300, 0).
+#[cfg(test)]
+pub(crate) fn straight_feature(width: f32) -> BezierFeature {
+    let mut feature = line_feature(Point::default(), Point { x: 300.0, y: 0.0 }, width);
+    feature.add_segment(Point { x: 500.0, y: 0.0 }, Point { x: 600.0, y: 0.0 });
+    feature
+}
+
+/// Creates a closed feature following a circle of radius 100 around the
+/// origin with four segments.
+#[cfg(test)]
+pub(crate) fn circle_feature(closure: Closure) -> BezierFeature {
+    let k = 100.0 * 0.5523;
+    let quarter = |p0: Point, p1: Point, p2: Point, p3: Point| {
+        Bezier::new_with_ctrl_point([p0, p1, p2, p3], DEFAULT_RESOLUTION)
+    };
+    let segments = vec![
+        quarter(
+            Point { x: 100.0, y: 0.0 },
+            Point { x: 100.0, y: k },
+            Point { x: k, y: 100.0 },
+            Point { x: 0.0, y: 100.0 },
+        ),
+        quarter(
+            Point { x: 0.0, y: 100.0 },
+            Point { x: -k, y: 100.0 },
+            Point { x: -100.0, y: k },
+            Point { x: -100.0, y: 0.0 },
+        ),
+        quarter(
+            Point { x: -100.0, y: 0.0 },
+            Point { x: -100.0, y: -k },
+            Point { x: -k, y: -100.0 },
+            Point { x: 0.0, y: -100.0 },
+        ),
+        quarter(
+            Point { x: 0.0, y: -100.0 },
+            Point { x: k, y: -100.0 },
+            Point { x: 100.0, y: -k },
+            Point { x: 100.0, y: 0.0 },
+        ),
+    ];
+    let mut feature = BezierFeature::new_with_segments(segments, 20.0);
+    feature.set_closure(closure);
+    feature
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const TEST_EPSILON: f32 = 0.001;
+
+    #[test]
+    fn test_add_segment() {
+        let feature = straight_feature(20.0);
+        assert_eq!(2, feature.segments().len());
+        assert_eq!(8, feature.into_iter().count());
+        // the new segment starts at the end of the previous segment with
+        // the reflected handle
+        let p = feature.ctrl_point(4).unwrap();
+        assert!(p.distance(&Point { x: 300.0, y: 0.0 }) < TEST_EPSILON);
+        let p = feature.ctrl_point(5).unwrap();
+        assert!(p.distance(&Point { x: 400.0, y: 0.0 }) < TEST_EPSILON);
+        assert!(feature.ctrl_point(8).is_none());
+        assert_eq!(Some(NodeType::Symmetric), feature.node_type(0));
+    }
+
+    #[test]
+    fn test_set_ctrl_point() {
+        let mut feature = straight_feature(20.0);
+        // a joint is shared by both segments
+        feature.set_ctrl_point(3, Point { x: 300.0, y: 50.0 });
+        let p = feature.ctrl_point(4).unwrap();
+        assert!(p.distance(&Point { x: 300.0, y: 50.0 }) < TEST_EPSILON);
+        // a symmetric node reflects the handle
+        feature.set_ctrl_point(2, Point { x: 250.0, y: 0.0 });
+        let p = feature.ctrl_point(5).unwrap();
+        assert!(p.distance(&Point { x: 350.0, y: 100.0 }) < TEST_EPSILON);
+        // a corner node leaves the other handle alone
+        feature.set_node_type(0, NodeType::Corner);
+        feature.set_ctrl_point(2, Point { x: 250.0, y: 100.0 });
+        let p = feature.ctrl_point(5).unwrap();
+        assert!(p.distance(&Point { x: 350.0, y: 100.0 }) < TEST_EPSILON);
+        assert!(!feature.continuity()[0].g1);
+    }
+
+    #[test]
+    fn test_surface() {
+        let mut feature = straight_feature(20.0);
+        let points = feature.curve().len();
+        let surface = feature.surface();
+        // the smooth joint appears once on each edge
+        assert_eq!((points - 1) * 2, surface.len());
+        assert!(feature.contains(&Point { x: 300.0, y: 5.0 }));
+        assert!(feature.contains(&Point { x: 550.0, y: -9.0 }));
+        assert!(!feature.contains(&Point { x: 300.0, y: 15.0 }));
+        assert!((feature.surface_mesh().area() - 12000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_insert_segment() {
+        let mut feature = straight_feature(20.0);
+        let segment = Bezier::new_with_ctrl_point(
+            [
+                Point { x: -300.0, y: 0.0 },
+                Point { x: -200.0, y: 0.0 },
+                Point { x: -100.0, y: 0.0 },
+                Point { x: 0.0, y: 0.0 },
+            ],
+            DEFAULT_RESOLUTION,
+        );
+        feature.insert_segment(0, &segment);
+        assert_eq!(3, feature.segments().len());
+        assert_eq!(12, feature.into_iter().count());
+        assert_eq!(2, feature.node_types().len());
+        assert_eq!(Some(NodeType::Curvature), feature.node_type(0));
+
+        // a segment in the middle moves the start of the following segment
+        let segment = Bezier::new_with_ctrl_point(
+            [
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 400.0, y: 0.0 },
+                Point { x: 400.0, y: 100.0 },
+                Point { x: 400.0, y: 200.0 },
+            ],
+            DEFAULT_RESOLUTION,
+        );
+        feature.insert_segment(2, &segment);
+        assert_eq!(4, feature.segments().len());
+        assert_eq!(3, feature.node_types().len());
+        let p = feature.ctrl_point(8).unwrap();
+        assert!(p.distance(&Point { x: 300.0, y: 0.0 }) < TEST_EPSILON);
+        let p = feature.ctrl_point(12).unwrap();
+        assert!(p.distance(&Point { x: 400.0, y: 200.0 }) < TEST_EPSILON);
+        let p = feature.ctrl_point(13).unwrap();
+        assert!(p.distance(&Point { x: 500.0, y: 200.0 }) < TEST_EPSILON);
+        assert_eq!(Some(NodeType::Corner), feature.node_type(2));
+        assert!(feature.continuity().iter().all(|c| c.g0));
+    }
+
+    #[test]
+    fn test_split_segment() {
+        let mut feature = straight_feature(20.0);
+        feature.set_ctrl_point(2, Point { x: 200.0, y: 100.0 });
+        let mid = feature.segments()[0].point_at(0.5);
+        feature.split_segment(0, 0.5);
+        assert_eq!(3, feature.segments().len());
+        assert_eq!(12, feature.into_iter().count());
+        assert_eq!(Some(NodeType::Curvature), feature.node_type(0));
+        assert_eq!(Some(NodeType::Symmetric), feature.node_type(1));
+        let p = feature.ctrl_point(3).unwrap();
+        assert!(p.distance(&mid) < TEST_EPSILON);
+        assert!(feature.continuity()[0].g1);
+
+        // removing the joint restores the segment
+        assert!(feature.remove_joint(0));
+        assert_eq!(2, feature.segments().len());
+        assert_eq!(8, feature.into_iter().count());
+        assert_eq!(Some(NodeType::Symmetric), feature.node_type(0));
+        let p = feature.segments()[0].point_at(0.5);
+        assert!(p.distance(&mid) < TEST_EPSILON);
+        assert!(feature.continuity().iter().all(|c| c.g0 && c.g1));
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut feature = straight_feature(20.0);
+        feature.set_node_type(0, NodeType::Corner);
+        feature.set_ctrl_point(5, Point { x: 400.0, y: 100.0 });
+        let points = feature.curve();
+        feature.reverse();
+        let reversed = feature.curve();
+        assert_eq!(points.len(), reversed.len());
+        for (p, r) in points.iter().zip(reversed.iter().rev()) {
+            assert!(p.distance(r) < TEST_EPSILON);
+        }
+        assert_eq!(Some(NodeType::Corner), feature.node_type(0));
+        let p = feature.ctrl_point(2).unwrap();
+        assert!(p.distance(&Point { x: 400.0, y: 100.0 }) < TEST_EPSILON);
+        assert!(feature.contains(&Point { x: 150.0, y: 5.0 }));
+    }
+
+    #[test]
+    fn test_tight_bend() {
+        // a quarter turn with a radius much smaller than the half width at
+        // the corner
+        let mut feature = BezierFeature::new_with_attributes(60.0, false);
+        feature.set_ctrl_point(0, Point { x: 0.0, y: 0.0 });
+        feature.set_ctrl_point(1, Point { x: 100.0, y: 0.0 });
+        feature.set_ctrl_point(2, Point { x: 100.0, y: 0.0 });
+        feature.set_ctrl_point(3, Point { x: 100.0, y: 100.0 });
+        let crossings = |points: &Vec<Point>| {
+            points.len() - Polyline::from(points.clone()).trim_loops(f32::MAX).len()
+        };
+        // the inner edge is on the right of the centerline
+        let raw = feature.centerline[0].clone().offset_curve(-30.0);
+        assert!(crossings(&raw) > 0);
+        for distance in [30.0, -30.0] {
+            assert_eq!(0, crossings(&feature.offset(distance)));
+        }
+        assert!(feature.contains(&Point { x: 80.0, y: 20.0 }));
+        assert!(!feature.contains(&Point { x: 60.0, y: 40.0 }));
+    }
+
+    #[test]
+    fn test_corner_seam() {
+        let straight = |p0: Point, p3: Point| {
+            Bezier::new_with_ctrl_point(
+                [
+                    p0,
+                    p0 + (p3 - p0) * (1.0 / 3.0),
+                    p0 + (p3 - p0) * (2.0 / 3.0),
+                    p3,
+                ],
+                DEFAULT_RESOLUTION,
+            )
+        };
+        let joint = Point { x: 300.0, y: 0.0 };
+        let segments = vec![
+            straight(Point { x: 0.0, y: 0.0 }, joint),
+            straight(joint, Point { x: 300.0, y: 300.0 }),
+        ];
+        let mut feature = BezierFeature::new_with_segments(segments, 20.0);
+        assert_eq!(Some(NodeType::Corner), feature.node_type(0));
+        // the outer edge turns around the joint
+        for edge in [feature.offset(10.0), feature.offset(-10.0)] {
+            let rounded = edge
+                .iter()
+                .filter(|p| p.x > 300.0 && p.y < 0.0)
+                .all(|p| (p.distance(&joint) - 10.0).abs() < TEST_EPSILON);
+            assert!(rounded);
+        }
+        // two strips with the inner overlap trimmed and a quarter circle at
+        // the outer corner
+        let area = 2.0 * 300.0 * 20.0 - 100.0 + std::f32::consts::PI * 25.0;
+        assert!((feature.surface_mesh().area() - area).abs() < 1.0);
+        assert!(!feature.contains(&Point { x: 285.0, y: 15.0 }));
+        assert!(feature.contains(&Point { x: 305.0, y: -5.0 }));
+    }
+
+    #[test]
+    fn test_closure() {
+        let mut feature = circle_feature(Closure::Ring);
+        assert!(feature.is_closed());
+        assert_eq!(4, feature.node_types().len());
+        assert_eq!(Some(NodeType::Curvature), feature.node_type(3));
+        assert!(feature.continuity().iter().all(|c| c.g0 && c.g1));
+        feature.set_node_type(3, NodeType::Symmetric);
+
+        // the start of the first segment is the end of the last segment
+        feature.set_ctrl_point(0, Point { x: 110.0, y: 0.0 });
+        let p = feature.ctrl_point(15).unwrap();
+        assert!(p.distance(&Point { x: 110.0, y: 0.0 }) < TEST_EPSILON);
+        // the closing joint reflects the handle of the last segment
+        feature.set_ctrl_point(1, Point { x: 110.0, y: 60.0 });
+        let p = feature.ctrl_point(14).unwrap();
+        assert!(p.distance(&Point { x: 110.0, y: -60.0 }) < TEST_EPSILON);
+        assert!(feature.remove_last_segment().is_none());
+
+        feature.set_closure(Closure::Open);
+        assert_eq!(3, feature.node_types().len());
+        feature.set_ctrl_point(0, Point { x: 100.0, y: 10.0 });
+        let p = feature.ctrl_point(15).unwrap();
+        assert!(p.distance(&Point { x: 110.0, y: 0.0 }) < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_closed_surface() {
+        let mut ring = circle_feature(Closure::Ring);
+        assert!(ring.contains(&Point { x: 0.0, y: 100.0 }));
+        assert!(ring.contains(&Point { x: 105.0, y: 0.0 }));
+        assert!(!ring.contains(&Point { x: 0.0, y: 0.0 }));
+        let area = 2.0 * std::f32::consts::PI * 100.0 * 20.0;
+        assert!((ring.surface_mesh().area() - area).abs() < area * 0.01);
+
+        let mut lake = circle_feature(Closure::Area);
+        assert!(lake.contains(&Point { x: 0.0, y: 0.0 }));
+        assert!(lake.contains(&Point { x: 0.0, y: -105.0 }));
+        assert!(!lake.contains(&Point { x: 0.0, y: -115.0 }));
+        let area = std::f32::consts::PI * 110.0 * 110.0;
+        assert!((lake.surface_mesh().area() - area).abs() < area * 0.01);
+    }
+
+    #[test]
+    fn test_closed_validate() {
+        let criteria = validation::DesignCriteria::for_design_speed(50.0);
+        let mut feature = circle_feature(Closure::Ring);
+        assert!(feature.validate(&criteria).is_empty());
+        // kink the joint that closes the feature
+        feature.set_node_type(3, NodeType::Corner);
+        feature.set_ctrl_point(1, Point { x: 110.0, y: 55.0 });
+        let diagnostics = feature.validate(&criteria);
+        let last = diagnostics.last().unwrap();
+        assert_eq!(3, last.segment);
+        assert!(matches!(last.kind, validation::DiagnosticKind::Kink { .. }));
+    }
+
+    #[test]
+    fn test_closed_edit() {
+        let mut feature = circle_feature(Closure::Ring);
+        feature.set_node_type(0, NodeType::Corner);
+        let points = feature.curve();
+        feature.reverse();
+        assert_eq!(Some(NodeType::Corner), feature.node_type(2));
+        let reversed = feature.curve();
+        for (p, r) in points.iter().zip(reversed.iter().rev()) {
+            assert!(p.distance(r) < TEST_EPSILON);
+        }
+
+        // removing the closing joint merges the last and first segments
+        assert!(feature.remove_joint(3));
+        assert_eq!(3, feature.segments().len());
+        assert_eq!(12, feature.into_iter().count());
+        assert_eq!(3, feature.node_types().len());
+        let start = feature.ctrl_point(0).unwrap();
+        assert!(start.distance(&Point { x: 0.0, y: -100.0 }) < TEST_EPSILON);
+        assert!(feature.continuity().iter().all(|c| c.g0));
+
+        // a segment inserted at the end joins the first segment
+        let segment = Bezier::new_with_ctrl_point(
+            [
+                Point { x: 0.0, y: -100.0 },
+                Point { x: 0.0, y: -150.0 },
+                Point { x: 50.0, y: -200.0 },
+                Point { x: 0.0, y: -200.0 },
+            ],
+            DEFAULT_RESOLUTION,
+        );
+        feature.insert_segment(3, &segment);
+        assert_eq!(4, feature.node_types().len());
+        let p = feature.ctrl_point(0).unwrap();
+        assert!(p.distance(&Point { x: 0.0, y: -200.0 }) < TEST_EPSILON);
+        assert!(feature.continuity().iter().all(|c| c.g0));
+    }
+
+    #[test]
+    fn test_remove_ring_joint() {
+        let mut feature = circle_feature(Closure::Ring);
+        assert!(feature.remove_joint(0));
+        assert!(feature.remove_joint(0));
+        assert_eq!(2, feature.segments().len());
+        // a ring of two segments keeps both of its joints
+        let points = feature.curve();
+        assert!(!feature.remove_joint(0));
+        assert!(!feature.remove_joint(1));
+        assert_eq!(2, feature.segments().len());
+        assert_eq!(2, feature.node_types().len());
+        assert!(feature.is_closed());
+        for (p, q) in points.iter().zip(feature.curve().iter()) {
+            assert!(p.distance(q) < TEST_EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_add_spiral() {
+        let mut feature = straight_feature(20.0);
+        feature.add_spiral(0.01, 100.0);
+        assert!(feature.segments().len() > 2);
+        let last = feature.segments().last().unwrap();
+        assert!((last.curvature(1.0) - 0.01).abs() < 0.001);
+        assert!(feature.continuity().iter().all(|c| c.g1));
+
+        // a clothoid starting away from the end is moved to the end
+        let mut feature = straight_feature(20.0);
+        let clothoid = Clothoid::new(Point { x: 0.0, y: 50.0 }, 0.0, 0.0, 0.01, 100.0);
+        feature.add_clothoid(&clothoid);
+        assert!(feature.continuity().iter().all(|c| c.g0));
+        let end = feature.segments().last().unwrap().ctrl_point(3);
+        let expected = clothoid.end() + Point { x: 600.0, y: -50.0 };
+        assert!(end.distance(&expected) < TEST_EPSILON);
+    }
+}
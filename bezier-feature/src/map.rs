@@ -0,0 +1,571 @@
+use geometry::Point;
+
+use crate::{
+    cross_section::RoadLayout,
+    crossing::{Crossing, CrossingRules},
+    junction::{Approach, Junction},
+    railroad::Railroad,
+    river::{Flow, River},
+    road::RoadMarkings,
+    BezierFeature, BezierFeatureType,
+};
+
+const DEFAULT_LAYER_NAME: &str = "Layer 1";
+
+/// The identifier of a feature in a map. Identifiers are never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FeatureId(pub u32);
+
+/// The identifier of a layer in a map. Identifiers are never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LayerId(pub u32);
+
+/// The type specific attributes of a feature
+#[derive(Debug, Clone)]
+pub enum Style {
+    Road(RoadMarkings),
+    /// A road with lanes, shoulders and sidewalks
+    Lanes(RoadLayout),
+    River(River),
+    Railroad(Railroad),
+}
+
+impl Style {
+    /// Creates the default style of the feature type for the feature
+    pub fn new(feature_type: BezierFeatureType, feature: &BezierFeature) -> Self {
+        match feature_type {
+            BezierFeatureType::Road => Style::Road(RoadMarkings::default()),
+            BezierFeatureType::River => {
+                Style::River(River::new(vec![feature.width()], Flow::Downstream))
+            }
+            BezierFeatureType::Railroad => Style::Railroad(Railroad::default()),
+        }
+    }
+
+    pub fn feature_type(&self) -> BezierFeatureType {
+        match self {
+            Style::Road(_) | Style::Lanes(_) => BezierFeatureType::Road,
+            Style::River(_) => BezierFeatureType::River,
+            Style::Railroad(_) => BezierFeatureType::Railroad,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub(crate) id: LayerId,
+    pub name: String,
+    pub visible: bool,
+}
+
+impl Layer {
+    pub fn id(&self) -> LayerId {
+        self.id
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MapFeature {
+    pub(crate) id: FeatureId,
+    pub(crate) layer: LayerId,
+    pub name: String,
+    pub visible: bool,
+    pub feature: BezierFeature,
+    pub style: Style,
+}
+
+impl MapFeature {
+    pub fn id(&self) -> FeatureId {
+        self.id
+    }
+
+    pub fn layer(&self) -> LayerId {
+        self.layer
+    }
+
+    pub fn feature_type(&self) -> BezierFeatureType {
+        self.style.feature_type()
+    }
+}
+
+/// A feature, or one control point of a feature, picked by the pointer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub feature: FeatureId,
+    pub ctrl_point: Option<usize>,
+}
+
+/// A document holding the features of a map in layers. The layers are
+/// stacked from the bottom to the top and the features within a layer are
+/// drawn in the order they are stored, so the last feature is on top. The
+/// junctions of the map join its roads.
+#[derive(Debug, Clone)]
+pub struct Map {
+    layers: Vec<Layer>,
+    features: Vec<MapFeature>,
+    junctions: Vec<Junction>,
+    next_id: u32,
+    pub selected: Option<Selection>,
+    pub hover: Option<Selection>,
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Map {
+    /// Creates an empty map with a single layer
+    pub fn new() -> Self {
+        let mut map = Map {
+            layers: Vec::new(),
+            features: Vec::new(),
+            junctions: Vec::new(),
+            next_id: 0,
+            selected: None,
+            hover: None,
+        };
+        map.add_layer(DEFAULT_LAYER_NAME);
+        map
+    }
+
+    /// Creates a map from layers, features and junctions read from a file.
+    /// New layers and features get identifiers after the largest restored
+    /// identifier.
+    pub(crate) fn from_parts(
+        layers: Vec<Layer>,
+        features: Vec<MapFeature>,
+        junctions: Vec<Junction>,
+    ) -> Self {
+        let next_id = layers
+            .iter()
+            .map(|l| l.id.0)
+            .chain(features.iter().map(|f| f.id.0))
+            .max()
+            .unwrap_or(0);
+        Map {
+            layers,
+            features,
+            junctions,
+            next_id,
+            selected: None,
+            hover: None,
+        }
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    pub fn layer(&self, id: LayerId) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.id == id)
+    }
+
+    pub fn layer_mut(&mut self, id: LayerId) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|l| l.id == id)
+    }
+
+    /// Adds a visible layer on top of the other layers
+    pub fn add_layer(&mut self, name: &str) -> LayerId {
+        let id = LayerId(self.next_id());
+        self.layers.push(Layer {
+            id,
+            name: name.to_string(),
+            visible: true,
+        });
+        id
+    }
+
+    /// Removes the layer and every feature on it. The last layer of a map
+    /// can not be removed.
+    pub fn remove_layer(&mut self, id: LayerId) -> bool {
+        if self.layers.len() < 2 || self.layer(id).is_none() {
+            return false;
+        }
+        self.layers.retain(|l| l.id != id);
+        let removed: Vec<FeatureId> = self
+            .features
+            .iter()
+            .filter(|f| f.layer == id)
+            .map(|f| f.id)
+            .collect();
+        for feature in removed {
+            self.remove_feature(feature);
+        }
+        true
+    }
+
+    /// Moves the layer to the position in the stack where 0 is the bottom
+    pub fn move_layer(&mut self, id: LayerId, position: usize) {
+        if let Some(idx) = self.layers.iter().position(|l| l.id == id) {
+            let layer = self.layers.remove(idx);
+            self.layers.insert(position.min(self.layers.len()), layer);
+        }
+    }
+
+    pub fn features(&self) -> impl Iterator<Item = &MapFeature> {
+        self.features.iter()
+    }
+
+    pub fn feature(&self, id: FeatureId) -> Option<&MapFeature> {
+        self.features.iter().find(|f| f.id == id)
+    }
+
+    pub fn feature_mut(&mut self, id: FeatureId) -> Option<&mut MapFeature> {
+        self.features.iter_mut().find(|f| f.id == id)
+    }
+
+    /// Adds a visible feature on top of the other features of the layer
+    pub fn add_feature(
+        &mut self,
+        name: &str,
+        feature: BezierFeature,
+        style: Style,
+        layer: LayerId,
+    ) -> Option<FeatureId> {
+        self.layer(layer)?;
+        let id = FeatureId(self.next_id());
+        self.features.push(MapFeature {
+            id,
+            layer,
+            name: name.to_string(),
+            visible: true,
+            feature,
+            style,
+        });
+        Some(id)
+    }
+
+    /// Removes the feature and clears any selection or hover of it. The
+    /// feature is detached from its junctions and junctions left without
+    /// roads are removed.
+    pub fn remove_feature(&mut self, id: FeatureId) -> Option<MapFeature> {
+        let idx = self.features.iter().position(|f| f.id == id)?;
+        for junction in self.junctions.iter_mut() {
+            junction.detach(id);
+        }
+        self.junctions.retain(|j| !j.approaches().is_empty());
+        if self.selected.is_some_and(|s| s.feature == id) {
+            self.selected = None;
+        }
+        if self.hover.is_some_and(|s| s.feature == id) {
+            self.hover = None;
+        }
+        Some(self.features.remove(idx))
+    }
+
+    pub fn junctions(&self) -> &[Junction] {
+        &self.junctions
+    }
+
+    pub fn junction_mut(&mut self, idx: usize) -> Option<&mut Junction> {
+        self.junctions.get_mut(idx)
+    }
+
+    /// Adds a junction without roads at the point
+    pub fn add_junction(&mut self, center: Point) -> usize {
+        self.junctions.push(Junction::new(center));
+        self.junctions.len() - 1
+    }
+
+    pub fn remove_junction(&mut self, idx: usize) -> Option<Junction> {
+        (idx < self.junctions.len()).then(|| self.junctions.remove(idx))
+    }
+
+    /// Attaches a road of the map to the junction. Only roads can be attached.
+    pub fn attach(&mut self, junction: usize, approach: Approach) -> bool {
+        let is_road = self
+            .feature(approach.feature)
+            .is_some_and(|f| f.feature_type() == BezierFeatureType::Road);
+        if !is_road || junction >= self.junctions.len() {
+            return false;
+        }
+        let mut junctions = std::mem::take(&mut self.junctions);
+        junctions[junction].attach(self, approach);
+        self.junctions = junctions;
+        true
+    }
+
+    /// Keeps the roads attached to the junctions after a feature has been
+    /// edited. The junctions of the edited feature follow it and the other
+    /// junctions pull the road ends moved with them back to their centers.
+    pub fn update_junctions(&mut self, moved: Option<FeatureId>) {
+        let mut junctions = std::mem::take(&mut self.junctions);
+        let (led, others): (Vec<_>, Vec<_>) = junctions
+            .iter_mut()
+            .partition(|j| j.approaches().iter().any(|a| Some(a.feature) == moved));
+        for junction in led {
+            junction.update(self, moved);
+        }
+        for junction in others {
+            junction.update(self, None);
+        }
+        self.junctions = junctions;
+    }
+
+    /// Moves the feature to the top of another layer
+    pub fn set_layer(&mut self, id: FeatureId, layer: LayerId) -> bool {
+        if self.layer(layer).is_none() {
+            return false;
+        }
+        match self.features.iter().position(|f| f.id == id) {
+            Some(idx) => {
+                let mut feature = self.features.remove(idx);
+                feature.layer = layer;
+                self.features.push(feature);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the feature above the next feature on its layer
+    pub fn raise(&mut self, id: FeatureId) {
+        if let Some(idx) = self.features.iter().position(|f| f.id == id) {
+            let layer = self.features[idx].layer;
+            if let Some(next) =
+                (idx + 1..self.features.len()).find(|i| self.features[*i].layer == layer)
+            {
+                self.features.swap(idx, next);
+            }
+        }
+    }
+
+    /// Moves the feature below the previous feature on its layer
+    pub fn lower(&mut self, id: FeatureId) {
+        if let Some(idx) = self.features.iter().position(|f| f.id == id) {
+            let layer = self.features[idx].layer;
+            if let Some(previous) = (0..idx).rev().find(|i| self.features[*i].layer == layer) {
+                self.features.swap(idx, previous);
+            }
+        }
+    }
+
+    /// Gets the visible features on visible layers from the bottom to the top
+    pub fn draw_order(&self) -> Vec<FeatureId> {
+        self.layers
+            .iter()
+            .filter(|l| l.visible)
+            .flat_map(|l| {
+                self.features
+                    .iter()
+                    .filter(move |f| f.layer == l.id && f.visible)
+                    .map(|f| f.id)
+            })
+            .collect()
+    }
+
+    /// Finds the top most feature with a control point within the radius of
+    /// the point or with a surface containing the point. Control points are
+    /// picked before any surface.
+    pub fn hit_test(&mut self, p: &Point, radius: f32) -> Option<Selection> {
+        let order = self.draw_order();
+        for id in order.iter().rev() {
+            let feature = &self.feature(*id)?.feature;
+            if let Some(idx) = feature
+                .into_iter()
+                .position(|ctrl| ctrl.distance(p) <= radius)
+            {
+                return Some(Selection {
+                    feature: *id,
+                    ctrl_point: Some(idx),
+                });
+            }
+        }
+        for id in order.iter().rev() {
+            if self.feature_mut(*id)?.feature.contains(p) {
+                return Some(Selection {
+                    feature: *id,
+                    ctrl_point: None,
+                });
+            }
+        }
+        None
+    }
+
+    /// Finds the crossings of every visible road over every visible river.
+    /// Each crossing is returned with the road that it carries.
+    pub fn crossings(&mut self, rules: &CrossingRules) -> Vec<(FeatureId, Crossing)> {
+        let visible: Vec<usize> = self
+            .draw_order()
+            .iter()
+            .filter_map(|id| self.features.iter().position(|f| f.id == *id))
+            .collect();
+        let of_type = |map: &Map, feature_type: BezierFeatureType| -> Vec<usize> {
+            visible
+                .iter()
+                .copied()
+                .filter(|idx| map.features[*idx].feature_type() == feature_type)
+                .collect()
+        };
+        let rivers = of_type(self, BezierFeatureType::River);
+        let mut crossings = Vec::new();
+        for road in of_type(self, BezierFeatureType::Road) {
+            for river in rivers.iter() {
+                let (road_feature, river_feature) = pair_mut(&mut self.features, road, *river);
+                if let Style::River(channel) = &river_feature.style {
+                    for crossing in rules.crossings(
+                        &mut road_feature.feature,
+                        &mut river_feature.feature,
+                        channel,
+                    ) {
+                        crossings.push((road_feature.id, crossing));
+                    }
+                }
+            }
+        }
+        crossings
+    }
+
+    fn next_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+/// Gets mutable references to two different elements of the slice
+fn pair_mut<T>(items: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    assert_ne!(a, b);
+    if a < b {
+        let (head, tail) = items.split_at_mut(b);
+        (&mut head[a], &mut tail[0])
+    } else {
+        let (head, tail) = items.split_at_mut(a);
+        (&mut tail[0], &mut head[b])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::line_feature;
+
+    fn add(
+        map: &mut Map,
+        feature_type: BezierFeatureType,
+        feature: BezierFeature,
+        layer: LayerId,
+    ) -> FeatureId {
+        let style = Style::new(feature_type, &feature);
+        map.add_feature("feature", feature, style, layer).unwrap()
+    }
+
+    #[test]
+    fn test_draw_order() {
+        let mut map = Map::new();
+        let base = map.layers()[0].id();
+        let top = map.add_layer("roads");
+        let feature = || line_feature(Point::default(), Point { x: 100.0, y: 0.0 }, 20.0);
+        let a = add(&mut map, BezierFeatureType::Road, feature(), top);
+        let b = add(&mut map, BezierFeatureType::River, feature(), base);
+        let c = add(&mut map, BezierFeatureType::Railroad, feature(), base);
+        assert_eq!(vec![b, c, a], map.draw_order());
+
+        map.raise(b);
+        assert_eq!(vec![c, b, a], map.draw_order());
+        map.lower(b);
+        map.move_layer(top, 0);
+        assert_eq!(vec![a, b, c], map.draw_order());
+
+        map.feature_mut(b).unwrap().visible = false;
+        map.layer_mut(top).unwrap().visible = false;
+        assert_eq!(vec![c], map.draw_order());
+
+        assert!(map.remove_layer(base));
+        assert!(!map.remove_layer(top));
+        assert_eq!(1, map.features().count());
+        assert!(map.feature(c).is_none());
+    }
+
+    #[test]
+    fn test_hit_test() {
+        let mut map = Map::new();
+        let layer = map.layers()[0].id();
+        let a = add(
+            &mut map,
+            BezierFeatureType::Road,
+            line_feature(Point::default(), Point { x: 300.0, y: 0.0 }, 20.0),
+            layer,
+        );
+        let b = add(
+            &mut map,
+            BezierFeatureType::River,
+            line_feature(
+                Point {
+                    x: 150.0,
+                    y: -100.0,
+                },
+                Point { x: 150.0, y: 200.0 },
+                20.0,
+            ),
+            layer,
+        );
+
+        // the river is on top where the features overlap
+        let hit = map.hit_test(&Point { x: 150.0, y: 5.0 }, 2.0).unwrap();
+        assert_eq!(
+            Selection {
+                feature: b,
+                ctrl_point: None
+            },
+            hit
+        );
+        // control points are scoped to their feature
+        let hit = map.hit_test(&Point { x: 102.0, y: 0.0 }, 5.0).unwrap();
+        assert_eq!(
+            Selection {
+                feature: a,
+                ctrl_point: Some(1)
+            },
+            hit
+        );
+        assert!(map.hit_test(&Point { x: 50.0, y: 50.0 }, 5.0).is_none());
+
+        map.selected = Some(hit);
+        map.remove_feature(a);
+        assert!(map.selected.is_none());
+    }
+
+    #[test]
+    fn test_crossings() {
+        let mut map = Map::new();
+        let layer = map.layers()[0].id();
+        let road = add(
+            &mut map,
+            BezierFeatureType::Road,
+            line_feature(
+                Point { x: 0.0, y: -200.0 },
+                Point { x: 0.0, y: 200.0 },
+                20.0,
+            ),
+            layer,
+        );
+        add(
+            &mut map,
+            BezierFeatureType::River,
+            line_feature(
+                Point { x: -300.0, y: 0.0 },
+                Point { x: 300.0, y: 0.0 },
+                40.0,
+            ),
+            layer,
+        );
+        add(
+            &mut map,
+            BezierFeatureType::Railroad,
+            line_feature(
+                Point {
+                    x: 100.0,
+                    y: -200.0,
+                },
+                Point { x: 100.0, y: 200.0 },
+                20.0,
+            ),
+            layer,
+        );
+        let crossings = map.crossings(&CrossingRules::default());
+        assert_eq!(1, crossings.len());
+        assert_eq!(road, crossings[0].0);
+    }
+}
@@ -0,0 +1,916 @@
+//! The map file format. A map file is a JSON document with a version number,
+//! the layers of the map from the bottom to the top and the features in
+//! their z-order. Points are written as `[x, y]` arrays and each feature has
+//! the four control points of every segment of its centerline. The junctions
+//! follow the features and refer to their roads by identifier.
+//!
+//! Files written by older versions are migrated to the current version when
+//! they are loaded. Version 1 files have a list of features with a type name
+//! and no layers, styles or identifiers.
+
+use std::{fmt, fs, path::Path};
+
+use geometry::{bezier::Bezier, continuity::NodeType, dash::DashPattern, Point};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cross_section::{CrossSection, RoadLayout},
+    junction::{Approach, Junction, Location},
+    map::{FeatureId, Layer, LayerId, Map, MapFeature, Style},
+    railroad::Railroad,
+    river::{Flow, River},
+    road::{CenterLine, RoadMarkings},
+    BezierFeature, BezierFeatureType, Closure,
+};
+
+/// The version written by `save` and `to_string`
+pub const MAP_FILE_VERSION: u32 = 2;
+
+/// Maximum distance between the end of a segment and the start of the next
+const JOINT_TOLERANCE: f32 = 0.01;
+const DEFAULT_RESOLUTION: f32 = 0.025;
+
+#[derive(Debug)]
+pub enum MapFileError {
+    Io(std::io::Error),
+    /// The file is not valid JSON or does not match the format of its version
+    Json(serde_json::Error),
+    MissingVersion,
+    /// The file was written by a newer version of the format
+    UnsupportedVersion(u32),
+    DuplicateId(u32),
+    UnknownLayer {
+        feature: String,
+        layer: u32,
+    },
+    InvalidFeature {
+        feature: String,
+        reason: &'static str,
+    },
+    /// The junction at the index in the list of junctions is invalid
+    InvalidJunction {
+        junction: usize,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for MapFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapFileError::Io(e) => write!(f, "unable to read or write the map file: {e}"),
+            MapFileError::Json(e) => write!(f, "malformed map file: {e}"),
+            MapFileError::MissingVersion => write!(f, "the map file has no version"),
+            MapFileError::UnsupportedVersion(v) => write!(
+                f,
+                "the map file version {v} is newer than the supported version {MAP_FILE_VERSION}"
+            ),
+            MapFileError::DuplicateId(id) => write!(f, "the identifier {id} is used twice"),
+            MapFileError::UnknownLayer { feature, layer } => {
+                write!(f, "feature \"{feature}\" is on the unknown layer {layer}")
+            }
+            MapFileError::InvalidFeature { feature, reason } => {
+                write!(f, "feature \"{feature}\" {reason}")
+            }
+            MapFileError::InvalidJunction { junction, reason } => {
+                write!(f, "junction {junction} {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MapFileError::Io(e) => Some(e),
+            MapFileError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for MapFileError {
+    fn from(e: std::io::Error) -> Self {
+        MapFileError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for MapFileError {
+    fn from(e: serde_json::Error) -> Self {
+        MapFileError::Json(e)
+    }
+}
+
+/// Writes the map to the file
+pub fn save(map: &Map, path: &Path) -> Result<(), MapFileError> {
+    fs::write(path, to_string(map)?)?;
+    Ok(())
+}
+
+/// Reads a map from the file, migrating files of older versions
+pub fn load(path: &Path) -> Result<Map, MapFileError> {
+    from_str(&fs::read_to_string(path)?)
+}
+
+/// Gets the map as a map file in the current version
+pub fn to_string(map: &Map) -> Result<String, MapFileError> {
+    let file = MapFile {
+        version: MAP_FILE_VERSION,
+        layers: map
+            .layers()
+            .iter()
+            .map(|l| LayerRecord {
+                id: l.id().0,
+                name: l.name.clone(),
+                visible: l.visible,
+            })
+            .collect(),
+        features: map.features().map(FeatureRecord::from).collect(),
+        junctions: map.junctions().iter().map(JunctionRecord::from).collect(),
+    };
+    Ok(serde_json::to_string_pretty(&file)?)
+}
+
+/// Reads a map from the text of a map file, migrating older versions
+pub fn from_str(text: &str) -> Result<Map, MapFileError> {
+    let version: VersionRecord = serde_json::from_str(text)?;
+    let file = match version.version {
+        None => return Err(MapFileError::MissingVersion),
+        Some(1) => migrate_v1(serde_json::from_str(text)?),
+        Some(MAP_FILE_VERSION) => serde_json::from_str(text)?,
+        Some(v) => return Err(MapFileError::UnsupportedVersion(v)),
+    };
+    file.into_map()
+}
+
+#[derive(Deserialize)]
+struct VersionRecord {
+    version: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MapFile {
+    version: u32,
+    layers: Vec<LayerRecord>,
+    features: Vec<FeatureRecord>,
+    #[serde(default)]
+    junctions: Vec<JunctionRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LayerRecord {
+    id: u32,
+    name: String,
+    #[serde(default = "visible")]
+    visible: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FeatureRecord {
+    id: u32,
+    name: String,
+    layer: u32,
+    #[serde(default = "visible")]
+    visible: bool,
+    width: f32,
+    segments: Vec<[[f32; 2]; 4]>,
+    /// The node type of each joint. Joints without a node type get the
+    /// strongest type they satisfy.
+    #[serde(default)]
+    nodes: Vec<NodeRecord>,
+    #[serde(default)]
+    closure: ClosureRecord,
+    style: StyleRecord,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ClosureRecord {
+    #[default]
+    Open,
+    Ring,
+    Area,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum NodeRecord {
+    Corner,
+    Smooth,
+    Symmetric,
+    Curvature,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum StyleRecord {
+    Road(MarkingsRecord),
+    Lanes {
+        sections: Vec<SectionRecord>,
+        taper_length: f32,
+        markings: MarkingsRecord,
+    },
+    River {
+        widths: Vec<f32>,
+        flow: FlowRecord,
+    },
+    Railroad {
+        gauge: f32,
+        tie_spacing: f32,
+        tie_length: f32,
+        tie_width: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct MarkingsRecord {
+    centerline: CenterLineRecord,
+    line_width: f32,
+    line_separation: f32,
+    stripe: Vec<f32>,
+    #[serde(default)]
+    stripe_offset: f32,
+    edge_lines: bool,
+    edge_inset: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SectionRecord {
+    lanes: usize,
+    lane_width: f32,
+    median_width: f32,
+    shoulder_width: f32,
+    sidewalk_width: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JunctionRecord {
+    center: [f32; 2],
+    fillet_radius: f32,
+    approaches: Vec<ApproachRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ApproachRecord {
+    feature: u32,
+    location: LocationRecord,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum LocationRecord {
+    Start,
+    End,
+    Crossing { segment: usize, t: f32 },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CenterLineRecord {
+    Solid,
+    DoubleSolid,
+    Stripe,
+    StripeSolid,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FlowRecord {
+    Downstream,
+    Upstream,
+}
+
+/// A version 1 map file
+#[derive(Deserialize)]
+struct MapFileV1 {
+    features: Vec<FeatureRecordV1>,
+}
+
+#[derive(Deserialize)]
+struct FeatureRecordV1 {
+    name: String,
+    #[serde(rename = "type")]
+    feature_type: FeatureTypeRecord,
+    width: f32,
+    segments: Vec<[[f32; 2]; 4]>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FeatureTypeRecord {
+    Road,
+    River,
+    Railroad,
+}
+
+fn visible() -> bool {
+    true
+}
+
+/// Migrates a version 1 file by putting every feature on a single layer with
+/// the default style of its type
+fn migrate_v1(file: MapFileV1) -> MapFile {
+    let layer = 1;
+    let features = file
+        .features
+        .into_iter()
+        .enumerate()
+        .map(|(idx, f)| {
+            let feature_type = match f.feature_type {
+                FeatureTypeRecord::Road => BezierFeatureType::Road,
+                FeatureTypeRecord::River => BezierFeatureType::River,
+                FeatureTypeRecord::Railroad => BezierFeatureType::Railroad,
+            };
+            let feature = BezierFeature::new_with_attributes(f.width, false);
+            FeatureRecord {
+                id: layer + 1 + idx as u32,
+                name: f.name,
+                layer,
+                visible: true,
+                width: f.width,
+                segments: f.segments,
+                nodes: Vec::new(),
+                closure: ClosureRecord::Open,
+                style: StyleRecord::from(&Style::new(feature_type, &feature)),
+            }
+        })
+        .collect();
+    MapFile {
+        version: MAP_FILE_VERSION,
+        layers: vec![LayerRecord {
+            id: layer,
+            name: "Layer 1".to_string(),
+            visible: true,
+        }],
+        features,
+        junctions: Vec::new(),
+    }
+}
+
+impl MapFile {
+    fn into_map(self) -> Result<Map, MapFileError> {
+        let mut ids = Vec::<u32>::new();
+        let mut unique = |id: u32| {
+            if ids.contains(&id) {
+                return Err(MapFileError::DuplicateId(id));
+            }
+            ids.push(id);
+            Ok(())
+        };
+        let mut layers = Vec::<Layer>::with_capacity(self.layers.len());
+        for l in self.layers {
+            unique(l.id)?;
+            layers.push(Layer {
+                id: LayerId(l.id),
+                name: l.name,
+                visible: l.visible,
+            });
+        }
+        let mut features = Vec::<MapFeature>::with_capacity(self.features.len());
+        for f in self.features {
+            unique(f.id)?;
+            if !layers.iter().any(|l| l.id.0 == f.layer) {
+                return Err(MapFileError::UnknownLayer {
+                    feature: f.name,
+                    layer: f.layer,
+                });
+            }
+            features.push(f.into_feature()?);
+        }
+        let mut junctions = Vec::<Junction>::with_capacity(self.junctions.len());
+        for (idx, j) in self.junctions.into_iter().enumerate() {
+            junctions.push(j.into_junction(idx, &features)?);
+        }
+        Ok(Map::from_parts(layers, features, junctions))
+    }
+}
+
+impl JunctionRecord {
+    fn into_junction(self, idx: usize, features: &[MapFeature]) -> Result<Junction, MapFileError> {
+        let invalid = |reason| MapFileError::InvalidJunction {
+            junction: idx,
+            reason,
+        };
+        if self.center.iter().any(|c| !c.is_finite()) {
+            return Err(invalid("has no center"));
+        }
+        if !self.fillet_radius.is_finite() || self.fillet_radius < 0.0 {
+            return Err(invalid("has a negative fillet radius"));
+        }
+        let mut approaches = Vec::<Approach>::with_capacity(self.approaches.len());
+        for a in self.approaches {
+            let road = features
+                .iter()
+                .find(|f| f.id().0 == a.feature && f.feature_type() == BezierFeatureType::Road)
+                .ok_or(invalid("joins a feature that is not a road"))?;
+            let location = match a.location {
+                LocationRecord::Start => Location::Start,
+                LocationRecord::End => Location::End,
+                LocationRecord::Crossing { segment, t } => {
+                    if segment >= road.feature.segments().len() || !(0.0..=1.0).contains(&t) {
+                        return Err(invalid("is not on its crossing road"));
+                    }
+                    Location::Crossing { segment, t }
+                }
+            };
+            approaches.push(Approach {
+                feature: FeatureId(a.feature),
+                location,
+            });
+        }
+        Ok(Junction::from_parts(
+            Point {
+                x: self.center[0],
+                y: self.center[1],
+            },
+            approaches,
+            self.fillet_radius,
+        ))
+    }
+}
+
+impl From<&Junction> for JunctionRecord {
+    fn from(j: &Junction) -> Self {
+        let center = j.center();
+        JunctionRecord {
+            center: [center.x, center.y],
+            fillet_radius: j.fillet_radius,
+            approaches: j
+                .approaches()
+                .iter()
+                .map(|a| ApproachRecord {
+                    feature: a.feature.0,
+                    location: match a.location {
+                        Location::Start => LocationRecord::Start,
+                        Location::End => LocationRecord::End,
+                        Location::Crossing { segment, t } => {
+                            LocationRecord::Crossing { segment, t }
+                        }
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FeatureRecord {
+    fn into_feature(self) -> Result<MapFeature, MapFileError> {
+        let invalid = |reason| MapFileError::InvalidFeature {
+            feature: self.name.clone(),
+            reason,
+        };
+        if self.segments.is_empty() {
+            return Err(invalid("has no segments"));
+        }
+        if !self.width.is_finite() || self.width < 0.0 {
+            return Err(invalid("has a negative width"));
+        }
+        let point = |p: [f32; 2]| Point { x: p[0], y: p[1] };
+        let segments: Vec<Bezier> = self
+            .segments
+            .iter()
+            .map(|s| Bezier::new_with_ctrl_point(s.map(point), DEFAULT_RESOLUTION))
+            .collect();
+        if segments
+            .windows(2)
+            .any(|s| s[0].ctrl_point(3).distance(&s[1].ctrl_point(0)) > JOINT_TOLERANCE)
+        {
+            return Err(invalid("has segments that are not joined"));
+        }
+        let closure = Closure::from(self.closure);
+        if closure != Closure::Open
+            && (segments.len() < 2
+                || segments[0]
+                    .ctrl_point(0)
+                    .distance(&segments[segments.len() - 1].ctrl_point(3))
+                    > JOINT_TOLERANCE)
+        {
+            return Err(invalid("is closed but its ends are not joined"));
+        }
+        let style = match self.style {
+            StyleRecord::Road(markings) => Style::Road(markings.into_markings().map_err(invalid)?),
+            StyleRecord::Lanes {
+                sections,
+                taper_length,
+                markings,
+            } => {
+                if sections.is_empty() {
+                    return Err(invalid("is a road without cross sections"));
+                }
+                if sections.iter().any(|s| {
+                    [
+                        s.lane_width,
+                        s.median_width,
+                        s.shoulder_width,
+                        s.sidewalk_width,
+                    ]
+                    .iter()
+                    .any(|w| !w.is_finite() || *w < 0.0)
+                }) {
+                    return Err(invalid("is a road with a negative cross section width"));
+                }
+                if !taper_length.is_finite() || taper_length <= 0.0 {
+                    return Err(invalid("is a road without a positive taper length"));
+                }
+                let sections = sections
+                    .into_iter()
+                    .map(|s| CrossSection {
+                        lanes: s.lanes,
+                        lane_width: s.lane_width,
+                        median_width: s.median_width,
+                        shoulder_width: s.shoulder_width,
+                        sidewalk_width: s.sidewalk_width,
+                    })
+                    .collect();
+                let mut layout = RoadLayout::new(sections);
+                layout.taper_length = taper_length;
+                layout.markings = markings.into_markings().map_err(invalid)?;
+                Style::Lanes(layout)
+            }
+            StyleRecord::River { widths, flow } => {
+                if widths.is_empty() {
+                    return Err(invalid("is a river without widths"));
+                }
+                if widths.iter().any(|w| !w.is_finite() || *w < 0.0) {
+                    return Err(invalid("is a river with a negative width"));
+                }
+                let flow = match flow {
+                    FlowRecord::Downstream => Flow::Downstream,
+                    FlowRecord::Upstream => Flow::Upstream,
+                };
+                Style::River(River::new(widths, flow))
+            }
+            StyleRecord::Railroad {
+                gauge,
+                tie_spacing,
+                tie_length,
+                tie_width,
+            } => {
+                if !tie_spacing.is_finite() || tie_spacing <= 0.0 {
+                    return Err(invalid("is a railroad without a positive tie spacing"));
+                }
+                if [gauge, tie_length, tie_width]
+                    .iter()
+                    .any(|d| !d.is_finite() || *d < 0.0)
+                {
+                    return Err(invalid("is a railroad with a negative dimension"));
+                }
+                Style::Railroad(Railroad {
+                    gauge,
+                    tie_spacing,
+                    tie_length,
+                    tie_width,
+                })
+            }
+        };
+
+        let mut feature = BezierFeature::new_with_segments(segments, self.width);
+        feature.set_closure(closure);
+        let nodes: Vec<NodeType> = self.nodes.iter().map(|n| NodeType::from(*n)).collect();
+        feature.restore_node_types(&nodes);
+        Ok(MapFeature {
+            id: FeatureId(self.id),
+            layer: LayerId(self.layer),
+            name: self.name,
+            visible: self.visible,
+            feature,
+            style,
+        })
+    }
+}
+
+impl MarkingsRecord {
+    fn into_markings(self) -> Result<RoadMarkings, &'static str> {
+        if self.stripe.len() < 2 || self.stripe.iter().any(|d| !d.is_finite() || *d <= 0.0) {
+            return Err("has a stripe without positive dashes and gaps");
+        }
+        Ok(RoadMarkings {
+            centerline: match self.centerline {
+                CenterLineRecord::Solid => CenterLine::Solid,
+                CenterLineRecord::DoubleSolid => CenterLine::DoubleSolid,
+                CenterLineRecord::Stripe => CenterLine::Stripe,
+                CenterLineRecord::StripeSolid => CenterLine::StripeSolid,
+            },
+            line_width: self.line_width,
+            line_separation: self.line_separation,
+            stripe: DashPattern::new(&self.stripe, self.stripe_offset),
+            edge_lines: self.edge_lines,
+            edge_inset: self.edge_inset,
+        })
+    }
+}
+
+impl From<&RoadMarkings> for MarkingsRecord {
+    fn from(m: &RoadMarkings) -> Self {
+        MarkingsRecord {
+            centerline: match m.centerline {
+                CenterLine::Solid => CenterLineRecord::Solid,
+                CenterLine::DoubleSolid => CenterLineRecord::DoubleSolid,
+                CenterLine::Stripe => CenterLineRecord::Stripe,
+                CenterLine::StripeSolid => CenterLineRecord::StripeSolid,
+            },
+            line_width: m.line_width,
+            line_separation: m.line_separation,
+            stripe: m.stripe.dashes().to_vec(),
+            stripe_offset: m.stripe.offset(),
+            edge_lines: m.edge_lines,
+            edge_inset: m.edge_inset,
+        }
+    }
+}
+
+impl From<&MapFeature> for FeatureRecord {
+    fn from(f: &MapFeature) -> Self {
+        FeatureRecord {
+            id: f.id().0,
+            name: f.name.clone(),
+            layer: f.layer().0,
+            visible: f.visible,
+            width: f.feature.width(),
+            segments: f
+                .feature
+                .segments()
+                .iter()
+                .map(|s| s.ctrl_points().map(|p| [p.x, p.y]))
+                .collect(),
+            nodes: f
+                .feature
+                .node_types()
+                .iter()
+                .map(|n| NodeRecord::from(*n))
+                .collect(),
+            closure: ClosureRecord::from(f.feature.closure()),
+            style: StyleRecord::from(&f.style),
+        }
+    }
+}
+
+impl From<&Style> for StyleRecord {
+    fn from(style: &Style) -> Self {
+        match style {
+            Style::Road(m) => StyleRecord::Road(MarkingsRecord::from(m)),
+            Style::Lanes(layout) => StyleRecord::Lanes {
+                sections: layout
+                    .sections()
+                    .iter()
+                    .map(|s| SectionRecord {
+                        lanes: s.lanes,
+                        lane_width: s.lane_width,
+                        median_width: s.median_width,
+                        shoulder_width: s.shoulder_width,
+                        sidewalk_width: s.sidewalk_width,
+                    })
+                    .collect(),
+                taper_length: layout.taper_length,
+                markings: MarkingsRecord::from(&layout.markings),
+            },
+            Style::River(r) => StyleRecord::River {
+                widths: r.widths().to_vec(),
+                flow: match r.flow() {
+                    Flow::Downstream => FlowRecord::Downstream,
+                    Flow::Upstream => FlowRecord::Upstream,
+                },
+            },
+            Style::Railroad(r) => StyleRecord::Railroad {
+                gauge: r.gauge,
+                tie_spacing: r.tie_spacing,
+                tie_length: r.tie_length,
+                tie_width: r.tie_width,
+            },
+        }
+    }
+}
+
+impl From<Closure> for ClosureRecord {
+    fn from(closure: Closure) -> Self {
+        match closure {
+            Closure::Open => ClosureRecord::Open,
+            Closure::Ring => ClosureRecord::Ring,
+            Closure::Area => ClosureRecord::Area,
+        }
+    }
+}
+
+impl From<ClosureRecord> for Closure {
+    fn from(closure: ClosureRecord) -> Self {
+        match closure {
+            ClosureRecord::Open => Closure::Open,
+            ClosureRecord::Ring => Closure::Ring,
+            ClosureRecord::Area => Closure::Area,
+        }
+    }
+}
+
+impl From<NodeType> for NodeRecord {
+    fn from(node: NodeType) -> Self {
+        match node {
+            NodeType::Corner => NodeRecord::Corner,
+            NodeType::Smooth => NodeRecord::Smooth,
+            NodeType::Symmetric => NodeRecord::Symmetric,
+            NodeType::Curvature => NodeRecord::Curvature,
+        }
+    }
+}
+
+impl From<NodeRecord> for NodeType {
+    fn from(node: NodeRecord) -> Self {
+        match node {
+            NodeRecord::Corner => NodeType::Corner,
+            NodeRecord::Smooth => NodeType::Smooth,
+            NodeRecord::Symmetric => NodeType::Symmetric,
+            NodeRecord::Curvature => NodeType::Curvature,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::line_feature;
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn map() -> Map {
+        let mut map = Map::new();
+        let base = map.layers()[0].id();
+        let roads = map.add_layer("roads");
+        let mut road = BezierFeature::new_with_attributes(20.0, false);
+        road.add_segment(Point { x: 250.0, y: 150.0 }, Point { x: 300.0, y: 200.0 });
+        road.set_node_type(0, NodeType::Corner);
+        let mut river = BezierFeature::new_with_attributes(40.0, false);
+        river.add_segment(Point { x: 0.0, y: 200.0 }, Point { x: 10.0, y: 10.0 });
+        river.set_closure(Closure::Area);
+        let channel = River::new(vec![10.0, 30.0], Flow::Upstream);
+        map.add_feature(
+            "Main Street",
+            road,
+            Style::Road(RoadMarkings::new(CenterLine::StripeSolid, false)),
+            roads,
+        );
+        map.add_feature("Creek", river, Style::River(channel), base);
+        let avenue = line_feature(Point::default(), Point { x: 0.0, y: 300.0 }, 40.0);
+        let mut layout = RoadLayout::new(vec![CrossSection::default()]);
+        layout.set_section(
+            1,
+            CrossSection {
+                lanes: 2,
+                ..Default::default()
+            },
+        );
+        layout.taper_length = 30.0;
+        let avenue = map
+            .add_feature("Avenue", avenue, Style::Lanes(layout), roads)
+            .unwrap();
+        let side = line_feature(
+            Point { x: 0.0, y: 150.0 },
+            Point {
+                x: -100.0,
+                y: 150.0,
+            },
+            20.0,
+        );
+        let side = map
+            .add_feature(
+                "Side Street",
+                side,
+                Style::Road(RoadMarkings::default()),
+                roads,
+            )
+            .unwrap();
+        let junction = map.add_junction(Point { x: 0.0, y: 150.0 });
+        map.attach(
+            junction,
+            Approach {
+                feature: avenue,
+                location: Location::Crossing { segment: 0, t: 0.5 },
+            },
+        );
+        map.attach(
+            junction,
+            Approach {
+                feature: side,
+                location: Location::Start,
+            },
+        );
+        map.layer_mut(base).unwrap().visible = false;
+        map
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let map = map();
+        let text = to_string(&map).unwrap();
+        let mut loaded = from_str(&text).unwrap();
+        assert_eq!(text, to_string(&loaded).unwrap());
+        assert_eq!(map.draw_order(), loaded.draw_order());
+        assert!(!loaded.layers()[0].visible);
+
+        let road = loaded.features().next().unwrap();
+        assert_eq!("Main Street", road.name);
+        assert_eq!(2, road.feature.segments().len());
+        assert_eq!(Some(NodeType::Corner), road.feature.node_type(0));
+        let end = road.feature.ctrl_point(7).unwrap();
+        assert!(end.distance(&Point { x: 300.0, y: 200.0 }) < TEST_EPSILON);
+        assert!(
+            matches!(&road.style, Style::Road(m) if m.centerline == CenterLine::StripeSolid && !m.edge_lines)
+        );
+
+        let avenue = loaded.features().find(|f| f.name == "Avenue").unwrap();
+        match &avenue.style {
+            Style::Lanes(layout) => {
+                assert_eq!(2, layout.sections().len());
+                assert_eq!(2, layout.section(1).lanes);
+                assert!((30.0 - layout.taper_length).abs() < TEST_EPSILON);
+            }
+            _ => panic!("the avenue has no lanes"),
+        }
+
+        let lake = loaded.features().find(|f| f.name == "Creek").unwrap();
+        assert_eq!(Closure::Area, lake.feature.closure());
+        assert_eq!(2, lake.feature.node_types().len());
+
+        let junction = &loaded.junctions()[0];
+        assert!(junction.center().distance(&Point { x: 0.0, y: 150.0 }) < TEST_EPSILON);
+        assert_eq!(map.junctions()[0].approaches(), junction.approaches());
+
+        // new identifiers follow the restored identifiers
+        let layer = loaded.add_layer("new");
+        assert!(map.layers().iter().all(|l| l.id() != layer));
+    }
+
+    #[test]
+    fn test_migrate_v1() {
+        let text = r#"{
+            "version": 1,
+            "features": [
+                {"name": "river", "type": "river", "width": 25.0,
+                 "segments": [[[0, 0], [100, 0], [200, 0], [300, 0]]]},
+                {"name": "road", "type": "road", "width": 30.0,
+                 "segments": [[[0, 0], [0, 100], [0, 200], [0, 300]]]}
+            ]
+        }"#;
+        let map = from_str(text).unwrap();
+        assert_eq!(1, map.layers().len());
+        assert_eq!(2, map.draw_order().len());
+        let river = map.features().next().unwrap();
+        assert_eq!(BezierFeatureType::River, river.feature_type());
+        assert!(matches!(&river.style, Style::River(r) if r.width(0) == 25.0));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            from_str("{\"layers\": []}"),
+            Err(MapFileError::MissingVersion)
+        ));
+        assert!(matches!(
+            from_str("{\"version\": 99}"),
+            Err(MapFileError::UnsupportedVersion(99))
+        ));
+        let error = from_str("{\"version\": 2, \"layers\": [}").unwrap_err();
+        assert!(matches!(error, MapFileError::Json(_)));
+        assert!(error.to_string().contains("line 1"));
+
+        let text = to_string(&map()).unwrap();
+        let unknown = text.replacen("\"layer\": 2", "\"layer\": 7", 1);
+        assert!(matches!(
+            from_str(&unknown),
+            Err(MapFileError::UnknownLayer { layer: 7, .. })
+        ));
+        // move the start of the second segment away from the first segment
+        let mut value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        value["features"][0]["segments"][1][0] = serde_json::json!([0.0, 0.0]);
+        let error = from_str(&value.to_string()).unwrap_err();
+        assert_eq!(
+            "feature \"Main Street\" has segments that are not joined",
+            error.to_string()
+        );
+
+        let mut value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        value["features"][1]["style"]["widths"][1] = serde_json::json!(-5.0);
+        let error = from_str(&value.to_string()).unwrap_err();
+        assert_eq!(
+            "feature \"Creek\" is a river with a negative width",
+            error.to_string()
+        );
+        let mut value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        value["features"][1]["style"] = serde_json::json!({
+            "type": "railroad",
+            "gauge": 1.435,
+            "tie_spacing": 0.6,
+            "tie_length": -2.6,
+            "tie_width": 0.25
+        });
+        assert!(matches!(
+            from_str(&value.to_string()),
+            Err(MapFileError::InvalidFeature { .. })
+        ));
+
+        // attach the creek to the junction
+        let mut value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        value["junctions"][0]["approaches"][1]["feature"] = value["features"][1]["id"].clone();
+        let error = from_str(&value.to_string()).unwrap_err();
+        assert_eq!(
+            "junction 0 joins a feature that is not a road",
+            error.to_string()
+        );
+    }
+}
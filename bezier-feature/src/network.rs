@@ -0,0 +1,458 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use geometry::Point;
+
+use crate::{
+    junction::{Junction, Location},
+    map::{FeatureId, Map},
+    BezierFeature, BezierFeatureType,
+};
+
+/// The part of a road centerline between the parameters t0 and t1 of one of
+/// its segments. A span travelled against the direction of the centerline has
+/// t0 greater than t1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteSpan {
+    pub feature: FeatureId,
+    pub segment: usize,
+    pub t0: f32,
+    pub t1: f32,
+}
+
+impl RouteSpan {
+    fn reversed(&self) -> Self {
+        RouteSpan {
+            t0: self.t1,
+            t1: self.t0,
+            ..*self
+        }
+    }
+}
+
+/// A path through the network from the first to the last node
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub length: f32,
+    pub nodes: Vec<usize>,
+    pub spans: Vec<RouteSpan>,
+}
+
+/// A road between two nodes with the spans from the first to the second node
+#[derive(Debug, Clone)]
+struct Edge {
+    from: usize,
+    to: usize,
+    length: f32,
+    spans: Vec<RouteSpan>,
+}
+
+/// A node waiting to be visited in the order of its estimated route length
+#[derive(Debug, Clone, Copy)]
+struct Visit {
+    estimate: f32,
+    length: f32,
+    node: usize,
+}
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed so the binary heap pops the shortest estimate first
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// The graph of a road network. There is a node for each junction and for
+/// each road end that is not at a junction. The edges are the parts of the
+/// roads between nodes weighted by the arc length of the centerline.
+#[derive(Debug, Clone, Default)]
+pub struct RoadNetwork {
+    nodes: Vec<Point>,
+    edges: Vec<Edge>,
+    adjacent: Vec<Vec<usize>>,
+}
+
+impl RoadNetwork {
+    /// Creates the network of the roads and the junctions joining them. The
+    /// node of each junction has the index of the junction and the nodes of
+    /// the free road ends follow.
+    pub fn new(roads: &[(FeatureId, &BezierFeature)], junctions: &[Junction]) -> Self {
+        let mut network = RoadNetwork {
+            nodes: junctions.iter().map(|j| j.center()).collect(),
+            ..Default::default()
+        };
+        for &(feature, road) in roads.iter() {
+            if road.segments().is_empty() {
+                continue;
+            }
+            let last = road.segments().len() - 1;
+            let find = |location: Location| {
+                junctions.iter().position(|j| {
+                    j.approaches()
+                        .iter()
+                        .any(|a| a.feature == feature && a.location == location)
+                })
+            };
+            // the nodes along the road in the direction of the centerline
+            let mut stops = Vec::<(usize, f32, usize)>::new();
+            let start = find(Location::Start)
+                .unwrap_or_else(|| network.add_node(road.segments()[0].ctrl_point(0)));
+            stops.push((0, 0.0, start));
+            for (node, junction) in junctions.iter().enumerate() {
+                for approach in junction.approaches() {
+                    if let (true, Location::Crossing { segment, t }) =
+                        (approach.feature == feature, approach.location)
+                    {
+                        stops.push((segment, t, node));
+                    }
+                }
+            }
+            let end = find(Location::End)
+                .unwrap_or_else(|| network.add_node(road.segments()[last].ctrl_point(3)));
+            stops.push((last, 1.0, end));
+            stops.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+            for pair in stops.windows(2) {
+                let (s0, t0, from) = pair[0];
+                let (s1, t1, to) = pair[1];
+                let mut spans = Vec::<RouteSpan>::new();
+                let mut length = 0.0;
+                for segment in s0..=s1 {
+                    let t0 = if segment == s0 { t0 } else { 0.0 };
+                    let t1 = if segment == s1 { t1 } else { 1.0 };
+                    if t1 - t0 <= f32::EPSILON {
+                        continue;
+                    }
+                    length += road.segments()[segment].segment(t0, t1).len();
+                    spans.push(RouteSpan {
+                        feature,
+                        segment,
+                        t0,
+                        t1,
+                    });
+                }
+                if !spans.is_empty() {
+                    network.add_edge(Edge {
+                        from,
+                        to,
+                        length,
+                        spans,
+                    });
+                }
+            }
+        }
+        network
+    }
+
+    /// Creates the network of the roads of the map and its junctions
+    pub fn from_map(map: &Map) -> Self {
+        let roads: Vec<(FeatureId, &BezierFeature)> = map
+            .features()
+            .filter(|f| f.feature_type() == BezierFeatureType::Road)
+            .map(|f| (f.id(), &f.feature))
+            .collect();
+        Self::new(&roads, map.junctions())
+    }
+
+    pub fn nodes(&self) -> &[Point] {
+        &self.nodes
+    }
+
+    /// Gets the node closest to the point
+    pub fn nearest_node(&self, p: &Point) -> Option<usize> {
+        (0..self.nodes.len()).min_by(|a, b| {
+            self.nodes[*a]
+                .distance(p)
+                .total_cmp(&self.nodes[*b].distance(p))
+        })
+    }
+
+    /// Gets the shortest route between the nodes using Dijkstra's algorithm
+    pub fn shortest_path(&self, from: usize, to: usize) -> Option<Route> {
+        self.search(from, to, |_| 0.0)
+    }
+
+    /// Gets the shortest route between the nodes using the A* algorithm with
+    /// the straight line distance to the destination as the heuristic. The
+    /// heuristic never overestimates since a road is never shorter than the
+    /// straight line between its ends.
+    pub fn a_star(&self, from: usize, to: usize) -> Option<Route> {
+        let goal = *self.nodes.get(to)?;
+        self.search(from, to, |node| self.nodes[node].distance(&goal))
+    }
+
+    fn add_node(&mut self, p: Point) -> usize {
+        self.nodes.push(p);
+        self.nodes.len() - 1
+    }
+
+    fn add_edge(&mut self, edge: Edge) {
+        let idx = self.edges.len();
+        self.adjacent.resize(self.nodes.len(), Vec::new());
+        self.adjacent[edge.from].push(idx);
+        if edge.to != edge.from {
+            self.adjacent[edge.to].push(idx);
+        }
+        self.edges.push(edge);
+    }
+
+    fn search<H: Fn(usize) -> f32>(&self, from: usize, to: usize, heuristic: H) -> Option<Route> {
+        if from >= self.nodes.len() || to >= self.nodes.len() {
+            return None;
+        }
+        let mut length = vec![f32::INFINITY; self.nodes.len()];
+        // the edge used to reach each node
+        let mut previous: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut queue = BinaryHeap::new();
+        length[from] = 0.0;
+        queue.push(Visit {
+            estimate: heuristic(from),
+            length: 0.0,
+            node: from,
+        });
+        while let Some(visit) = queue.pop() {
+            let node = visit.node;
+            if node == to {
+                break;
+            }
+            if visit.length > length[node] {
+                // a shorter route to the node was already visited
+                continue;
+            }
+            for &idx in self.adjacent.get(node).into_iter().flatten() {
+                let edge = &self.edges[idx];
+                let next = if edge.from == node {
+                    edge.to
+                } else {
+                    edge.from
+                };
+                let candidate = length[node] + edge.length;
+                if candidate < length[next] {
+                    length[next] = candidate;
+                    previous[next] = Some(idx);
+                    queue.push(Visit {
+                        estimate: candidate + heuristic(next),
+                        length: candidate,
+                        node: next,
+                    });
+                }
+            }
+        }
+        if length[to].is_infinite() {
+            return None;
+        }
+
+        // walk back from the destination to the start
+        let mut nodes = vec![to];
+        let mut spans = Vec::<RouteSpan>::new();
+        let mut node = to;
+        while let Some(idx) = previous[node] {
+            let edge = &self.edges[idx];
+            if edge.to == node {
+                spans.extend(edge.spans.iter().rev());
+                node = edge.from;
+            } else {
+                spans.extend(edge.spans.iter().map(|s| s.reversed()));
+                node = edge.to;
+            }
+            nodes.push(node);
+            if node == from {
+                break;
+            }
+        }
+        nodes.reverse();
+        spans.reverse();
+        Some(Route {
+            length: length[to],
+            nodes,
+            spans,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{junction::Approach, line_feature, map::Style, map_file, road::RoadMarkings};
+    const TEST_EPSILON: f32 = 0.1;
+
+    /// Three roads joining the corners of a triangle and a fourth road
+    /// crossing the first road half way
+    fn network() -> (RoadNetwork, Vec<FeatureId>) {
+        let (a, b, c) = (
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 300.0, y: 0.0 },
+            Point { x: 300.0, y: 300.0 },
+        );
+        let mut map = Map::new();
+        let layer = map.layers()[0].id();
+        let roads: Vec<FeatureId> = [
+            line_feature(a, b, 20.0),
+            line_feature(b, c, 20.0),
+            line_feature(a, c, 20.0),
+            line_feature(
+                Point {
+                    x: 150.0,
+                    y: -100.0,
+                },
+                Point { x: 150.0, y: 100.0 },
+                20.0,
+            ),
+        ]
+        .into_iter()
+        .map(|road| {
+            let style = Style::Road(RoadMarkings::default());
+            map.add_feature("road", road, style, layer).unwrap()
+        })
+        .collect();
+        for center in [a, b, c, Point { x: 150.0, y: 0.0 }] {
+            map.add_junction(center);
+        }
+        let crossing = Location::Crossing { segment: 0, t: 0.5 };
+        for (junction, feature, location) in [
+            (0, 0, Location::Start),
+            (0, 2, Location::Start),
+            (1, 0, Location::End),
+            (1, 1, Location::Start),
+            (2, 1, Location::End),
+            (2, 2, Location::End),
+            (3, 0, crossing),
+            (3, 3, crossing),
+        ] {
+            let feature = roads[feature];
+            map.attach(junction, Approach { feature, location });
+        }
+        (RoadNetwork::from_map(&map), roads)
+    }
+
+    #[test]
+    fn test_network() {
+        let (network, _) = network();
+        // 4 junctions and the 2 ends of the crossing road
+        assert_eq!(6, network.nodes().len());
+        assert_eq!(6, network.edges.len());
+        assert_eq!(Some(3), network.nearest_node(&Point { x: 140.0, y: 5.0 }));
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let (network, roads) = network();
+        let route = network.shortest_path(0, 2).unwrap();
+        assert!((route.length - 300.0 * std::f32::consts::SQRT_2).abs() < TEST_EPSILON);
+        assert_eq!(vec![0, 2], route.nodes);
+        assert_eq!(1, route.spans.len());
+
+        // from the south end of the crossing road to the east along the
+        // second half of the first road
+        let start = network
+            .nearest_node(&Point {
+                x: 150.0,
+                y: -100.0,
+            })
+            .unwrap();
+        let route = network.shortest_path(start, 1).unwrap();
+        assert!((route.length - 250.0).abs() < TEST_EPSILON);
+        assert_eq!(
+            vec![
+                RouteSpan {
+                    feature: roads[3],
+                    segment: 0,
+                    t0: 0.0,
+                    t1: 0.5
+                },
+                RouteSpan {
+                    feature: roads[0],
+                    segment: 0,
+                    t0: 0.5,
+                    t1: 1.0
+                },
+            ],
+            route.spans
+        );
+
+        // travelling against the centerline reverses the spans
+        let route = network.shortest_path(1, 3).unwrap();
+        assert_eq!(0.5, route.spans[0].t1);
+        assert!(route.spans[0].t0 > route.spans[0].t1);
+    }
+
+    #[test]
+    fn test_a_star() {
+        let (network, _) = network();
+        for from in 0..network.nodes().len() {
+            for to in 0..network.nodes().len() {
+                let dijkstra = network.shortest_path(from, to).unwrap();
+                let a_star = network.a_star(from, to).unwrap();
+                assert!((dijkstra.length - a_star.length).abs() < TEST_EPSILON);
+            }
+        }
+        assert!(RoadNetwork::default().a_star(0, 1).is_none());
+    }
+
+    #[test]
+    fn test_map_file() {
+        // a road from west to east crossed by a road from north to south and
+        // a river that is not part of the network
+        let text = r#"{
+            "version": 2,
+            "layers": [{"id": 1, "name": "Layer 1"}],
+            "features": [
+                {"id": 2, "name": "West", "layer": 1, "width": 20.0,
+                 "segments": [[[0, 0], [100, 0], [200, 0], [300, 0]]],
+                 "style": {"type": "road", "centerline": "solid",
+                           "line_width": 1.0, "line_separation": 1.0,
+                           "stripe": [3.0, 9.0], "edge_lines": false,
+                           "edge_inset": 1.0}},
+                {"id": 3, "name": "North", "layer": 1, "width": 20.0,
+                 "segments": [[[150, -150], [150, -50], [150, 50], [150, 150]]],
+                 "style": {"type": "road", "centerline": "solid",
+                           "line_width": 1.0, "line_separation": 1.0,
+                           "stripe": [3.0, 9.0], "edge_lines": false,
+                           "edge_inset": 1.0}},
+                {"id": 4, "name": "River", "layer": 1, "width": 20.0,
+                 "segments": [[[0, 100], [100, 100], [200, 100], [300, 100]]],
+                 "style": {"type": "river", "widths": [20.0],
+                           "flow": "downstream"}}
+            ],
+            "junctions": [
+                {"center": [150, 0], "fillet_radius": 10.0,
+                 "approaches": [
+                    {"feature": 2, "location": {"type": "crossing", "segment": 0, "t": 0.5}},
+                    {"feature": 3, "location": {"type": "crossing", "segment": 0, "t": 0.5}}
+                 ]}
+            ]
+        }"#;
+        let map = map_file::from_str(text).unwrap();
+        let network = RoadNetwork::from_map(&map);
+        // the junction and the ends of both roads
+        assert_eq!(5, network.nodes().len());
+        let from = network.nearest_node(&Point::default()).unwrap();
+        let to = network
+            .nearest_node(&Point {
+                x: 150.0,
+                y: -150.0,
+            })
+            .unwrap();
+        let route = network.a_star(from, to).unwrap();
+        assert!((route.length - 300.0).abs() < TEST_EPSILON);
+        assert_eq!(vec![from, 0, to], route.nodes);
+        assert_eq!(FeatureId(2), route.spans[0].feature);
+        assert_eq!(FeatureId(3), route.spans[1].feature);
+        // north is travelled against its centerline
+        assert_eq!(0.5, route.spans[1].t0);
+        assert_eq!(0.0, route.spans[1].t1);
+    }
+}
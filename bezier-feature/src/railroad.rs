@@ -0,0 +1,141 @@
+use geometry::{polygon::Polygon, polyline::Polyline, Point};
+
+use crate::BezierFeature;
+
+/// Standard gauge between the inner faces of the rails in metres
+pub const STANDARD_GAUGE: f32 = 1.435;
+/// Distance between the centers of two ties in metres
+pub const DEFAULT_TIE_SPACING: f32 = 0.6;
+pub const DEFAULT_TIE_LENGTH: f32 = 2.6;
+pub const DEFAULT_TIE_WIDTH: f32 = 0.25;
+
+/// The track of a railroad following the centerline of a feature. The rails
+/// are offset curves of the centerline, the ties are placed perpendicular to
+/// the centerline at a fixed arc length spacing and the ballast is the
+/// surface of the feature.
+#[derive(Debug, Clone, Copy)]
+pub struct Railroad {
+    pub gauge: f32,
+    pub tie_spacing: f32,
+    pub tie_length: f32,
+    pub tie_width: f32,
+}
+
+impl Default for Railroad {
+    fn default() -> Self {
+        Railroad {
+            gauge: STANDARD_GAUGE,
+            tie_spacing: DEFAULT_TIE_SPACING,
+            tie_length: DEFAULT_TIE_LENGTH,
+            tie_width: DEFAULT_TIE_WIDTH,
+        }
+    }
+}
+
+impl Railroad {
+    /// Creates a railroad with every dimension multiplied by the scale, for
+    /// example the number of map units per metre.
+    pub fn scaled(scale: f32) -> Self {
+        let r = Railroad::default();
+        Railroad {
+            gauge: r.gauge * scale,
+            tie_spacing: r.tie_spacing * scale,
+            tie_length: r.tie_length * scale,
+            tie_width: r.tie_width * scale,
+        }
+    }
+
+    /// Gets the left and right rail
+    pub fn rails(&self, feature: &mut BezierFeature) -> [Vec<Point>; 2] {
+        let half_gauge = self.gauge / 2.0;
+        [feature.offset(half_gauge), feature.offset(-half_gauge)]
+    }
+
+    /// Gets a rectangle for each tie. The first tie is half of the spacing
+    /// from the start of the centerline. There are no ties when the spacing
+    /// is not a positive finite number.
+    pub fn ties(&self, feature: &mut BezierFeature) -> Vec<Polygon> {
+        if !(self.tie_spacing.is_finite() && self.tie_spacing > 0.0) {
+            return Vec::new();
+        }
+        let centerline = Polyline::from(feature.offset(0.0));
+        let length = centerline.length();
+        let (half_length, half_width) = (self.tie_length / 2.0, self.tie_width / 2.0);
+        let mut ties = Vec::<Polygon>::new();
+        let mut distance = self.tie_spacing / 2.0;
+        while distance <= length {
+            let (Some(p), Some(tangent)) = (
+                centerline.point_at_distance(distance),
+                centerline.tangent_at_distance(distance),
+            ) else {
+                break;
+            };
+            let across = Point {
+                x: tangent.y,
+                y: -tangent.x,
+            } * half_length;
+            let along = tangent * half_width;
+            ties.push(Polygon::new(vec![
+                p - along - across,
+                p + along - across,
+                p + along + across,
+                p - along + across,
+            ]));
+            distance += self.tie_spacing;
+        }
+        ties
+    }
+
+    /// Gets the ballast bed under the track, which has the width of the
+    /// feature.
+    pub fn ballast(&self, feature: &mut BezierFeature) -> Polygon {
+        feature.surface().into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::straight_feature;
+    const TEST_EPSILON: f32 = 0.001;
+
+    #[test]
+    fn test_rails() {
+        let mut feature = straight_feature(40.0);
+        let railroad = Railroad::scaled(10.0);
+        let [left, right] = railroad.rails(&mut feature);
+        assert_eq!(left.len(), right.len());
+        for (l, r) in left.iter().zip(right.iter()) {
+            assert!((l.distance(r) - 14.35).abs() < TEST_EPSILON);
+        }
+        // the joint between the segments is not repeated
+        assert_eq!(feature.curve().len() - 1, left.len());
+    }
+
+    #[test]
+    fn test_ties() {
+        let mut feature = straight_feature(40.0);
+        let railroad = Railroad::scaled(10.0);
+        let ties = railroad.ties(&mut feature);
+        assert_eq!(100, ties.len());
+        let c = ties[0].centroid().unwrap();
+        assert!(c.distance(&Point { x: 3.0, y: 0.0 }) < TEST_EPSILON);
+        assert!((ties[0].area() - 26.0 * 2.5).abs() < 0.01);
+        let b = ties[0].bounds().unwrap();
+        assert!((b.height - 26.0).abs() < TEST_EPSILON);
+
+        assert!(Railroad::scaled(0.0).ties(&mut feature).is_empty());
+        let railroad = Railroad {
+            tie_spacing: f32::NAN,
+            ..Railroad::default()
+        };
+        assert!(railroad.ties(&mut feature).is_empty());
+    }
+
+    #[test]
+    fn test_ballast() {
+        let mut feature = straight_feature(40.0);
+        let ballast = Railroad::default().ballast(&mut feature);
+        assert!((ballast.area() - 600.0 * 40.0).abs() < 1.0);
+    }
+}
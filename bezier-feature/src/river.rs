@@ -0,0 +1,383 @@
+use geometry::{polygon::Polygon, Point};
+
+use crate::{BezierFeature, Closure};
+
+/// The direction the water flows relative to the direction of the
+/// centerline of the feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Flow {
+    /// The water flows from the first to the last control point
+    #[default]
+    Downstream,
+    /// The water flows from the last to the first control point
+    Upstream,
+}
+
+/// A river following the centerline of a feature. The width is set at each
+/// node, the start of the centerline and the end of each segment, and is
+/// interpolated along the arc length of the centerline with a monotone cubic
+/// so that it changes smoothly without overshooting the node widths.
+#[derive(Debug, Clone)]
+pub struct River {
+    widths: Vec<f32>,
+    flow: Flow,
+}
+
+impl River {
+    /// Creates a river with the width at each node. Nodes without a width
+    /// have the width of the last node with a width.
+    pub fn new(widths: Vec<f32>, flow: Flow) -> Self {
+        assert!(!widths.is_empty());
+        River { widths, flow }
+    }
+
+    /// Creates a river that widens linearly from the source width to the
+    /// mouth width in the direction of flow.
+    pub fn tapered(feature: &BezierFeature, source: f32, mouth: f32, flow: Flow) -> Self {
+        let nodes = feature.segments().len() + 1;
+        let (first, last) = match flow {
+            Flow::Downstream => (source, mouth),
+            Flow::Upstream => (mouth, source),
+        };
+        let widths = (0..nodes)
+            .map(|i| first + (last - first) * i as f32 / (nodes - 1) as f32)
+            .collect();
+        River { widths, flow }
+    }
+
+    pub fn flow(&self) -> Flow {
+        self.flow
+    }
+
+    pub fn set_flow(&mut self, flow: Flow) {
+        self.flow = flow;
+    }
+
+    /// Gets the width set at each node
+    pub fn widths(&self) -> &[f32] {
+        &self.widths
+    }
+
+    /// Gets the width at the node
+    pub fn width(&self, node: usize) -> f32 {
+        *self.widths.get(node).unwrap_or(self.widths.last().unwrap())
+    }
+
+    /// Sets the width at the node. The widths of the nodes between the last
+    /// node with a width and this node are set to the width of the last node.
+    pub fn set_width(&mut self, node: usize, width: f32) {
+        if node >= self.widths.len() {
+            let last = *self.widths.last().unwrap();
+            self.widths.resize(node + 1, last);
+        }
+        self.widths[node] = width;
+    }
+
+    /// Adds the node at the end of a segment inserted before the segment at
+    /// the index, to follow `BezierFeature::insert_segment`. The node has the
+    /// mean width of the nodes on either side.
+    pub fn insert_segment(&mut self, idx: usize) {
+        let width = (self.width(idx) + self.width(idx + 1)) / 2.0;
+        self.insert_node(idx + 1, width);
+    }
+
+    /// Adds the node splitting the segment, to follow
+    /// `BezierFeature::split_segment`. The width is interpolated at the
+    /// parameter between the nodes at the ends of the segment.
+    pub fn split_segment(&mut self, segment: usize, t: f32) {
+        let width = self.width(segment) * (1.0 - t) + self.width(segment + 1) * t;
+        self.insert_node(segment + 1, width);
+    }
+
+    /// Removes the node at the end of the segment, to follow
+    /// `BezierFeature::remove_joint`. Removing the closing joint of a closed
+    /// feature also moves the start to the following node.
+    pub fn remove_joint(&mut self, joint: usize, closing: bool) {
+        self.fill(joint + 2);
+        self.widths.remove(joint + 1);
+        if closing {
+            self.widths.remove(0);
+            self.widths.push(self.widths[0]);
+        }
+    }
+
+    /// Reverses the order of the nodes of a feature with the number of
+    /// segments, to follow `BezierFeature::reverse`. The flow is reversed
+    /// too, so the water keeps running the same way.
+    pub fn reverse(&mut self, segments: usize) {
+        self.fill(segments + 1);
+        self.widths.truncate(segments + 1);
+        self.widths.reverse();
+        self.flow = match self.flow {
+            Flow::Downstream => Flow::Upstream,
+            Flow::Upstream => Flow::Downstream,
+        };
+    }
+
+    fn insert_node(&mut self, node: usize, width: f32) {
+        self.fill(node);
+        self.widths.insert(node, width);
+    }
+
+    /// Sets a width for at least the number of nodes
+    fn fill(&mut self, nodes: usize) {
+        if self.widths.len() < nodes {
+            let last = *self.widths.last().unwrap();
+            self.widths.resize(nodes, last);
+        }
+    }
+
+    /// Gets the left and right bank of the river looking downstream. Each
+    /// bank has a point for each point of the centerline. A river closed
+    /// around an area, such as a lake, has a single bank along the outside.
+    pub fn banks(&self, feature: &mut BezierFeature) -> Vec<Vec<Point>> {
+        let samples = self.samples(feature);
+        let mut left = Vec::<Point>::with_capacity(samples.len());
+        let mut right = Vec::<Point>::with_capacity(samples.len());
+        for (p, n, w) in samples {
+            left.push(p + n * (w / 2.0));
+            right.push(p - n * (w / 2.0));
+        }
+        if feature.closure() == Closure::Area {
+            let area = |bank: &Vec<Point>| bank.iter().copied().collect::<Polygon>().area();
+            let outer = if area(&left) >= area(&right) {
+                left
+            } else {
+                right
+            };
+            return vec![outer];
+        }
+        match self.flow {
+            Flow::Downstream => vec![left, right],
+            Flow::Upstream => {
+                // upstream the left bank of the centerline is the right bank
+                // of the river and both banks run against the centerline
+                right.reverse();
+                left.reverse();
+                vec![right, left]
+            }
+        }
+    }
+
+    /// Gets the surface of the river as a polygon running down the left bank
+    /// and back up the right bank, or the area inside the bank of a river
+    /// closed around an area.
+    pub fn surface(&self, feature: &mut BezierFeature) -> Polygon {
+        let mut banks = self.banks(feature).into_iter();
+        let left = banks.next().unwrap();
+        let right = banks.next().unwrap_or_default();
+        left.into_iter().chain(right.into_iter().rev()).collect()
+    }
+
+    /// Gets points along the river spaced by the distance with the unit
+    /// direction of flow at each point, for drawing flow arrows. There are no
+    /// markers when the spacing is not a positive finite number.
+    pub fn flow_markers(&self, feature: &mut BezierFeature, spacing: f32) -> Vec<(Point, Point)> {
+        if !(spacing.is_finite() && spacing > 0.0) {
+            return Vec::new();
+        }
+        let centerline = geometry::polyline::Polyline::from(feature.offset(0.0));
+        let length = centerline.length();
+        let mut markers = Vec::<(Point, Point)>::new();
+        let mut distance = spacing / 2.0;
+        while distance < length {
+            let s = match self.flow {
+                Flow::Downstream => distance,
+                Flow::Upstream => length - distance,
+            };
+            if let (Some(p), Some(t)) = (
+                centerline.point_at_distance(s),
+                centerline.tangent_at_distance(s),
+            ) {
+                let direction = match self.flow {
+                    Flow::Downstream => t,
+                    Flow::Upstream => t * -1.0,
+                };
+                markers.push((p, direction));
+            }
+            distance += spacing;
+        }
+        markers
+    }
+
+    /// Gets each point of the centerline with its normal and the width of the
+    /// river at that point.
+    fn samples(&self, feature: &mut BezierFeature) -> Vec<(Point, Point, f32)> {
+        let stations = feature.stations();
+        // the node at the start of the centerline and at the end of each
+        // segment
+        let mut knots = vec![(0.0, self.width(0))];
+        for (i, station) in stations.iter().enumerate() {
+            let next = stations.get(i + 1);
+            if next.is_none_or(|next| next.segment != station.segment) {
+                knots.push((station.distance, self.width(knots.len())));
+            }
+        }
+        let slopes = monotone_slopes(&knots);
+
+        let mut knot = 0;
+        stations
+            .iter()
+            .map(|station| {
+                let s = station.distance;
+                while knot + 2 < knots.len() && s > knots[knot + 1].0 {
+                    knot += 1;
+                }
+                (
+                    station.point,
+                    station.normal,
+                    hermite(&knots, &slopes, knot, s),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Gets the slope at each knot of a monotone cubic interpolation using the
+/// Fritsch-Carlson method.
+fn monotone_slopes(knots: &[(f32, f32)]) -> Vec<f32> {
+    let n = knots.len();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+    let secants: Vec<f32> = knots
+        .windows(2)
+        .map(|k| {
+            let h = k[1].0 - k[0].0;
+            if h > f32::EPSILON {
+                (k[1].1 - k[0].1) / h
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let mut slopes = Vec::<f32>::with_capacity(n);
+    slopes.push(secants[0]);
+    for i in 1..n - 1 {
+        let (d0, d1) = (secants[i - 1], secants[i]);
+        slopes.push(if d0 * d1 <= 0.0 {
+            0.0
+        } else {
+            // harmonic mean keeps the interpolation monotone
+            2.0 * d0 * d1 / (d0 + d1)
+        });
+    }
+    slopes.push(secants[n - 2]);
+    slopes
+}
+
+/// Evaluates the cubic Hermite interpolation between knot i and i + 1 at s
+fn hermite(knots: &[(f32, f32)], slopes: &[f32], i: usize, s: f32) -> f32 {
+    if knots.len() < 2 {
+        return knots[0].1;
+    }
+    let (s0, w0) = knots[i];
+    let (s1, w1) = knots[i + 1];
+    let h = s1 - s0;
+    if h <= f32::EPSILON {
+        return w1;
+    }
+    let t = ((s - s0) / h).clamp(0.0, 1.0);
+    let (t2, t3) = (t * t, t * t * t);
+    (2.0 * t3 - 3.0 * t2 + 1.0) * w0
+        + (t3 - 2.0 * t2 + t) * h * slopes[i]
+        + (-2.0 * t3 + 3.0 * t2) * w1
+        + (t3 - t2) * h * slopes[i + 1]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{circle_feature, line_feature, straight_feature};
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn half_width(p: &Point) -> f32 {
+        p.y.abs()
+    }
+
+    #[test]
+    fn test_width_interpolation() {
+        let mut feature = straight_feature(20.0);
+        let river = River::new(vec![10.0, 20.0, 60.0], Flow::Downstream);
+        let banks = river.banks(&mut feature);
+        let (left, right) = (&banks[0], &banks[1]);
+        assert_eq!(left.len(), right.len());
+        assert!((half_width(&left[0]) - 5.0).abs() < TEST_EPSILON);
+        assert!((half_width(left.last().unwrap()) - 30.0).abs() < TEST_EPSILON);
+        // the width at the middle node is exact and the width never
+        // decreases along the river
+        let middle = left
+            .iter()
+            .position(|p| (p.x - 300.0).abs() < TEST_EPSILON)
+            .unwrap();
+        assert!((half_width(&left[middle]) - 10.0).abs() < TEST_EPSILON);
+        for w in left.windows(2) {
+            assert!(half_width(&w[1]) >= half_width(&w[0]) - TEST_EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_flow() {
+        let mut feature = straight_feature(20.0);
+        let mut river = River::tapered(&feature, 10.0, 50.0, Flow::Downstream);
+        assert_eq!(50.0, river.width(2));
+        let area = river.surface(&mut feature).area();
+        assert!((area - 600.0 * 30.0).abs() < 1.0);
+        let markers = river.flow_markers(&mut feature, 100.0);
+        assert_eq!(6, markers.len());
+        assert!(markers[0].1.distance(&Point { x: 1.0, y: 0.0 }) < TEST_EPSILON);
+
+        river.set_flow(Flow::Upstream);
+        let left = &river.banks(&mut feature)[0];
+        // looking upstream along the centerline the left bank is below
+        assert!(left[0].x > 599.0 && left[0].y > 0.0);
+        let markers = river.flow_markers(&mut feature, 100.0);
+        assert!(markers[0].0.x > 500.0);
+        assert!(markers[0].1.distance(&Point { x: -1.0, y: 0.0 }) < TEST_EPSILON);
+        assert!(river.flow_markers(&mut feature, 0.0).is_empty());
+        assert!(river.flow_markers(&mut feature, f32::NAN).is_empty());
+    }
+
+    #[test]
+    fn test_lake() {
+        let mut lake = circle_feature(Closure::Area);
+        let river = River::new(vec![20.0], Flow::Downstream);
+        assert_eq!(1, river.banks(&mut lake).len());
+        let area = std::f32::consts::PI * 110.0 * 110.0;
+        assert!((river.surface(&mut lake).area() - area).abs() < area * 0.01);
+
+        let mut ring = circle_feature(Closure::Ring);
+        assert_eq!(2, river.banks(&mut ring).len());
+    }
+
+    #[test]
+    fn test_node_edits() {
+        let mut feature = straight_feature(20.0);
+        let mut river = River::new(vec![10.0, 20.0, 60.0], Flow::Downstream);
+        feature.split_segment(1, 0.25);
+        river.split_segment(1, 0.25);
+        assert_eq!(&[10.0, 20.0, 30.0, 60.0], river.widths());
+        // the end of the river keeps the width of the last node
+        let left = &river.banks(&mut feature)[0];
+        assert!((half_width(left.last().unwrap()) - 30.0).abs() < TEST_EPSILON);
+
+        feature.remove_joint(0);
+        river.remove_joint(0, false);
+        assert_eq!(&[10.0, 30.0, 60.0], river.widths());
+        let segment = line_feature(Point { x: 600.0, y: 0.0 }, Point { x: 700.0, y: 0.0 }, 20.0)
+            .segments()[0]
+            .clone();
+        feature.insert_segment(2, &segment);
+        river.insert_segment(2);
+        assert_eq!(&[10.0, 30.0, 60.0, 60.0], river.widths());
+
+        feature.reverse();
+        river.reverse(feature.segments().len());
+        assert_eq!(&[60.0, 60.0, 30.0, 10.0], river.widths());
+        assert_eq!(Flow::Upstream, river.flow());
+
+        let mut ring = River::new(vec![10.0, 20.0, 30.0, 10.0], Flow::Downstream);
+        ring.remove_joint(2, true);
+        assert_eq!(&[20.0, 30.0, 20.0], ring.widths());
+    }
+}
@@ -0,0 +1,154 @@
+use geometry::{dash::DashPattern, polyline::Polyline};
+
+use crate::BezierFeature;
+
+pub const DEFAULT_ROAD_WIDTH: f32 = 50.0;
+/// Width of a painted line
+pub const DEFAULT_LINE_WIDTH: f32 = 2.0;
+/// Distance between the two lines of a double line
+pub const DEFAULT_LINE_SEPARATION: f32 = 5.0;
+/// Distance of an edge line from the edge of the road
+pub const DEFAULT_EDGE_INSET: f32 = 3.0;
+/// Dash and gap length of a striped line
+pub const DEFAULT_STRIPE: [f32; 2] = [12.0, 18.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CenterLine {
+    #[default]
+    Solid,
+    DoubleSolid,
+    Stripe,
+    /// A passing zone with a stripe on the left of the centerline and a
+    /// solid line on the right, so passing is allowed for traffic on the
+    /// left side of the road only.
+    StripeSolid,
+}
+
+/// The painted lines of a road. Each line is a polyline along the middle of
+/// the paint that a renderer strokes with the line width.
+#[derive(Debug, Clone)]
+pub struct RoadMarkings {
+    pub centerline: CenterLine,
+    pub line_width: f32,
+    pub line_separation: f32,
+    pub stripe: DashPattern,
+    pub edge_lines: bool,
+    pub edge_inset: f32,
+}
+
+impl Default for RoadMarkings {
+    fn default() -> Self {
+        RoadMarkings {
+            centerline: CenterLine::default(),
+            line_width: DEFAULT_LINE_WIDTH,
+            line_separation: DEFAULT_LINE_SEPARATION,
+            stripe: DashPattern::new(&DEFAULT_STRIPE, 0.0),
+            edge_lines: true,
+            edge_inset: DEFAULT_EDGE_INSET,
+        }
+    }
+}
+
+impl RoadMarkings {
+    pub fn new(centerline: CenterLine, edge_lines: bool) -> Self {
+        RoadMarkings {
+            centerline,
+            edge_lines,
+            ..Default::default()
+        }
+    }
+
+    /// Gets the lines dividing the two directions of traffic in the style of
+    /// the centerline
+    pub fn centerline(&self, feature: &mut BezierFeature) -> Vec<Polyline> {
+        let half = self.line_separation / 2.0;
+        let solid = |feature: &mut BezierFeature, distance: f32| {
+            vec![Polyline::from(feature.offset(distance))]
+        };
+        let stripe = |feature: &mut BezierFeature, distance: f32| {
+            self.stripe
+                .dash_polyline(&Polyline::from(feature.offset(distance)))
+        };
+        match self.centerline {
+            CenterLine::Solid => solid(feature, 0.0),
+            CenterLine::DoubleSolid => {
+                let mut lines = solid(feature, half);
+                lines.append(&mut solid(feature, -half));
+                lines
+            }
+            CenterLine::Stripe => stripe(feature, 0.0),
+            CenterLine::StripeSolid => {
+                let mut lines = stripe(feature, half);
+                lines.append(&mut solid(feature, -half));
+                lines
+            }
+        }
+    }
+
+    /// Gets the solid lines along the left and right edge of the road or no
+    /// lines when edge lines are turned off
+    pub fn edge_lines(&self, feature: &mut BezierFeature) -> Vec<Polyline> {
+        if !self.edge_lines {
+            return Vec::new();
+        }
+        let distance = feature.width() / 2.0 - self.edge_inset;
+        vec![
+            Polyline::from(feature.offset(distance)),
+            Polyline::from(feature.offset(-distance)),
+        ]
+    }
+
+    /// Gets every line of the road markings
+    pub fn lines(&self, feature: &mut BezierFeature) -> Vec<Polyline> {
+        let mut lines = self.centerline(feature);
+        lines.append(&mut self.edge_lines(feature));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::straight_feature;
+    const TEST_EPSILON: f32 = 0.001;
+
+    #[test]
+    fn test_centerline_styles() {
+        let mut feature = straight_feature(DEFAULT_ROAD_WIDTH);
+        let mut markings = RoadMarkings::new(CenterLine::Solid, false);
+        let lines = markings.centerline(&mut feature);
+        assert_eq!(1, lines.len());
+        assert!((lines[0].length() - 600.0).abs() < TEST_EPSILON);
+
+        markings.centerline = CenterLine::DoubleSolid;
+        let lines = markings.centerline(&mut feature);
+        assert_eq!(2, lines.len());
+        assert!((lines[0].points()[0].y + 2.5).abs() < TEST_EPSILON);
+        assert!((lines[1].points()[0].y - 2.5).abs() < TEST_EPSILON);
+
+        // 12 unit dashes every 30 units
+        markings.centerline = CenterLine::Stripe;
+        assert_eq!(20, markings.centerline(&mut feature).len());
+
+        markings.centerline = CenterLine::StripeSolid;
+        let lines = markings.centerline(&mut feature);
+        assert_eq!(21, lines.len());
+        let solid = lines.last().unwrap();
+        assert!((solid.length() - 600.0).abs() < TEST_EPSILON);
+        assert!((solid.points()[0].y - 2.5).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_edge_lines() {
+        let mut feature = straight_feature(DEFAULT_ROAD_WIDTH);
+        let markings = RoadMarkings::default();
+        let lines = markings.edge_lines(&mut feature);
+        assert_eq!(2, lines.len());
+        assert!((lines[0].points()[0].y + 22.0).abs() < TEST_EPSILON);
+        assert!((lines[1].points()[0].y - 22.0).abs() < TEST_EPSILON);
+        assert_eq!(3, markings.lines(&mut feature).len());
+        assert!(RoadMarkings::new(CenterLine::Solid, false)
+            .edge_lines(&mut feature)
+            .is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use geometry::{bezier::Bezier, continuity, Point};
+
+/// Number of curvature samples taken along each segment of a centerline
+const SAMPLES_PER_SEGMENT: usize = 32;
+/// Maximum superelevation rate of the road surface
+pub const DEFAULT_SUPERELEVATION: f32 = 0.08;
+/// Largest change in direction at a joint that is not reported as a kink
+pub const DEFAULT_MAX_KINK_ANGLE: f32 = 0.5 * std::f32::consts::PI / 180.0;
+
+/// The geometric design criteria of a road. Lengths are in map units which
+/// are taken to be metres.
+#[derive(Debug, Clone, Copy)]
+pub struct DesignCriteria {
+    pub min_radius: f32,
+    pub max_curvature_jump: f32,
+    pub max_kink_angle: f32,
+}
+
+impl DesignCriteria {
+    /// Creates the criteria for a design speed in km/h using the point mass
+    /// formula R = V² / 127(e + f) with the default superelevation and a
+    /// side friction factor that falls linearly with speed. The largest
+    /// curvature change allowed at a joint is half of the curvature of the
+    /// minimum radius.
+    pub fn for_design_speed(speed: f32) -> Self {
+        let side_friction = (0.188 - 0.00065 * speed).clamp(0.08, 0.17);
+        let min_radius = speed * speed / (127.0 * (DEFAULT_SUPERELEVATION + side_friction));
+        DesignCriteria {
+            min_radius,
+            max_curvature_jump: 0.5 / min_radius,
+            max_kink_angle: DEFAULT_MAX_KINK_ANGLE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagnosticKind {
+    /// A stretch of a segment is tighter than the minimum radius. The radius
+    /// is the smallest radius in the stretch.
+    MinimumRadius { radius: f32 },
+    /// The curvature changes abruptly at a joint
+    CurvatureJump { before: f32, after: f32 },
+    /// The centerline changes direction at a joint by the angle in radians
+    Kink { angle: f32 },
+}
+
+/// A violation of the design criteria. A stretch within a segment runs from
+/// the parameter t0 to t1 and a joint has t0 and t1 of 1.0 at the end of the
+/// segment. The position is the point an editor should highlight.
+#[derive(Debug, Clone, Copy)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub segment: usize,
+    pub t0: f32,
+    pub t1: f32,
+    pub position: Point,
+}
+
+/// Validates a compound curve centerline against the design criteria.
+/// Diagnostics are ordered by their position along the centerline.
+pub fn validate(segments: &[Bezier], criteria: &DesignCriteria) -> Vec<Diagnostic> {
+    let max_curvature = 1.0 / criteria.min_radius;
+    let joints = continuity::analyze(segments);
+    let mut diagnostics = Vec::<Diagnostic>::new();
+    for (idx, b) in segments.iter().enumerate() {
+        // runs of samples sharper than the minimum radius
+        let mut run: Option<(f32, f32, f32)> = None;
+        for i in 0..=SAMPLES_PER_SEGMENT {
+            let t = i as f32 / SAMPLES_PER_SEGMENT as f32;
+            let k = b.curvature(t).abs();
+            if k > max_curvature {
+                run = match run {
+                    Some((t0, _, k_max)) if k > k_max => Some((t0, t, k)),
+                    Some(r) => Some(r),
+                    None => Some((t, t, k)),
+                };
+                if i < SAMPLES_PER_SEGMENT {
+                    continue;
+                }
+            }
+            if let Some((t0, sharpest, k_max)) = run.take() {
+                diagnostics.push(Diagnostic {
+                    kind: DiagnosticKind::MinimumRadius {
+                        radius: 1.0 / k_max,
+                    },
+                    segment: idx,
+                    t0,
+                    t1: t,
+                    position: b.point_at(sharpest),
+                });
+            }
+        }
+
+        let Some(joint) = joints.get(idx) else {
+            continue;
+        };
+        if let Some(d) = validate_joint(b, &segments[idx + 1], idx, joint.g1, criteria) {
+            diagnostics.push(d);
+        }
+    }
+    diagnostics
+}
+
+/// Validates the joint at the end of the segment at the index, where the
+/// next segment starts. The joint is g1 when the tangents match.
+pub(crate) fn validate_joint(
+    b: &Bezier,
+    next: &Bezier,
+    segment: usize,
+    g1: bool,
+    criteria: &DesignCriteria,
+) -> Option<Diagnostic> {
+    let (t0, t1) = (b.tangent(1.0), next.tangent(0.0));
+    let angle = t0.cross(&t1).atan2(t0.dot(&t1)).abs();
+    // a joint turning less than the largest kink is checked like a smooth
+    // joint
+    let kind = (!g1 && angle > criteria.max_kink_angle)
+        .then_some(DiagnosticKind::Kink { angle })
+        .or_else(|| {
+            let before = b.curvature(1.0);
+            let after = next.curvature(0.0);
+            ((before - after).abs() > criteria.max_curvature_jump)
+                .then_some(DiagnosticKind::CurvatureJump { before, after })
+        })?;
+    Some(Diagnostic {
+        kind,
+        segment,
+        t0: 1.0,
+        t1: 1.0,
+        position: b.ctrl_point(3),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn arc(radius: f32) -> Bezier {
+        // quarter circle turning left on screen
+        let k = 0.5523 * radius;
+        Bezier::new_with_ctrl_point(
+            [
+                Point { x: 0.0, y: 0.0 },
+                Point { x: k, y: 0.0 },
+                Point {
+                    x: radius,
+                    y: k - radius,
+                },
+                Point {
+                    x: radius,
+                    y: -radius,
+                },
+            ],
+            0.1,
+        )
+    }
+
+    #[test]
+    fn test_design_speed() {
+        let slow = DesignCriteria::for_design_speed(50.0);
+        let fast = DesignCriteria::for_design_speed(100.0);
+        assert!((slow.min_radius - 83.6).abs() < 1.0);
+        assert!(fast.min_radius > slow.min_radius * 3.0);
+    }
+
+    #[test]
+    fn test_minimum_radius() {
+        let criteria = DesignCriteria::for_design_speed(50.0);
+        assert!(validate(&[arc(200.0)], &criteria).is_empty());
+        let diagnostics = validate(&[arc(50.0)], &criteria);
+        assert_eq!(1, diagnostics.len());
+        let d = diagnostics[0];
+        assert_eq!(0.0, d.t0);
+        assert_eq!(1.0, d.t1);
+        match d.kind {
+            DiagnosticKind::MinimumRadius { radius } => assert!((radius - 50.0).abs() < 1.0),
+            _ => panic!("unexpected diagnostic {:?}", d.kind),
+        }
+    }
+
+    #[test]
+    fn test_joints() {
+        let criteria = DesignCriteria::for_design_speed(50.0);
+        let straight = Bezier::new_with_ctrl_point(
+            [
+                Point { x: -300.0, y: 0.0 },
+                Point { x: -200.0, y: 0.0 },
+                Point { x: -100.0, y: 0.0 },
+                Point { x: 0.0, y: 0.0 },
+            ],
+            0.1,
+        );
+        // a straight road straight into a curve of radius 100
+        let diagnostics = validate(&[straight.clone(), arc(100.0)], &criteria);
+        assert_eq!(1, diagnostics.len());
+        assert!(matches!(
+            diagnostics[0].kind,
+            DiagnosticKind::CurvatureJump { .. }
+        ));
+
+        let kinked = Bezier::new_with_ctrl_point(
+            [
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 100.0, y: 100.0 },
+                Point { x: 200.0, y: 200.0 },
+                Point { x: 300.0, y: 300.0 },
+            ],
+            0.1,
+        );
+        let diagnostics = validate(&[straight.clone(), kinked], &criteria);
+        assert_eq!(1, diagnostics.len());
+        match diagnostics[0].kind {
+            DiagnosticKind::Kink { angle } => {
+                assert!((angle - std::f32::consts::FRAC_PI_4).abs() < 0.001)
+            }
+            _ => panic!("unexpected diagnostic {:?}", diagnostics[0].kind),
+        }
+        assert!(diagnostics[0].position.distance(&Point::default()) < 0.001);
+
+        // a slight kink below the largest kink angle into the curve still
+        // reports the curvature jump
+        let mut slight = arc(100.0);
+        slight.set_ctrl_point(Point { x: 55.23, y: 0.2 }, 1);
+        let diagnostics = validate(&[straight, slight], &criteria);
+        assert_eq!(1, diagnostics.len());
+        assert!(matches!(
+            diagnostics[0].kind,
+            DiagnosticKind::CurvatureJump { .. }
+        ));
+    }
+}
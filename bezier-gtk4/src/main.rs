@@ -1,47 +1,123 @@
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 
+use bezier_feature::history::History;
+use bezier_feature::map::{Map, Selection, Style};
+use bezier_feature::map_file;
+use bezier_feature::road::RoadMarkings;
+use bezier_feature::BezierFeature;
 use geometry::Point;
-use gtk::gdk::ModifierType;
+use gtk::gdk::{Key, ModifierType};
 use gtk::{prelude::*, DrawingArea};
 use gtk::{Application, ApplicationWindow};
 
-use geometry::bezier::Bezier;
+use geometry::dash::DashPattern;
+use geometry::polyline::Polyline;
 
 const HANDLE_RADIUS: f32 = 5.0;
 const HANDLE_LINE_WIDTH: f64 = 1.0;
 const HANDLE_GRAY: f64 = 0.25;
 const HANDLE_SELECT_RED: f64 = 0.8;
+const HANDLE_DASH: [f32; 2] = [2.0, 1.0];
 
 trait Draw {
     fn draw(&self, context: &cairo::Context);
     fn draw_mut(&mut self, context: &cairo::Context);
 }
 
+/// The map being edited, the file it was opened from and the history of its
+/// edits
 #[derive(Debug, Clone)]
-struct BezierRender {
-    bezier: Bezier,
-    selected_ctrl_pt: Option<usize>,
+struct MapRender {
+    map: Map,
+    map_path: Option<PathBuf>,
+    history: History,
+    selected: Option<Selection>,
 }
 
-impl Draw for BezierRender {
+impl MapRender {
+    /// Creates the render state with the map in the file. The example map is
+    /// used when there is no file or the file can not be read, and the error
+    /// is returned to be shown once the window is open.
+    fn new(map_path: Option<PathBuf>) -> (Self, Option<String>) {
+        let mut error = None;
+        let map = match map_path.as_deref().filter(|path| path.exists()) {
+            Some(path) => map_file::load(path).unwrap_or_else(|e| {
+                error = Some(format!("Unable to open {}: {e}", path.display()));
+                Self::example_map()
+            }),
+            None => Self::example_map(),
+        };
+        let render = MapRender {
+            map,
+            map_path,
+            history: History::default(),
+            selected: None,
+        };
+        (render, error)
+    }
+
+    /// Creates a map with a single road
+    fn example_map() -> Map {
+        let mut feature = BezierFeature::new_with_attributes(20.0, false);
+        feature.set_ctrl_point(0, Point { x: 0.0, y: 0.0 });
+        feature.set_ctrl_point(1, Point { x: 50.0, y: 0.0 });
+        feature.set_ctrl_point(2, Point { x: 100.0, y: 100.0 });
+        feature.set_ctrl_point(3, Point { x: 150.0, y: 100.0 });
+        let mut map = Map::new();
+        let layer = map.layers()[0].id();
+        map.add_feature("Road", feature, Style::Road(RoadMarkings::default()), layer);
+        map
+    }
+
+    /// Saves the map to the file it was opened from
+    fn save(&self) -> Result<(), String> {
+        match self.map_path.as_deref() {
+            Some(path) => map_file::save(&self.map, path)
+                .map_err(|e| format!("Unable to save {}: {e}", path.display())),
+            None => Ok(()),
+        }
+    }
+
+    /// Finds the control point at the position on the top most feature
+    fn in_control_point(&mut self, x: f64, y: f64) -> Option<Selection> {
+        let p = Point {
+            x: x as f32,
+            y: y as f32,
+        };
+        self.map
+            .hit_test(&p, HANDLE_RADIUS)
+            .filter(|hit| hit.ctrl_point.is_some())
+    }
+}
+
+impl Draw for MapRender {
     fn draw(&self, _context: &cairo::Context) {
         unimplemented!();
     }
 
     fn draw_mut(&mut self, context: &cairo::Context) {
-        let curve = self.bezier.curve();
-        context.set_source_rgb(0.0, 0.0, 0.0);
-        context.move_to(curve[0].x as f64, curve[0].y as f64);
-        for p in curve.iter().skip(1) {
-            context.line_to(p.x as f64, p.y as f64);
-        }
-        context.stroke().expect("Unable to draw");
-        context.set_line_width(HANDLE_LINE_WIDTH);
-        context.set_source_rgb(HANDLE_GRAY, HANDLE_GRAY, HANDLE_GRAY);
-        for (i, p) in self.bezier.ctrl_points().iter().enumerate() {
-            if let Some(s) = self.selected_ctrl_pt {
-                if s == i {
+        for id in self.map.draw_order() {
+            let selected = self
+                .selected
+                .filter(|s| s.feature == id)
+                .and_then(|s| s.ctrl_point);
+            let feature = match self.map.feature_mut(id) {
+                Some(f) => &mut f.feature,
+                None => continue,
+            };
+            let curve = feature.curve();
+            context.set_line_width(HANDLE_LINE_WIDTH);
+            context.set_source_rgb(0.0, 0.0, 0.0);
+            context.move_to(curve[0].x as f64, curve[0].y as f64);
+            for p in curve.iter().skip(1) {
+                context.line_to(p.x as f64, p.y as f64);
+            }
+            context.stroke().expect("Unable to draw");
+            context.set_source_rgb(HANDLE_GRAY, HANDLE_GRAY, HANDLE_GRAY);
+            for (i, p) in feature.into_iter().enumerate() {
+                if selected == Some(i) {
                     context.set_source_rgb(HANDLE_SELECT_RED, 0.0, 0.0);
                     context.arc(
                         p.x as f64,
@@ -53,40 +129,62 @@ impl Draw for BezierRender {
                     context.fill().expect("unable to draw to context");
                     context.set_source_rgb(HANDLE_GRAY, HANDLE_GRAY, HANDLE_GRAY);
                 }
+                context.arc(
+                    p.x as f64,
+                    p.y as f64,
+                    HANDLE_RADIUS as f64,
+                    0.0,
+                    std::f64::consts::TAU,
+                );
+                context.stroke().expect("unable to draw to context");
+            }
+            let pattern = DashPattern::new(&HANDLE_DASH, 0.0);
+            for segment in feature.segments() {
+                let p = segment.ctrl_points();
+                for handle in [
+                    Polyline::new(vec![p[0], p[1]]),
+                    Polyline::new(vec![p[2], p[3]]),
+                ] {
+                    for dash in pattern.dash_polyline(&handle) {
+                        let points = dash.points();
+                        context.move_to(points[0].x as f64, points[0].y as f64);
+                        for p in points.iter().skip(1) {
+                            context.line_to(p.x as f64, p.y as f64);
+                        }
+                    }
+                }
             }
-            context.arc(
-                p.x as f64,
-                p.y as f64,
-                HANDLE_RADIUS as f64,
-                0.0,
-                std::f64::consts::TAU,
-            );
             context.stroke().expect("unable to draw to context");
         }
-        context.set_dash(&[2.0, 1.0], 0.0);
-        let p = self.bezier.ctrl_points();
-        context.move_to(p[0].x as f64, p[0].y as f64);
-        context.line_to(p[1].x as f64, p[1].y as f64);
-        context.move_to(p[2].x as f64, p[2].y as f64);
-        context.line_to(p[3].x as f64, p[3].y as f64);
-        context.stroke().expect("unable to draw to context");
     }
 }
 
+/// Shows the error in a dialog over the window
+fn show_error(window: &ApplicationWindow, message: &str) {
+    let dialog = gtk::MessageDialog::builder()
+        .transient_for(window)
+        .modal(true)
+        .message_type(gtk::MessageType::Error)
+        .buttons(gtk::ButtonsType::Close)
+        .text(message)
+        .build();
+    dialog.connect_response(|dialog, _| dialog.close());
+    dialog.show();
+}
+
 fn main() {
+    // the map file to open is the only argument and Ctrl+S saves the map to
+    // it, so GTK is only given the program name
+    let mut args = std::env::args();
+    let program: Vec<String> = args.next().into_iter().collect();
+    let map_path = args.next().map(PathBuf::from);
+
     let app = Application::builder()
         .application_id("org.bytetrail.Bezier")
         .build();
 
-    app.connect_activate(|app| {
-        let mut b = Bezier::new(0.025);
-        b.set_ctrl_point(Point { x: 50.0, y: 0.0 }, 1);
-        b.set_ctrl_point(Point { x: 100.0, y: 100.0 }, 2);
-        b.set_ctrl_point(Point { x: 150.0, y: 100.0 }, 3);
-        let render_context = BezierRender {
-            bezier: b,
-            selected_ctrl_pt: None,
-        };
+    app.connect_activate(move |app| {
+        let (render_context, error) = MapRender::new(map_path.clone());
         let context = Rc::new(RefCell::new(render_context));
 
         let window = ApplicationWindow::builder()
@@ -112,18 +210,46 @@ fn main() {
         let bezier_pressed = context.clone();
         g.connect_pressed(move |_g, _i, x, y| {
             let mut b = bezier_pressed.borrow_mut();
-            for (i, p) in b.bezier.ctrl_points().iter().enumerate() {
-                if p.distance(&Point {
-                    x: x as f32,
-                    y: y as f32,
-                }) < HANDLE_RADIUS
-                {
-                    b.selected_ctrl_pt = Some(i);
-                    break;
+            b.selected = b.in_control_point(x, y);
+        });
+        let bezier_released = context.clone();
+        g.connect_released(move |_g, _i, _x, _y| {
+            bezier_released.borrow_mut().history.end_drag();
+        });
+        window.set_child(Some(&view));
+
+        // Ctrl+S saves the map to the file it was opened from, Ctrl+Z and
+        // Ctrl+Y undo and redo the last edit
+        let k = gtk::EventControllerKey::new();
+        window.add_controller(&k);
+        let map_keys = context.clone();
+        let key_window = window.clone();
+        let key_view = view.clone();
+        k.connect_key_pressed(move |_k, key, _code, modifiers| {
+            if !modifiers.contains(ModifierType::CONTROL_MASK) {
+                return gtk::Inhibit(false);
+            }
+            let mut render_context = map_keys.borrow_mut();
+            let key = key.to_lower();
+            if key == Key::s {
+                if let Err(e) = render_context.save() {
+                    show_error(&key_window, &e);
+                }
+            } else if key == Key::z || key == Key::y {
+                let MapRender { map, history, .. } = &mut *render_context;
+                let changed = if key == Key::z {
+                    history.undo(map)
+                } else {
+                    history.redo(map)
+                };
+                if changed.is_some() {
+                    key_view.queue_draw();
                 }
+            } else {
+                return gtk::Inhibit(false);
             }
+            gtk::Inhibit(true)
         });
-        window.set_child(Some(&view));
 
         let m = gtk::EventControllerMotion::new();
         view.add_controller(&m);
@@ -136,37 +262,35 @@ fn main() {
             let modifiers = event.modifier_state();
             let mut render_context = bezier_drag.borrow_mut();
             if modifiers.bits() & ModifierType::BUTTON1_MASK.bits() != 0 {
-                if let Some(selected) = render_context.selected_ctrl_pt {
-                    // move the control point
-                    render_context.bezier.set_ctrl_point(
-                        Point {
-                            x: x as f32,
-                            y: y as f32,
-                        },
-                        selected,
-                    );
-                    view_drag_update.borrow_mut().queue_draw();
-                }
-            } else {
-                let prev_selected: Option<usize> = render_context.selected_ctrl_pt;
-                render_context.selected_ctrl_pt = None;
-                for (i, p) in render_context.bezier.ctrl_points().iter().enumerate() {
-                    if p.distance(&Point {
+                let selected = render_context.selected;
+                if let Some(Selection {
+                    feature,
+                    ctrl_point: Some(ctrl_point),
+                }) = selected
+                {
+                    // move the control point as one undo step per drag
+                    let point = Point {
                         x: x as f32,
                         y: y as f32,
-                    }) < HANDLE_RADIUS
-                    {
-                        render_context.selected_ctrl_pt = Some(i);
-                        break;
+                    };
+                    let MapRender { map, history, .. } = &mut *render_context;
+                    if history.drag(map, feature, ctrl_point, point) {
+                        view_drag_update.borrow_mut().queue_draw();
                     }
                 }
-                if prev_selected != render_context.selected_ctrl_pt {
+            } else {
+                let prev_selected = render_context.selected;
+                render_context.selected = render_context.in_control_point(x, y);
+                if prev_selected != render_context.selected {
                     view_drag_update.borrow_mut().queue_draw();
                 }
             }
         });
         window.show();
+        if let Some(error) = error {
+            show_error(&window, &error);
+        }
     });
 
-    app.run();
+    app.run_with_args(&program);
 }
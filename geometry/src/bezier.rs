@@ -28,14 +28,13 @@ impl Bezier {
     /// resolution - a value representing the resolution of the curve.
     /// 0.0 < value <= 1.0. A smaller value is a higher resolution
     pub fn new_with_ctrl_point(ctrl_point: [Point; 4], resolution: f32) -> Self {
-        let bezier = Bezier {
+        Bezier {
             ctrl_point,
             resolution,
             length: 0.0,
             modified: true,
             curve: None,
-        };
-        bezier
+        }
     }
 
     pub fn len(&mut self) -> f32 {
@@ -52,6 +51,10 @@ impl Bezier {
         self.length
     }
 
+    pub fn resolution(&self) -> f32 {
+        self.resolution
+    }
+
     pub fn set_resolution(&mut self, resolution: f32) {
         if self.resolution != resolution {
             self.resolution = resolution;
@@ -95,6 +98,116 @@ impl Bezier {
         self.modified
     }
 
+    /// Gets the point on the curve at the parameter t where 0.0 <= t <= 1.0
+    pub fn point_at(&self, t: f32) -> Point {
+        let p = &self.ctrl_point;
+        let mt = 1.0 - t;
+        mt * mt * mt * p[0] + 3.0 * mt * mt * t * p[1] + 3.0 * mt * t * t * p[2] + t * t * t * p[3]
+    }
+
+    /// Gets the first derivative of the curve at the parameter t. The
+    /// derivative is the quadratic Bézier curve defined by the derived
+    /// control points 3 * (P(i+1) - P(i)).
+    pub fn derivative(&self, t: f32) -> Point {
+        let p = &self.ctrl_point;
+        let mt = 1.0 - t;
+        3.0 * (mt * mt * (p[1] - p[0]) + 2.0 * mt * t * (p[2] - p[1]) + t * t * (p[3] - p[2]))
+    }
+
+    /// Gets the unit tangent of the curve at the parameter t. Where the
+    /// derivative vanishes, such as at an end point with a coincident handle,
+    /// the direction of the curve around t is used instead.
+    pub fn tangent(&self, t: f32) -> Point {
+        let d = self.derivative(t);
+        if d.length() > f32::EPSILON {
+            return d.normalize();
+        }
+        let delta = 0.001;
+        (self.point_at((t + delta).min(1.0)) - self.point_at((t - delta).max(0.0))).normalize()
+    }
+
+    /// Gets the unit normal of the curve at the parameter t. The normal is
+    /// the tangent rotated a quarter turn so that it points to the left of
+    /// the direction of the curve in screen coordinates.
+    pub fn normal(&self, t: f32) -> Point {
+        let tangent = self.tangent(t);
+        Point {
+            x: tangent.y,
+            y: -tangent.x,
+        }
+    }
+
+    /// Gets the parameter of each point in the curve returned by `curve()`
+    pub fn params(&self) -> Vec<f32> {
+        let size = (1.0 / self.resolution + 1.0) as usize;
+        let mut params: Vec<f32> = (0..size - 1).map(|i| self.resolution * i as f32).collect();
+        params.push(1.0);
+        params
+    }
+
+    /// Gets the curve offset along its normal by the distance. A positive
+    /// distance offsets to the left of the curve and a negative distance to
+    /// the right. The offset curve has a point for each point of `curve()`.
+    pub fn offset_curve(&mut self, distance: f32) -> Vec<Point> {
+        let params = self.params();
+        let normals: Vec<Point> = params.iter().map(|t| self.normal(*t)).collect();
+        self.curve()
+            .iter()
+            .zip(normals)
+            .map(|(p, n)| *p + n * distance)
+            .collect()
+    }
+
+    /// Splits the curve at the parameter t using de Casteljau's algorithm.
+    /// The two curves have the same shape as the original curve.
+    pub fn split(&self, t: f32) -> (Bezier, Bezier) {
+        let p = &self.ctrl_point;
+        let lerp = |a: Point, b: Point| a + (b - a) * t;
+        let p01 = lerp(p[0], p[1]);
+        let p12 = lerp(p[1], p[2]);
+        let p23 = lerp(p[2], p[3]);
+        let p012 = lerp(p01, p12);
+        let p123 = lerp(p12, p23);
+        let mid = lerp(p012, p123);
+        (
+            Bezier::new_with_ctrl_point([p[0], p01, p012, mid], self.resolution),
+            Bezier::new_with_ctrl_point([mid, p123, p23, p[3]], self.resolution),
+        )
+    }
+
+    /// Gets the part of the curve between the parameters t0 and t1
+    pub fn segment(&self, t0: f32, t1: f32) -> Bezier {
+        if t1 <= f32::EPSILON {
+            return Bezier::new_with_ctrl_point([self.ctrl_point[0]; 4], self.resolution);
+        }
+        let (head, _) = self.split(t1);
+        head.split(t0 / t1).1
+    }
+
+    /// Gets the curve traversed from the end to the start
+    pub fn reversed(&self) -> Bezier {
+        let p = &self.ctrl_point;
+        Bezier::new_with_ctrl_point([p[3], p[2], p[1], p[0]], self.resolution)
+    }
+
+    /// Gets the second derivative of the curve at the parameter t.
+    pub fn second_derivative(&self, t: f32) -> Point {
+        let p = &self.ctrl_point;
+        6.0 * ((1.0 - t) * (p[2] - 2.0 * p[1] + p[0]) + t * (p[3] - 2.0 * p[2] + p[1]))
+    }
+
+    /// Gets the signed curvature of the curve at the parameter t. The
+    /// curvature is the reciprocal of the radius of the osculating circle
+    /// and is positive where the curve turns in the direction of its normal.
+    pub fn curvature(&self, t: f32) -> f32 {
+        let d1 = self.derivative(t);
+        let speed = d1.length();
+        if speed < f32::EPSILON {
+            return 0.0;
+        }
+        -d1.cross(&self.second_derivative(t)) / (speed * speed * speed)
+    }
+
     /// Gets the bezier curve represented as a vector of 2D Points.
     pub fn curve(&mut self) -> &Vec<Point> {
         if self.modified {
@@ -112,7 +225,7 @@ impl Bezier {
         });
 
         for i in 1..size - 1 {
-            let t = self.resolution * (i - 1) as f32;
+            let t = self.resolution * i as f32;
             let x = self.ctrl_point[0].x * (1.0 - t).powf(3.0)
                 + self.ctrl_point[1].x * 3.0 * (1.0 - t).powf(2.0) * t
                 + self.ctrl_point[2].x * 3.0 * (1.0 - t) * t * t
@@ -157,6 +270,122 @@ mod test {
         assert!((b.len() - 10.0).abs() < TEST_EPSILON);
     }
 
+    #[test]
+    fn test_point_at() {
+        let b = Bezier::new_with_ctrl_point(
+            [
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.0, y: 10.0 },
+                Point { x: 10.0, y: 10.0 },
+                Point { x: 10.0, y: 0.0 },
+            ],
+            0.125,
+        );
+        let mid = b.point_at(0.5);
+        assert!((mid.x - 5.0).abs() < TEST_EPSILON);
+        assert!((mid.y - 7.5).abs() < TEST_EPSILON);
+        let d = b.derivative(0.0);
+        assert!(d.x.abs() < TEST_EPSILON);
+        assert!((d.y - 30.0).abs() < TEST_EPSILON);
+        let dd = b.second_derivative(0.5);
+        assert!(dd.x.abs() < TEST_EPSILON);
+        assert!((dd.y + 60.0).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_offset_curve() {
+        let mut b = Bezier::new_with_ctrl_point(
+            [
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 10.0, y: 0.0 },
+                Point { x: 20.0, y: 0.0 },
+            ],
+            0.25,
+        );
+        assert_eq!(b.curve().len(), b.params().len());
+        let mid = b.point_at(0.5);
+        assert!(b.curve()[2].distance(&mid) < TEST_EPSILON);
+        let left = b.offset_curve(5.0);
+        assert_eq!(5, left.len());
+        // the degenerate handle at the start still has a normal
+        assert!(left[0].distance(&Point { x: 0.0, y: -5.0 }) < TEST_EPSILON);
+        assert!(left[4].distance(&Point { x: 20.0, y: -5.0 }) < TEST_EPSILON);
+        let right = b.offset_curve(-5.0);
+        assert!(right[2].distance(&(mid + Point { x: 0.0, y: 5.0 })) < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_curvature() {
+        // quarter circle of radius 100 turning to the left on screen
+        let k = 0.5523 * 100.0;
+        let b = Bezier::new_with_ctrl_point(
+            [
+                Point { x: 100.0, y: 0.0 },
+                Point { x: 100.0, y: -k },
+                Point { x: k, y: -100.0 },
+                Point { x: 0.0, y: -100.0 },
+            ],
+            0.1,
+        );
+        for t in [0.0, 0.5, 1.0] {
+            assert!((b.curvature(t) - 0.01).abs() < 0.0005);
+        }
+        let reversed = Bezier::new_with_ctrl_point(
+            [
+                b.ctrl_point(3),
+                b.ctrl_point(2),
+                b.ctrl_point(1),
+                b.ctrl_point(0),
+            ],
+            0.1,
+        );
+        assert!((reversed.curvature(0.5) + 0.01).abs() < 0.0005);
+    }
+
+    #[test]
+    fn test_curve() {
+        let mut b = Bezier::new_with_ctrl_point(
+            [
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.0, y: 10.0 },
+                Point { x: 10.0, y: 10.0 },
+                Point { x: 10.0, y: 0.0 },
+            ],
+            0.25,
+        );
+        let first = b.point_at(0.25);
+        let curve = b.curve();
+        assert_eq!(5, curve.len());
+        assert!(curve[0].distance(&Point { x: 0.0, y: 0.0 }) < TEST_EPSILON);
+        assert!(curve[4].distance(&Point { x: 10.0, y: 0.0 }) < TEST_EPSILON);
+        // each point is sampled at its own parameter
+        assert!(curve[1].distance(&first) < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_split() {
+        let b = Bezier::new_with_ctrl_point(
+            [
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 0.0, y: 10.0 },
+                Point { x: 10.0, y: 10.0 },
+                Point { x: 10.0, y: 0.0 },
+            ],
+            0.125,
+        );
+        let (head, tail) = b.split(0.25);
+        assert!(head.point_at(1.0).distance(&b.point_at(0.25)) < TEST_EPSILON);
+        assert!(head.point_at(0.5).distance(&b.point_at(0.125)) < TEST_EPSILON);
+        assert!(tail.point_at(0.5).distance(&b.point_at(0.625)) < TEST_EPSILON);
+        let mid = b.segment(0.25, 0.75);
+        assert!(mid.point_at(0.0).distance(&b.point_at(0.25)) < TEST_EPSILON);
+        assert!(mid.point_at(0.5).distance(&b.point_at(0.5)) < TEST_EPSILON);
+        assert!(mid.point_at(1.0).distance(&b.point_at(0.75)) < TEST_EPSILON);
+        let reversed = b.reversed();
+        assert!(reversed.point_at(0.25).distance(&b.point_at(0.75)) < TEST_EPSILON);
+    }
+
     #[test]
     fn test_set_resolution() {
         let mut b = Bezier::new(0.25);
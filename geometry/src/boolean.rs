@@ -0,0 +1,788 @@
+//! Boolean operations on polygons and flattened paths.
+//!
+//! The edges of both operands are split at every intersection to form a
+//! planar arrangement. Each edge of the arrangement is then classified by the
+//! winding numbers of the subject and clip polygons on either side of it and
+//! kept when the result of the operation differs between the two sides. The
+//! kept edges are linked into closed contours with the inside on the left so
+//! that outer contours are `CounterClockwise` and holes are `Clockwise`. The
+//! result is the same under either fill rule.
+//!
+//! Holes and self-overlapping operands are handled by the fill rule, so a
+//! polygon with holes is passed as its outer contour and hole contours with
+//! the opposite orientation, or with any orientation using `EvenOdd`.
+
+use std::collections::HashMap;
+
+use crate::polygon::{FillRule, Polygon};
+use crate::polyline::Polyline;
+use crate::Point;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+impl BooleanOp {
+    fn apply(&self, subject: bool, clip: bool) -> bool {
+        match self {
+            BooleanOp::Union => subject || clip,
+            BooleanOp::Intersection => subject && clip,
+            BooleanOp::Difference => subject && !clip,
+            BooleanOp::Xor => subject != clip,
+        }
+    }
+}
+
+/// Snapping distance relative to the size of the operands
+const RELATIVE_EPSILON: f64 = 1.0e-6;
+
+#[derive(Debug, Clone, Copy)]
+struct Vec2 {
+    x: f64,
+    y: f64,
+}
+
+impl Vec2 {
+    fn sub(&self, o: &Vec2) -> Vec2 {
+        Vec2 {
+            x: self.x - o.x,
+            y: self.y - o.y,
+        }
+    }
+
+    fn lerp(&self, o: &Vec2, t: f64) -> Vec2 {
+        Vec2 {
+            x: self.x + (o.x - self.x) * t,
+            y: self.y + (o.y - self.y) * t,
+        }
+    }
+
+    fn cross(&self, o: &Vec2) -> f64 {
+        self.x * o.y - self.y * o.x
+    }
+
+    fn dot(&self, o: &Vec2) -> f64 {
+        self.x * o.x + self.y * o.y
+    }
+
+    fn length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    fn distance_to_segment(&self, a: &Vec2, b: &Vec2) -> f64 {
+        let ab = b.sub(a);
+        let len2 = ab.dot(&ab);
+        if len2 == 0.0 {
+            return self.sub(a).length();
+        }
+        let t = (self.sub(a).dot(&ab) / len2).clamp(0.0, 1.0);
+        self.sub(&a.lerp(b, t)).length()
+    }
+}
+
+impl From<&Point> for Vec2 {
+    fn from(p: &Point) -> Self {
+        Vec2 {
+            x: p.x as f64,
+            y: p.y as f64,
+        }
+    }
+}
+
+impl From<Vec2> for Point {
+    fn from(v: Vec2) -> Self {
+        Point {
+            x: v.x as f32,
+            y: v.y as f32,
+        }
+    }
+}
+
+/// An edge of one of the operands. Operand 0 is the subject and 1 the clip.
+struct Edge {
+    a: Vec2,
+    b: Vec2,
+    operand: usize,
+}
+
+impl Edge {
+    fn bounds(&self) -> Bounds {
+        Bounds::of_segment(&self.a, &self.b)
+    }
+}
+
+/// An axis aligned bounding box used to reject edges and points before the
+/// more expensive intersection and winding number tests.
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl Bounds {
+    fn of_segment(a: &Vec2, b: &Vec2) -> Bounds {
+        Bounds {
+            min: Vec2 {
+                x: a.x.min(b.x),
+                y: a.y.min(b.y),
+            },
+            max: Vec2 {
+                x: a.x.max(b.x),
+                y: a.y.max(b.y),
+            },
+        }
+    }
+
+    /// Gets the bounds of the edges of one operand, or `None` when the
+    /// operand has no edges.
+    fn of_operand(edges: &[Edge], operand: usize) -> Option<Bounds> {
+        edges
+            .iter()
+            .filter(|e| e.operand == operand)
+            .map(Edge::bounds)
+            .reduce(|a, b| a.union(&b))
+    }
+
+    fn union(&self, o: &Bounds) -> Bounds {
+        Bounds {
+            min: Vec2 {
+                x: self.min.x.min(o.min.x),
+                y: self.min.y.min(o.min.y),
+            },
+            max: Vec2 {
+                x: self.max.x.max(o.max.x),
+                y: self.max.y.max(o.max.y),
+            },
+        }
+    }
+
+    fn expand(&self, d: f64) -> Bounds {
+        Bounds {
+            min: Vec2 {
+                x: self.min.x - d,
+                y: self.min.y - d,
+            },
+            max: Vec2 {
+                x: self.max.x + d,
+                y: self.max.y + d,
+            },
+        }
+    }
+
+    fn overlaps(&self, o: &Bounds) -> bool {
+        self.min.x <= o.max.x
+            && o.min.x <= self.max.x
+            && self.min.y <= o.max.y
+            && o.min.y <= self.max.y
+    }
+
+    fn contains(&self, p: &Vec2) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+}
+
+/// A uniform grid of the bounds of the edges of the arrangement, used to
+/// find the edges near a point without testing every edge.
+struct EdgeGrid {
+    origin: Vec2,
+    cell: f64,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl EdgeGrid {
+    /// Creates a grid of about one cell per edge over the bounds of the edges
+    fn new(bounds: &[Bounds]) -> Self {
+        let extent = bounds
+            .iter()
+            .copied()
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or(Bounds {
+                min: Vec2 { x: 0.0, y: 0.0 },
+                max: Vec2 { x: 0.0, y: 0.0 },
+            });
+        let size = (extent.max.x - extent.min.x).max(extent.max.y - extent.min.y);
+        let divisions = (bounds.len() as f64).sqrt().ceil().max(1.0);
+        let cell = (size / divisions).max(f64::MIN_POSITIVE);
+        let columns = ((extent.max.x - extent.min.x) / cell) as usize + 1;
+        let rows = ((extent.max.y - extent.min.y) / cell) as usize + 1;
+        let mut grid = EdgeGrid {
+            origin: extent.min,
+            cell,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+        };
+        for (idx, b) in bounds.iter().enumerate() {
+            let (c0, r0, c1, r1) = grid.range(b);
+            for r in r0..=r1 {
+                for c in c0..=c1 {
+                    grid.cells[r * columns + c].push(idx);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Gets the columns and rows of the cells covered by the bounds
+    fn range(&self, b: &Bounds) -> (usize, usize, usize, usize) {
+        let column =
+            |x: f64| (((x - self.origin.x) / self.cell).max(0.0) as usize).min(self.columns - 1);
+        let row = |y: f64| (((y - self.origin.y) / self.cell).max(0.0) as usize).min(self.rows - 1);
+        (column(b.min.x), row(b.min.y), column(b.max.x), row(b.max.y))
+    }
+
+    /// Gets the sorted edges in the cells covered by the bounds
+    fn query(&self, b: &Bounds) -> Vec<usize> {
+        let (c0, r0, c1, r1) = self.range(b);
+        let mut found = Vec::<usize>::new();
+        for r in r0..=r1 {
+            for c in c0..=c1 {
+                found.extend_from_slice(&self.cells[r * self.columns + c]);
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+/// Computes the boolean operation between the subject and clip polygons. The
+/// fill rule decides which areas are inside each operand. Each returned
+/// polygon is a contour of the result; outer contours are
+/// `CounterClockwise` and holes are `Clockwise`.
+pub fn boolean(
+    subject: &[Polygon],
+    clip: &[Polygon],
+    op: BooleanOp,
+    rule: FillRule,
+) -> Vec<Polygon> {
+    let edges = collect_edges(subject, clip);
+    if edges.is_empty() {
+        return Vec::new();
+    }
+    let epsilon = snap_epsilon(&edges);
+    let splits = split_params(&edges, epsilon);
+
+    // snap the split points to shared vertices and build the unique edges of
+    // the arrangement with the signed number of operand edges along each
+    let mut vertices = VertexTable::new(epsilon);
+    let mut unique = HashMap::<(usize, usize), [i32; 2]>::new();
+    for (edge, params) in edges.iter().zip(splits) {
+        let ids: Vec<usize> = params
+            .iter()
+            .map(|t| vertices.insert(edge.a.lerp(&edge.b, *t)))
+            .collect();
+        for w in ids.windows(2) {
+            if w[0] == w[1] {
+                continue;
+            }
+            let key = (w[0].min(w[1]), w[0].max(w[1]));
+            let direction = if w[0] < w[1] { 1 } else { -1 };
+            unique.entry(key).or_insert([0, 0])[edge.operand] += direction;
+        }
+    }
+
+    let points = vertices.points;
+    let operand_bounds = [Bounds::of_operand(&edges, 0), Bounds::of_operand(&edges, 1)];
+    // a point outside the bounds of an operand has a winding number of zero
+    let winding = |operand: usize, p: &Vec2| match operand_bounds[operand] {
+        Some(bounds) if bounds.contains(p) => winding_number(&edges, operand, p),
+        _ => 0,
+    };
+    let mut keys: Vec<(usize, usize)> = unique.keys().copied().collect();
+    keys.sort_unstable();
+    let key_bounds: Vec<Bounds> = keys
+        .iter()
+        .map(|key| Bounds::of_segment(&points[key.0], &points[key.1]))
+        .collect();
+    let grid = EdgeGrid::new(&key_bounds);
+    let mut result_edges = Vec::<(usize, usize)>::new();
+    for (idx, key) in keys.iter().enumerate() {
+        let (u, v) = (&points[key.0], &points[key.1]);
+        let mid = u.lerp(v, 0.5);
+        let dir = v.sub(u);
+        let len = dir.length();
+        // step off the edge by less than the distance to any other edge so
+        // that the sample is in the face immediately to the left of the edge
+        // only edges closer than half the length of the edge can reduce the
+        // clearance below a quarter of the length
+        let mut clearance = len / 4.0;
+        let near = Bounds::of_segment(&mid, &mid).expand(len / 2.0);
+        for other_idx in grid.query(&near) {
+            if other_idx != idx && key_bounds[other_idx].overlaps(&near) {
+                let other = keys[other_idx];
+                let d = mid.distance_to_segment(&points[other.0], &points[other.1]);
+                clearance = clearance.min(d / 2.0);
+            }
+        }
+        let left = Vec2 {
+            x: mid.x - dir.y / len * clearance,
+            y: mid.y + dir.x / len * clearance,
+        };
+        let counts = unique[key];
+        let winding_left = [winding(0, &left), winding(1, &left)];
+        let winding_right = [winding_left[0] - counts[0], winding_left[1] - counts[1]];
+        let inside_left = op.apply(
+            is_inside(winding_left[0], rule),
+            is_inside(winding_left[1], rule),
+        );
+        let inside_right = op.apply(
+            is_inside(winding_right[0], rule),
+            is_inside(winding_right[1], rule),
+        );
+        match (inside_left, inside_right) {
+            (true, false) => result_edges.push((key.0, key.1)),
+            (false, true) => result_edges.push((key.1, key.0)),
+            _ => (),
+        }
+    }
+    link_contours(&points, &result_edges)
+}
+
+/// Clips the open polyline with the clip polygons. `Intersection` keeps the
+/// parts inside the clip polygons and `Difference` and `Xor` keep the parts
+/// outside. `Union` keeps the whole polyline since the polyline has no area.
+pub fn clip_polyline(
+    line: &Polyline,
+    clip: &[Polygon],
+    op: BooleanOp,
+    rule: FillRule,
+) -> Vec<Polyline> {
+    if op == BooleanOp::Union || line.len() < 2 {
+        return vec![line.clone()];
+    }
+    let keep_inside = op == BooleanOp::Intersection;
+    let clip_edges = collect_edges(clip, &[]);
+    let clip_bounds = Bounds::of_operand(&clip_edges, 0);
+
+    let mut result = Vec::<Polyline>::new();
+    let mut current = Vec::<Point>::new();
+    for s in line.points().windows(2) {
+        let (a, b) = (Vec2::from(&s[0]), Vec2::from(&s[1]));
+        let mut params = vec![0.0, 1.0];
+        for e in clip_edges.iter() {
+            if let Some((t, _)) = intersect(&a, &b, &e.a, &e.b) {
+                params.push(t);
+            }
+        }
+        params.sort_by(|a, b| a.total_cmp(b));
+        params.dedup();
+        for t in params.windows(2) {
+            let mid = a.lerp(&b, (t[0] + t[1]) / 2.0);
+            let inside = match clip_bounds {
+                Some(bounds) if bounds.contains(&mid) => {
+                    is_inside(winding_number(&clip_edges, 0, &mid), rule)
+                }
+                _ => false,
+            };
+            if inside == keep_inside {
+                if current.is_empty() {
+                    current.push(a.lerp(&b, t[0]).into());
+                }
+                current.push(a.lerp(&b, t[1]).into());
+            } else if !current.is_empty() {
+                result.push(Polyline::new(std::mem::take(&mut current)));
+            }
+        }
+    }
+    if !current.is_empty() {
+        result.push(Polyline::new(current));
+    }
+    result
+}
+
+impl Polygon {
+    pub fn union(&self, other: &Polygon) -> Vec<Polygon> {
+        self.boolean(other, BooleanOp::Union)
+    }
+
+    pub fn intersection(&self, other: &Polygon) -> Vec<Polygon> {
+        self.boolean(other, BooleanOp::Intersection)
+    }
+
+    pub fn difference(&self, other: &Polygon) -> Vec<Polygon> {
+        self.boolean(other, BooleanOp::Difference)
+    }
+
+    pub fn xor(&self, other: &Polygon) -> Vec<Polygon> {
+        self.boolean(other, BooleanOp::Xor)
+    }
+
+    fn boolean(&self, other: &Polygon, op: BooleanOp) -> Vec<Polygon> {
+        boolean(
+            std::slice::from_ref(self),
+            std::slice::from_ref(other),
+            op,
+            FillRule::NonZero,
+        )
+    }
+}
+
+fn collect_edges(subject: &[Polygon], clip: &[Polygon]) -> Vec<Edge> {
+    let mut edges = Vec::<Edge>::new();
+    for (operand, polygons) in [subject, clip].iter().enumerate() {
+        for polygon in polygons.iter() {
+            for (a, b) in polygon.edges() {
+                let (a, b) = (Vec2::from(a), Vec2::from(b));
+                if a.x != b.x || a.y != b.y {
+                    edges.push(Edge { a, b, operand });
+                }
+            }
+        }
+    }
+    edges
+}
+
+fn snap_epsilon(edges: &[Edge]) -> f64 {
+    let extent = edges
+        .iter()
+        .flat_map(|e| [e.a.x.abs(), e.a.y.abs(), e.b.x.abs(), e.b.y.abs()])
+        .fold(1.0, f64::max);
+    extent * RELATIVE_EPSILON
+}
+
+/// Finds the sorted parameters, including 0.0 and 1.0, at which each edge
+/// is split by the other edges.
+fn split_params(edges: &[Edge], epsilon: f64) -> Vec<Vec<f64>> {
+    let mut splits: Vec<Vec<f64>> = edges.iter().map(|_| vec![0.0, 1.0]).collect();
+    let bounds: Vec<Bounds> = edges.iter().map(|e| e.bounds().expand(epsilon)).collect();
+    let grid = EdgeGrid::new(&bounds);
+    for i in 0..edges.len() {
+        for j in grid.query(&bounds[i]) {
+            if j <= i || !bounds[i].overlaps(&bounds[j]) {
+                continue;
+            }
+            let (e, f) = (&edges[i], &edges[j]);
+            if let Some((t, u)) = intersect(&e.a, &e.b, &f.a, &f.b) {
+                splits[i].push(t);
+                splits[j].push(u);
+            }
+            // endpoints touching or overlapping the other edge
+            for (p, target, idx) in [(&f.a, e, i), (&f.b, e, i), (&e.a, f, j), (&e.b, f, j)] {
+                if let Some(t) = project_onto(p, target, epsilon) {
+                    splits[idx].push(t);
+                }
+            }
+        }
+    }
+    for params in splits.iter_mut() {
+        params.sort_by(|a, b| a.total_cmp(b));
+        params.dedup();
+    }
+    splits
+}
+
+/// Gets the parameters of the proper intersection of the segments a-b and
+/// c-d when they are not parallel.
+fn intersect(a: &Vec2, b: &Vec2, c: &Vec2, d: &Vec2) -> Option<(f64, f64)> {
+    let r = b.sub(a);
+    let s = d.sub(c);
+    let denominator = r.cross(&s);
+    if denominator.abs() <= f64::EPSILON * r.length() * s.length() {
+        return None;
+    }
+    let qp = c.sub(a);
+    let t = qp.cross(&s) / denominator;
+    let u = qp.cross(&r) / denominator;
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some((t, u))
+    } else {
+        None
+    }
+}
+
+/// Gets the parameter of the point on the edge when the point is within
+/// epsilon of the interior of the edge.
+fn project_onto(p: &Vec2, edge: &Edge, epsilon: f64) -> Option<f64> {
+    let ab = edge.b.sub(&edge.a);
+    let len2 = ab.dot(&ab);
+    let t = p.sub(&edge.a).dot(&ab) / len2;
+    if t <= 0.0 || t >= 1.0 {
+        return None;
+    }
+    if p.sub(&edge.a.lerp(&edge.b, t)).length() <= epsilon {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// Gets the winding number of the edges of one operand around the point
+fn winding_number(edges: &[Edge], operand: usize, p: &Vec2) -> i32 {
+    let mut winding = 0;
+    for e in edges.iter().filter(|e| e.operand == operand) {
+        let side = e.b.sub(&e.a).cross(&p.sub(&e.a));
+        if e.a.y <= p.y {
+            if e.b.y > p.y && side > 0.0 {
+                winding += 1;
+            }
+        } else if e.b.y <= p.y && side < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+fn is_inside(winding: i32, rule: FillRule) -> bool {
+    match rule {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::NonZero => winding != 0,
+    }
+}
+
+/// Merges points closer than epsilon into a single vertex
+struct VertexTable {
+    epsilon: f64,
+    points: Vec<Vec2>,
+    grid: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl VertexTable {
+    fn new(epsilon: f64) -> Self {
+        VertexTable {
+            epsilon,
+            points: Vec::new(),
+            grid: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, p: Vec2) -> usize {
+        let cell = (
+            (p.x / self.epsilon).floor() as i64,
+            (p.y / self.epsilon).floor() as i64,
+        );
+        for cx in cell.0 - 1..=cell.0 + 1 {
+            for cy in cell.1 - 1..=cell.1 + 1 {
+                if let Some(ids) = self.grid.get(&(cx, cy)) {
+                    for id in ids {
+                        if self.points[*id].sub(&p).length() <= self.epsilon {
+                            return *id;
+                        }
+                    }
+                }
+            }
+        }
+        let id = self.points.len();
+        self.points.push(p);
+        self.grid.entry(cell).or_default().push(id);
+        id
+    }
+}
+
+/// Links the directed edges into closed contours. Where more than one edge
+/// leaves a vertex the edge with the smallest clockwise turn from the
+/// reversed incoming edge is taken, keeping areas that touch at a vertex as
+/// separate contours.
+fn link_contours(points: &[Vec2], edges: &[(usize, usize)]) -> Vec<Polygon> {
+    let mut outgoing = HashMap::<usize, Vec<usize>>::new();
+    for (idx, e) in edges.iter().enumerate() {
+        outgoing.entry(e.0).or_default().push(idx);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut contours = Vec::<Polygon>::new();
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        let mut contour = Vec::<Vec2>::new();
+        let mut current = start;
+        loop {
+            used[current] = true;
+            let (from, to) = edges[current];
+            contour.push(points[from]);
+            // contours touching at a vertex are kept apart by closing the
+            // contour as soon as it returns to its first vertex
+            if to == edges[start].0 {
+                break;
+            }
+            let back = points[from].sub(&points[to]);
+            let back_angle = back.y.atan2(back.x);
+            let next = outgoing.get(&to).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .filter(|c| !used[**c])
+                    .min_by(|a, b| {
+                        let turn = |idx: &usize| {
+                            let d = points[edges[*idx].1].sub(&points[to]);
+                            (back_angle - d.y.atan2(d.x)).rem_euclid(std::f64::consts::TAU)
+                        };
+                        turn(a).total_cmp(&turn(b))
+                    })
+                    .copied()
+            });
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        let contour = remove_collinear(contour);
+        if contour.len() >= 3 {
+            contours.push(contour.into_iter().map(Point::from).collect());
+        }
+    }
+    contours
+}
+
+/// Removes vertices where the contour continues in a straight line, which
+/// are left behind by the splitting of edges.
+fn remove_collinear(contour: Vec<Vec2>) -> Vec<Vec2> {
+    let n = contour.len();
+    if n < 3 {
+        return contour;
+    }
+    (0..n)
+        .filter(|i| {
+            let prev = contour[(i + n - 1) % n];
+            let next = contour[(i + 1) % n];
+            let a = contour[*i].sub(&prev);
+            let b = next.sub(&contour[*i]);
+            a.cross(&b).abs() > f64::EPSILON * a.length() * b.length() * 16.0 || a.dot(&b) < 0.0
+        })
+        .map(|i| contour[i])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::polygon::Orientation;
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn square(x: f32, y: f32, size: f32) -> Polygon {
+        Polygon::new(vec![
+            Point { x, y },
+            Point { x: x + size, y },
+            Point {
+                x: x + size,
+                y: y + size,
+            },
+            Point { x, y: y + size },
+        ])
+    }
+
+    fn total_area(polygons: &[Polygon]) -> f32 {
+        polygons.iter().map(|p| p.signed_area()).sum()
+    }
+
+    #[test]
+    fn test_overlapping_squares() {
+        let a = square(0.0, 0.0, 10.0);
+        let b = square(5.0, 5.0, 10.0);
+
+        let union = a.union(&b);
+        assert_eq!(1, union.len());
+        assert_eq!(8, union[0].len());
+        assert!((total_area(&union) - 175.0).abs() < TEST_EPSILON);
+
+        let intersection = a.intersection(&b);
+        assert_eq!(1, intersection.len());
+        assert!((total_area(&intersection) - 25.0).abs() < TEST_EPSILON);
+
+        let difference = a.difference(&b);
+        assert_eq!(1, difference.len());
+        assert!((total_area(&difference) - 75.0).abs() < TEST_EPSILON);
+
+        let xor = a.xor(&b);
+        assert_eq!(2, xor.len());
+        assert!((total_area(&xor) - 150.0).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_hole() {
+        // subtracting a smaller square from inside another leaves a hole
+        let outer = square(0.0, 0.0, 10.0);
+        let inner = square(3.0, 3.0, 4.0);
+        let result = outer.difference(&inner);
+        assert_eq!(2, result.len());
+        assert!((total_area(&result) - 84.0).abs() < TEST_EPSILON);
+        let holes = result
+            .iter()
+            .filter(|p| p.orientation() == Orientation::Clockwise)
+            .count();
+        assert_eq!(1, holes);
+
+        // a strip across the polygon with a hole splits the hole in two
+        let clip = [Polygon::new(vec![
+            Point { x: 4.0, y: -2.0 },
+            Point { x: 6.0, y: -2.0 },
+            Point { x: 6.0, y: 12.0 },
+            Point { x: 4.0, y: 12.0 },
+        ])];
+        let result = boolean(&result, &clip, BooleanOp::Union, FillRule::NonZero);
+        assert!((total_area(&result) - 100.0).abs() < TEST_EPSILON);
+        let holes = result
+            .iter()
+            .filter(|p| p.orientation() == Orientation::Clockwise)
+            .count();
+        assert_eq!(2, holes);
+    }
+
+    #[test]
+    fn test_self_overlap() {
+        // the union of a single operand with overlapping contours merges them
+        let subject = [square(0.0, 0.0, 10.0), square(5.0, 0.0, 10.0)];
+        let nonzero = boolean(&subject, &[], BooleanOp::Union, FillRule::NonZero);
+        assert_eq!(1, nonzero.len());
+        assert!((total_area(&nonzero) - 150.0).abs() < TEST_EPSILON);
+        let evenodd = boolean(&subject, &[], BooleanOp::Union, FillRule::EvenOdd);
+        assert_eq!(2, evenodd.len());
+        assert!((total_area(&evenodd) - 100.0).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_shared_edges() {
+        // squares sharing an edge merge and squares touching at a corner don't
+        let union = square(0.0, 0.0, 10.0).union(&square(10.0, 0.0, 10.0));
+        assert_eq!(1, union.len());
+        assert_eq!(4, union[0].len());
+        let union = square(0.0, 0.0, 10.0).union(&square(10.0, 10.0, 10.0));
+        assert_eq!(2, union.len());
+        assert!((total_area(&union) - 200.0).abs() < TEST_EPSILON);
+        assert!(square(0.0, 0.0, 10.0)
+            .intersection(&square(20.0, 0.0, 10.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_many_edges() {
+        // two finely divided circles of radius 10 overlapping by half a radius
+        let circle = |cx: f32| -> Polygon {
+            (0..512)
+                .map(|i| {
+                    let a = i as f32 / 512.0 * std::f32::consts::TAU;
+                    Point {
+                        x: cx + 10.0 * a.cos(),
+                        y: 10.0 * a.sin(),
+                    }
+                })
+                .collect()
+        };
+        let (a, b) = (circle(0.0), circle(15.0));
+        let intersection = total_area(&a.intersection(&b));
+        let union = total_area(&a.union(&b));
+        assert!((union + intersection - a.area() - b.area()).abs() < 0.1);
+        // the lens of two circles of radius r with centers 1.5 r apart
+        let half_angle = (0.75f32).acos();
+        let lens = 2.0 * 100.0 * (half_angle - half_angle.sin() * half_angle.cos());
+        assert!((intersection - lens).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_clip_polyline() {
+        let line = Polyline::new(vec![Point { x: -5.0, y: 5.0 }, Point { x: 25.0, y: 5.0 }]);
+        let clip = [square(0.0, 0.0, 10.0)];
+        let inside = clip_polyline(&line, &clip, BooleanOp::Intersection, FillRule::NonZero);
+        assert_eq!(1, inside.len());
+        assert!((inside[0].length() - 10.0).abs() < TEST_EPSILON);
+        let outside = clip_polyline(&line, &clip, BooleanOp::Difference, FillRule::NonZero);
+        assert_eq!(2, outside.len());
+        assert!((outside[0].length() - 5.0).abs() < TEST_EPSILON);
+        assert!((outside[1].length() - 15.0).abs() < TEST_EPSILON);
+    }
+}
@@ -0,0 +1,325 @@
+//! Clothoids, or Euler spirals, are curves whose curvature changes linearly
+//! with arc length. They are used in road and railway alignments as
+//! transition curves between straight lines and circular arcs so that the
+//! steering of a vehicle changes at a constant rate.
+//!
+//! The curvature follows the same sign convention as `Bezier::curvature` and
+//! is positive where the curve turns in the direction of its normal, to the
+//! left in screen coordinates. The heading is the angle of the tangent from
+//! the x axis.
+
+use std::f64::consts::PI;
+
+use crate::bezier::Bezier;
+use crate::Point;
+
+/// Number of Simpson's rule intervals per radian of heading change used to
+/// integrate the position along a clothoid.
+const INTERVALS_PER_RADIAN: f64 = 32.0;
+
+/// Maximum depth of subdivision when approximating a clothoid with Bézier
+/// segments
+const MAX_SUBDIVISION: u32 = 10;
+
+/// Maximum number of Newton iterations when fitting a clothoid
+const MAX_ITERATIONS: usize = 20;
+
+/// A clothoid starting at a point with a heading and a curvature that
+/// changes at a constant rate, the sharpness, over its length.
+#[derive(Debug, Clone, Copy)]
+pub struct Clothoid {
+    start: Point,
+    heading: f32,
+    curvature: f32,
+    sharpness: f32,
+    length: f32,
+}
+
+impl Clothoid {
+    /// Creates a clothoid from its start point and heading and the curvature
+    /// at either end. A straight line or circular arc is a clothoid with the
+    /// same curvature at either end.
+    pub fn new(
+        start: Point,
+        heading: f32,
+        start_curvature: f32,
+        end_curvature: f32,
+        length: f32,
+    ) -> Self {
+        assert!(length > 0.0);
+        Clothoid {
+            start,
+            heading,
+            curvature: start_curvature,
+            sharpness: (end_curvature - start_curvature) / length,
+            length,
+        }
+    }
+
+    /// Creates the clothoid that leaves the start point with the start
+    /// heading and arrives at the end point with the end heading. The
+    /// curvature at either end is whatever the fit requires. None is returned
+    /// when the points coincide or no clothoid joins them.
+    ///
+    /// The fit solves the G1 Hermite interpolation problem with Newton's
+    /// method as described by Bertolazzi and Frego in "Fast and accurate
+    /// clothoid fitting".
+    pub fn fit(start: Point, start_heading: f32, end: Point, end_heading: f32) -> Option<Self> {
+        let dx = (end.x - start.x) as f64;
+        let dy = (end.y - start.y) as f64;
+        let r = dx.hypot(dy);
+        if r < f32::EPSILON as f64 {
+            return None;
+        }
+        // the fit is done with the heading increasing with curvature, which
+        // is the opposite of the curvature sign used by this module
+        let phi = dy.atan2(dx);
+        let phi0 = normalize_angle(start_heading as f64 - phi);
+        let phi1 = normalize_angle(end_heading as f64 - phi);
+        let delta = phi1 - phi0;
+
+        let mut a = 3.0 * (phi0 + phi1);
+        for _ in 0..MAX_ITERATIONS {
+            let (_, g) = integrate(2.0 * a, delta - a, phi0);
+            let dg = integrate_moment(2.0 * a, delta - a, phi0);
+            if dg.abs() < f64::EPSILON {
+                return None;
+            }
+            let step = g / dg;
+            a -= step;
+            if step.abs() < 1.0e-10 {
+                break;
+            }
+        }
+        let (x, y) = integrate(2.0 * a, delta - a, phi0);
+        if y.abs() > 1.0e-6 || x <= 0.0 {
+            return None;
+        }
+        let length = r / x;
+        let curvature = (delta - a) / length;
+        let sharpness = 2.0 * a / (length * length);
+        Some(Clothoid {
+            start,
+            heading: start_heading,
+            curvature: -curvature as f32,
+            sharpness: -sharpness as f32,
+            length: length as f32,
+        })
+    }
+
+    pub fn start(&self) -> Point {
+        self.start
+    }
+
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Gets the rate of change of curvature per unit of arc length
+    pub fn sharpness(&self) -> f32 {
+        self.sharpness
+    }
+
+    /// Gets the curvature at the distance s along the clothoid
+    pub fn curvature_at(&self, s: f32) -> f32 {
+        self.curvature + self.sharpness * s
+    }
+
+    /// Gets the heading at the distance s along the clothoid
+    pub fn heading_at(&self, s: f32) -> f32 {
+        self.heading - (self.curvature * s + self.sharpness * s * s / 2.0)
+    }
+
+    /// Gets the unit tangent at the distance s along the clothoid
+    pub fn tangent_at(&self, s: f32) -> Point {
+        let heading = self.heading_at(s);
+        Point {
+            x: heading.cos(),
+            y: heading.sin(),
+        }
+    }
+
+    /// Gets the point at the distance s along the clothoid
+    pub fn point_at(&self, s: f32) -> Point {
+        let s = s as f64;
+        let (x, y) = integrate(
+            -self.sharpness as f64 * s * s,
+            -self.curvature as f64 * s,
+            self.heading as f64,
+        );
+        Point {
+            x: self.start.x + (x * s) as f32,
+            y: self.start.y + (y * s) as f32,
+        }
+    }
+
+    pub fn end(&self) -> Point {
+        self.point_at(self.length)
+    }
+
+    /// Approximates the clothoid with a compound Bézier curve. The clothoid
+    /// is bisected until each segment is within the tolerance of the
+    /// clothoid, so the segments are G1 continuous at the joints.
+    pub fn to_beziers(&self, tolerance: f32, resolution: f32) -> Vec<Bezier> {
+        let mut segments = Vec::<Bezier>::new();
+        self.approximate(0.0, self.length, tolerance, resolution, 0, &mut segments);
+        segments
+    }
+
+    fn approximate(
+        &self,
+        s0: f32,
+        s1: f32,
+        tolerance: f32,
+        resolution: f32,
+        depth: u32,
+        segments: &mut Vec<Bezier>,
+    ) {
+        let p0 = self.point_at(s0);
+        let p3 = self.point_at(s1);
+        // a cubic with handles of a third of the arc length has the same
+        // speed as the clothoid at either end
+        let handle = (s1 - s0) / 3.0;
+        let b = Bezier::new_with_ctrl_point(
+            [
+                p0,
+                p0 + self.tangent_at(s0) * handle,
+                p3 - self.tangent_at(s1) * handle,
+                p3,
+            ],
+            resolution,
+        );
+        let error = (1..8)
+            .map(|i| {
+                let t = i as f32 / 8.0;
+                b.point_at(t).distance(&self.point_at(s0 + (s1 - s0) * t))
+            })
+            .fold(0.0, f32::max);
+        if error <= tolerance || depth >= MAX_SUBDIVISION {
+            segments.push(b);
+        } else {
+            let mid = (s0 + s1) / 2.0;
+            self.approximate(s0, mid, tolerance, resolution, depth + 1, segments);
+            self.approximate(mid, s1, tolerance, resolution, depth + 1, segments);
+        }
+    }
+}
+
+/// Evaluates the normalized Fresnel integrals C(t) and S(t), the integrals
+/// of cos(πu²/2) and sin(πu²/2) from 0 to t. They are the coordinates of the
+/// standard clothoid with sharpness π.
+pub fn fresnel(t: f64) -> (f64, f64) {
+    let (c, s) = integrate(PI * t * t, 0.0, 0.0);
+    (c * t, s * t)
+}
+
+/// Integrates cos and sin of a/2 u² + b u + c for u from 0 to 1
+fn integrate(a: f64, b: f64, c: f64) -> (f64, f64) {
+    simpson(a, b, |u| {
+        let theta = a / 2.0 * u * u + b * u + c;
+        (theta.cos(), theta.sin())
+    })
+}
+
+/// Integrates cos(a/2 u² + b u + c)(u² - u) for u from 0 to 1, which is the
+/// derivative of the sin integral of a clothoid fit with respect to the fit
+/// parameter.
+fn integrate_moment(a: f64, b: f64, c: f64) -> f64 {
+    simpson(a, b, |u| {
+        ((a / 2.0 * u * u + b * u + c).cos() * (u * u - u), 0.0)
+    })
+    .0
+}
+
+/// Integrates the function for u from 0 to 1 with the composite Simpson's
+/// rule using enough intervals for the heading change of a/2 u² + b u.
+fn simpson<F: Fn(f64) -> (f64, f64)>(a: f64, b: f64, f: F) -> (f64, f64) {
+    let turning = a.abs() / 2.0 + b.abs();
+    let n = 2 * ((turning * INTERVALS_PER_RADIAN / 2.0).ceil() as usize).max(4);
+    let h = 1.0 / n as f64;
+    let (mut x, mut y) = (0.0, 0.0);
+    for i in 0..=n {
+        let w = if i == 0 || i == n {
+            1.0
+        } else if i % 2 == 1 {
+            4.0
+        } else {
+            2.0
+        };
+        let (fx, fy) = f(i as f64 * h);
+        x += w * fx;
+        y += w * fy;
+    }
+    (x * h / 3.0, y * h / 3.0)
+}
+
+fn normalize_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const TEST_EPSILON: f32 = 0.001;
+
+    #[test]
+    fn test_fresnel() {
+        let (c, s) = fresnel(1.0);
+        assert!((c - 0.779893).abs() < 1.0e-5);
+        assert!((s - 0.438259).abs() < 1.0e-5);
+        let (c, s) = fresnel(-2.0);
+        assert!((c + 0.488253).abs() < 1.0e-5);
+        assert!((s + 0.343416).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_circular_arc() {
+        // a quarter circle of radius 100 turning left on screen
+        let arc = Clothoid::new(
+            Point { x: 0.0, y: 0.0 },
+            0.0,
+            0.01,
+            0.01,
+            50.0 * std::f32::consts::PI,
+        );
+        assert!(
+            arc.end().distance(&Point {
+                x: 100.0,
+                y: -100.0
+            }) < 0.01
+        );
+        assert!((arc.heading_at(arc.length()) + std::f32::consts::FRAC_PI_2).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_to_beziers() {
+        let spiral = Clothoid::new(Point { x: 10.0, y: 20.0 }, 0.5, 0.0, 0.02, 100.0);
+        let segments = spiral.to_beziers(0.01, 0.1);
+        assert!(!segments.is_empty());
+        assert!(segments[0].ctrl_point(0).distance(&spiral.start()) < TEST_EPSILON);
+        let last = segments.last().unwrap();
+        assert!(last.ctrl_point(3).distance(&spiral.end()) < 0.01);
+        // the curvature of the approximation changes linearly
+        let k = segments[0].curvature(0.0);
+        assert!(k.abs() < 0.001);
+        let k = last.curvature(1.0);
+        assert!((k - 0.02).abs() < 0.002);
+    }
+
+    #[test]
+    fn test_fit() {
+        let spiral = Clothoid::new(Point { x: 10.0, y: 20.0 }, 0.5, 0.005, 0.02, 100.0);
+        let fit = Clothoid::fit(
+            spiral.start(),
+            spiral.heading_at(0.0),
+            spiral.end(),
+            spiral.heading_at(spiral.length()),
+        )
+        .unwrap();
+        assert!((fit.length() - spiral.length()).abs() < 0.01);
+        assert!((fit.curvature_at(0.0) - 0.005).abs() < 0.0001);
+        assert!((fit.sharpness() - spiral.sharpness()).abs() < 0.00001);
+        assert!(fit.end().distance(&spiral.end()) < 0.01);
+        assert!(Clothoid::fit(spiral.start(), 0.0, spiral.start(), 1.0).is_none());
+    }
+}
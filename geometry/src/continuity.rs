@@ -0,0 +1,243 @@
+//! Continuity of the joints between the segments of a compound Bézier curve.
+//!
+//! A joint is G0 continuous when the segments meet, G1 continuous when they
+//! also share a tangent direction, C1 continuous when the handles on either
+//! side of the joint are reflections of each other and G2 continuous when
+//! the curvature is the same on either side. C1 does not imply G2 and G2
+//! does not imply C1.
+
+use crate::bezier::Bezier;
+use crate::Point;
+
+/// Default tolerance used by `analyze`
+pub const DEFAULT_TOLERANCE: f32 = 0.001;
+
+/// The continuity of a joint between two Bézier segments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Continuity {
+    pub g0: bool,
+    pub g1: bool,
+    pub c1: bool,
+    pub g2: bool,
+}
+
+/// The constraint enforced between the handles on either side of a joint
+/// when one of them is moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeType {
+    /// The handles move independently so the joint may be a sharp corner
+    Corner,
+    /// The handles are kept collinear but may have different lengths
+    Smooth,
+    /// The handles are kept collinear with the same length
+    #[default]
+    Symmetric,
+    /// The handles are kept collinear and the length of the opposite handle
+    /// is chosen so the curvature is the same on either side of the joint
+    Curvature,
+}
+
+impl NodeType {
+    /// Gets the strongest node type satisfied by the joint
+    pub fn from_continuity(continuity: &Continuity) -> NodeType {
+        if continuity.g1 && continuity.g2 {
+            NodeType::Curvature
+        } else if continuity.c1 {
+            NodeType::Symmetric
+        } else if continuity.g1 {
+            NodeType::Smooth
+        } else {
+            NodeType::Corner
+        }
+    }
+}
+
+/// The side of a joint holding the handle that was moved. `Incoming` is
+/// control point 2 of the segment ending at the joint and `Outgoing` is
+/// control point 1 of the segment starting at the joint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JointSide {
+    Incoming,
+    Outgoing,
+}
+
+/// Gets the continuity of the joint between the end of the incoming segment
+/// and the start of the outgoing segment. Positions and handles are compared
+/// with the tolerance as an absolute distance, tangent directions with the
+/// tolerance as the sine of the angle between them and curvatures with the
+/// tolerance relative to the larger curvature.
+pub fn joint_continuity(incoming: &Bezier, outgoing: &Bezier, tolerance: f32) -> Continuity {
+    let g0 = incoming.ctrl_point(3).distance(&outgoing.ctrl_point(0)) <= tolerance;
+    let t0 = incoming.tangent(1.0);
+    let t1 = outgoing.tangent(0.0);
+    let g1 = g0 && t0.cross(&t1).abs() <= tolerance && t0.dot(&t1) > 0.0;
+    let h0 = incoming.ctrl_point(3) - incoming.ctrl_point(2);
+    let h1 = outgoing.ctrl_point(1) - outgoing.ctrl_point(0);
+    let c1 = g0 && h0.distance(&h1) <= tolerance;
+    let k0 = incoming.curvature(1.0);
+    let k1 = outgoing.curvature(0.0);
+    let g2 = g1 && (k0 - k1).abs() <= tolerance * k0.abs().max(k1.abs()) + f32::EPSILON;
+    Continuity { g0, g1, c1, g2 }
+}
+
+/// Gets the continuity of each joint in a compound curve using the default
+/// tolerance. The result has one entry fewer than the number of segments.
+pub fn analyze(segments: &[Bezier]) -> Vec<Continuity> {
+    segments
+        .windows(2)
+        .map(|s| joint_continuity(&s[0], &s[1], DEFAULT_TOLERANCE))
+        .collect()
+}
+
+/// Adjusts the handle opposite to the moved handle of a joint so that the
+/// joint satisfies the node type. The moved handle is never changed.
+pub fn enforce(node: NodeType, incoming: &mut Bezier, outgoing: &mut Bezier, moved: JointSide) {
+    let joint = incoming.ctrl_point(3);
+    let (moved_handle, other_handle) = match moved {
+        JointSide::Incoming => (incoming.ctrl_point(2), outgoing.ctrl_point(1)),
+        JointSide::Outgoing => (outgoing.ctrl_point(1), incoming.ctrl_point(2)),
+    };
+    let direction = (joint - moved_handle).normalize();
+    let length = match node {
+        NodeType::Corner => return,
+        NodeType::Symmetric => moved_handle.distance(&joint),
+        NodeType::Smooth => other_handle.distance(&joint),
+        NodeType::Curvature => {
+            let (curvature, far, chord) = match moved {
+                JointSide::Incoming => (
+                    incoming.curvature(1.0),
+                    outgoing.ctrl_point(2),
+                    outgoing.ctrl_point(3).distance(&joint),
+                ),
+                JointSide::Outgoing => (
+                    outgoing.curvature(0.0),
+                    incoming.ctrl_point(1),
+                    incoming.ctrl_point(0).distance(&joint),
+                ),
+            };
+            // the direction points backwards along an incoming segment which
+            // flips the sign of the height of its inner control point
+            let sign = match moved {
+                JointSide::Incoming => 1.0,
+                JointSide::Outgoing => -1.0,
+            };
+            matching_handle_length(curvature, direction * sign, joint, far, chord)
+                .unwrap_or_else(|| other_handle.distance(&joint))
+        }
+    };
+    if direction.length() < f32::EPSILON {
+        return;
+    }
+    let p = joint + direction * length;
+    match moved {
+        JointSide::Incoming => outgoing.set_ctrl_point(p, 1),
+        JointSide::Outgoing => incoming.set_ctrl_point(p, 2),
+    }
+}
+
+/// Gets the handle length that gives a cubic Bézier segment the curvature at
+/// the joint. The curvature at the end of a segment with handle length a is
+/// 2h / 3a² where h is the distance of the next inner control point from the
+/// tangent line, measured in the direction of the normal. None is returned
+/// when the curvature cannot be matched with a handle no longer than the
+/// chord of the segment. The direction is the direction of the curve at the
+/// joint rather than the direction of the handle.
+fn matching_handle_length(
+    curvature: f32,
+    direction: Point,
+    joint: Point,
+    far: Point,
+    chord: f32,
+) -> Option<f32> {
+    // the normal of the tangent direction, as returned by `Bezier::normal`
+    let normal = Point {
+        x: direction.y,
+        y: -direction.x,
+    };
+    let height = (far - joint).dot(&normal);
+    if curvature.abs() < f32::EPSILON || height * curvature <= 0.0 {
+        return None;
+    }
+    let length = (2.0 * height / (3.0 * curvature)).sqrt();
+    if length > chord {
+        return None;
+    }
+    Some(length)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn segments() -> [Bezier; 2] {
+        [
+            Bezier::new_with_ctrl_point(
+                [
+                    Point { x: 0.0, y: 0.0 },
+                    Point { x: 30.0, y: 0.0 },
+                    Point { x: 70.0, y: 20.0 },
+                    Point { x: 100.0, y: 50.0 },
+                ],
+                0.1,
+            ),
+            Bezier::new_with_ctrl_point(
+                [
+                    Point { x: 100.0, y: 50.0 },
+                    Point { x: 130.0, y: 80.0 },
+                    Point { x: 160.0, y: 140.0 },
+                    Point { x: 200.0, y: 150.0 },
+                ],
+                0.1,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_analyze() {
+        let mut s = segments();
+        let c = analyze(&s);
+        assert_eq!(1, c.len());
+        assert!(c[0].g0 && c[0].g1 && c[0].c1);
+        assert_eq!(NodeType::Symmetric, NodeType::from_continuity(&c[0]));
+
+        // a longer handle on the outgoing side breaks C1 but not G1
+        s[1].set_ctrl_point(Point { x: 150.0, y: 100.0 }, 1);
+        let c = analyze(&s);
+        assert!(c[0].g1 && !c[0].c1);
+        assert_eq!(NodeType::Smooth, NodeType::from_continuity(&c[0]));
+
+        s[1].set_ctrl_point(Point { x: 150.0, y: 50.0 }, 1);
+        let c = analyze(&s);
+        assert!(c[0].g0 && !c[0].g1);
+        assert_eq!(NodeType::Corner, NodeType::from_continuity(&c[0]));
+    }
+
+    #[test]
+    fn test_enforce() {
+        let [mut a, mut b] = segments();
+        a.set_ctrl_point(Point { x: 80.0, y: 50.0 }, 2);
+        enforce(NodeType::Corner, &mut a, &mut b, JointSide::Incoming);
+        assert!(b.ctrl_point(1).distance(&Point { x: 130.0, y: 80.0 }) < TEST_EPSILON);
+
+        enforce(NodeType::Symmetric, &mut a, &mut b, JointSide::Incoming);
+        assert!(b.ctrl_point(1).distance(&Point { x: 120.0, y: 50.0 }) < TEST_EPSILON);
+
+        b.set_ctrl_point(Point { x: 100.0, y: 90.0 }, 1);
+        enforce(NodeType::Smooth, &mut a, &mut b, JointSide::Outgoing);
+        assert!(a.ctrl_point(2).distance(&Point { x: 100.0, y: 30.0 }) < TEST_EPSILON);
+        let c = joint_continuity(&a, &b, DEFAULT_TOLERANCE);
+        assert!(c.g1 && !c.c1);
+    }
+
+    #[test]
+    fn test_enforce_curvature() {
+        let [mut a, mut b] = segments();
+        for moved in [JointSide::Incoming, JointSide::Outgoing] {
+            enforce(NodeType::Curvature, &mut a, &mut b, moved);
+            let c = joint_continuity(&a, &b, DEFAULT_TOLERANCE);
+            assert!(c.g1 && c.g2);
+            assert_eq!(NodeType::Curvature, NodeType::from_continuity(&c));
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::bezier::Bezier;
+use crate::polyline::Polyline;
+use crate::Point;
+
+/// Number of samples per Bézier segment used to map arc length to the curve
+/// parameter.
+const ARC_LENGTH_SAMPLES: usize = 64;
+
+/// A dash pattern of alternating dash and gap lengths measured along the arc
+/// length of a path. The offset is the distance into the pattern at which
+/// the path starts, in the same way as a Direct2D or Cairo dash offset
+/// except that the lengths are absolute rather than multiples of the stroke
+/// width.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashPattern {
+    dashes: Vec<f32>,
+    offset: f32,
+}
+
+impl DashPattern {
+    /// Creates a dash pattern. A pattern with an odd number of lengths is
+    /// repeated so that dashes and gaps alternate, so [4.0] is a 4 unit dash
+    /// followed by a 4 unit gap.
+    pub fn new(dashes: &[f32], offset: f32) -> Self {
+        assert!(!dashes.is_empty());
+        assert!(dashes.iter().all(|d| *d >= 0.0));
+        assert!(dashes.iter().sum::<f32>() > 0.0);
+        let mut dashes = dashes.to_vec();
+        if dashes.len() % 2 == 1 {
+            dashes.extend_from_within(..);
+        }
+        DashPattern { dashes, offset }
+    }
+
+    pub fn dashes(&self) -> &[f32] {
+        &self.dashes
+    }
+
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// Gets the length of one repetition of the pattern
+    pub fn period(&self) -> f32 {
+        self.dashes.iter().sum()
+    }
+
+    /// Gets the start and end distance of each dash along a path of the given
+    /// length. Dashes are clipped at the start and end of the path.
+    pub fn intervals(&self, length: f32) -> Vec<(f32, f32)> {
+        let period = self.period();
+        let mut intervals = Vec::<(f32, f32)>::new();
+        // find the dash or gap the path starts in
+        let mut phase = self.offset.rem_euclid(period);
+        let mut idx = 0;
+        while phase >= self.dashes[idx] {
+            phase -= self.dashes[idx];
+            idx = (idx + 1) % self.dashes.len();
+        }
+        let mut distance = -phase;
+        while distance < length {
+            let end = distance + self.dashes[idx];
+            if idx % 2 == 0 && end > 0.0 && end > distance {
+                intervals.push((distance.max(0.0), end.min(length)));
+            }
+            distance = end;
+            idx = (idx + 1) % self.dashes.len();
+        }
+        intervals
+    }
+
+    /// Splits the polyline into a polyline for each dash
+    pub fn dash_polyline(&self, line: &Polyline) -> Vec<Polyline> {
+        self.intervals(line.length())
+            .into_iter()
+            .map(|(start, end)| line.slice(start, end))
+            .collect()
+    }
+
+    /// Splits a compound Bézier curve into dashes. Each dash is a compound
+    /// curve since a dash may continue across the joint between segments.
+    pub fn dash_curve(&self, segments: &[Bezier]) -> Vec<Vec<Bezier>> {
+        let tables: Vec<ArcLengthTable> = segments.iter().map(ArcLengthTable::new).collect();
+        let mut starts = Vec::<f32>::with_capacity(segments.len());
+        let mut total = 0.0;
+        for table in tables.iter() {
+            starts.push(total);
+            total += table.length();
+        }
+
+        let mut dashes = Vec::<Vec<Bezier>>::new();
+        for (start, end) in self.intervals(total) {
+            let mut dash = Vec::<Bezier>::new();
+            for (idx, segment) in segments.iter().enumerate() {
+                let s0 = starts[idx];
+                let s1 = s0 + tables[idx].length();
+                if end <= s0 || start >= s1 {
+                    continue;
+                }
+                let t0 = tables[idx].param(start - s0);
+                let t1 = tables[idx].param(end - s0);
+                dash.push(segment.segment(t0, t1));
+            }
+            if !dash.is_empty() {
+                dashes.push(dash);
+            }
+        }
+        dashes
+    }
+}
+
+/// Maps arc length along a Bézier segment to the curve parameter by linear
+/// interpolation of a sampled table.
+struct ArcLengthTable {
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    fn new(b: &Bezier) -> Self {
+        let mut lengths = Vec::<f32>::with_capacity(ARC_LENGTH_SAMPLES + 1);
+        let mut total = 0.0;
+        let mut prev: Point = b.point_at(0.0);
+        lengths.push(0.0);
+        for i in 1..=ARC_LENGTH_SAMPLES {
+            let p = b.point_at(i as f32 / ARC_LENGTH_SAMPLES as f32);
+            total += prev.distance(&p);
+            lengths.push(total);
+            prev = p;
+        }
+        ArcLengthTable { lengths }
+    }
+
+    fn length(&self) -> f32 {
+        self.lengths[ARC_LENGTH_SAMPLES]
+    }
+
+    fn param(&self, distance: f32) -> f32 {
+        if distance <= 0.0 {
+            return 0.0;
+        }
+        if distance >= self.length() {
+            return 1.0;
+        }
+        let idx = self.lengths.partition_point(|l| *l < distance).max(1);
+        let (l0, l1) = (self.lengths[idx - 1], self.lengths[idx]);
+        let fraction = if l1 > l0 {
+            (distance - l0) / (l1 - l0)
+        } else {
+            0.0
+        };
+        (idx as f32 - 1.0 + fraction) / ARC_LENGTH_SAMPLES as f32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const TEST_EPSILON: f32 = 0.001;
+
+    #[test]
+    fn test_intervals() {
+        let pattern = DashPattern::new(&[4.0, 2.0], 0.0);
+        assert_eq!(
+            vec![(0.0, 4.0), (6.0, 10.0), (12.0, 13.0)],
+            pattern.intervals(13.0)
+        );
+        let pattern = DashPattern::new(&[4.0, 2.0], 5.0);
+        assert_eq!(vec![(1.0, 5.0), (7.0, 10.0)], pattern.intervals(10.0));
+        let pattern = DashPattern::new(&[3.0], 1.0);
+        assert_eq!(vec![(0.0, 2.0), (5.0, 8.0)], pattern.intervals(10.0));
+    }
+
+    #[test]
+    fn test_dash_polyline() {
+        let line = Polyline::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 8.0, y: 0.0 },
+            Point { x: 8.0, y: 7.0 },
+        ]);
+        let dashes = DashPattern::new(&[4.0, 2.0], 0.0).dash_polyline(&line);
+        assert_eq!(3, dashes.len());
+        // the second dash turns the corner
+        assert_eq!(3, dashes[1].len());
+        assert!((dashes[1].length() - 4.0).abs() < TEST_EPSILON);
+        assert!((dashes[2].length() - 3.0).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_dash_curve() {
+        let straight = |x0: f32, x1: f32| {
+            Bezier::new_with_ctrl_point(
+                [
+                    Point { x: x0, y: 0.0 },
+                    Point {
+                        x: x0 + (x1 - x0) / 3.0,
+                        y: 0.0,
+                    },
+                    Point {
+                        x: x1 - (x1 - x0) / 3.0,
+                        y: 0.0,
+                    },
+                    Point { x: x1, y: 0.0 },
+                ],
+                0.025,
+            )
+        };
+        let segments = [straight(0.0, 10.0), straight(10.0, 20.0)];
+        let dashes = DashPattern::new(&[6.0, 2.0], 0.0).dash_curve(&segments);
+        assert_eq!(3, dashes.len());
+        assert_eq!(1, dashes[0].len());
+        // the second dash from 8.0 to 14.0 spans the joint
+        assert_eq!(2, dashes[1].len());
+        assert!(
+            dashes[1][0]
+                .point_at(0.0)
+                .distance(&Point { x: 8.0, y: 0.0 })
+                < 0.01
+        );
+        assert!(
+            dashes[1][1]
+                .point_at(1.0)
+                .distance(&Point { x: 14.0, y: 0.0 })
+                < 0.01
+        );
+        assert!(
+            dashes[2][0]
+                .point_at(1.0)
+                .distance(&Point { x: 20.0, y: 0.0 })
+                < 0.01
+        );
+    }
+}
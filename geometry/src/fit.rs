@@ -0,0 +1,329 @@
+//! Least-squares fitting of cubic Bézier curves to sampled points using
+//! Philip J. Schneider's algorithm from "An Algorithm for Automatically
+//! Fitting Digitized Curves", Graphics Gems, 1990.
+
+use crate::bezier::Bezier;
+use crate::Point;
+
+/// The default turn angle, in radians, at which a sample is treated as a
+/// corner and the curve is split without tangent continuity.
+pub const DEFAULT_CORNER_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+
+const MAX_REPARAMETERIZE: usize = 4;
+const MIN_SAMPLE_DISTANCE: f32 = 0.0001;
+
+/// Fits a compound Bézier curve to the sampled points so that no sample is
+/// further than the tolerance from the curve. Corners are detected using the
+/// `DEFAULT_CORNER_ANGLE`.
+///
+/// The returned segments share their end and start control points in the
+/// same way as the centerline of a `BezierFeature`. An empty vector is
+/// returned when fewer than 2 distinct points are provided.
+pub fn fit_curve(points: &[Point], tolerance: f32, resolution: f32) -> Vec<Bezier> {
+    fit_curve_with_corners(points, tolerance, DEFAULT_CORNER_ANGLE, resolution)
+}
+
+/// Fits a compound Bézier curve to the sampled points splitting the samples
+/// at any point where the direction of the samples turns by more than the
+/// corner angle in radians.
+pub fn fit_curve_with_corners(
+    points: &[Point],
+    tolerance: f32,
+    corner_angle: f32,
+    resolution: f32,
+) -> Vec<Bezier> {
+    let mut samples = Vec::<Point>::with_capacity(points.len());
+    for p in points {
+        match samples.last() {
+            Some(last) if last.distance(p) < MIN_SAMPLE_DISTANCE => (),
+            _ => samples.push(*p),
+        }
+    }
+    let mut segments = Vec::<Bezier>::new();
+    if samples.len() < 2 {
+        return segments;
+    }
+
+    let corners = find_corners(&samples, tolerance, corner_angle);
+    for run in corners.windows(2) {
+        let run = &samples[run[0]..=run[1]];
+        let t0 = left_tangent(run, tolerance);
+        let t1 = right_tangent(run, tolerance);
+        fit_cubic(run, t0, t1, tolerance, resolution, &mut segments);
+    }
+    segments
+}
+
+/// Finds the indices of the first and last samples as well as any sample
+/// where the curve turns by more than the corner angle. The direction on
+/// either side of a sample is measured to the first sample at least twice
+/// the tolerance away so that noise is not mistaken for a corner.
+fn find_corners(samples: &[Point], tolerance: f32, corner_angle: f32) -> Vec<usize> {
+    let last = samples.len() - 1;
+    let reach = 2.0 * tolerance;
+    let mut corners = vec![0];
+    for i in 1..last {
+        let p = samples[i];
+        let before = samples[..i]
+            .iter()
+            .rev()
+            .find(|s| s.distance(&p) >= reach)
+            .unwrap_or(&samples[0]);
+        let after = samples[i + 1..]
+            .iter()
+            .find(|s| s.distance(&p) >= reach)
+            .unwrap_or(&samples[last]);
+        let d0 = (p - *before).normalize();
+        let d1 = (*after - p).normalize();
+        let angle = d0.dot(&d1).clamp(-1.0, 1.0).acos();
+        if angle > corner_angle && i - corners.last().unwrap() > 1 {
+            corners.push(i);
+        }
+    }
+    corners.push(last);
+    corners
+}
+
+fn left_tangent(points: &[Point], tolerance: f32) -> Point {
+    let p = points[0];
+    let to = points
+        .iter()
+        .skip(1)
+        .find(|s| s.distance(&p) >= tolerance)
+        .unwrap_or(&points[points.len() - 1]);
+    (*to - p).normalize()
+}
+
+fn right_tangent(points: &[Point], tolerance: f32) -> Point {
+    let p = points[points.len() - 1];
+    let to = points
+        .iter()
+        .rev()
+        .skip(1)
+        .find(|s| s.distance(&p) >= tolerance)
+        .unwrap_or(&points[0]);
+    (*to - p).normalize()
+}
+
+/// Recursively fits the points with cubic segments, appending the segments
+/// to the result. t0 is the unit tangent leaving the first point and t1 the
+/// unit tangent leaving the last point back towards the previous points.
+fn fit_cubic(
+    points: &[Point],
+    t0: Point,
+    t1: Point,
+    tolerance: f32,
+    resolution: f32,
+    result: &mut Vec<Bezier>,
+) {
+    let first = points[0];
+    let last = points[points.len() - 1];
+    if points.len() == 2 {
+        let dist = first.distance(&last) / 3.0;
+        result.push(Bezier::new_with_ctrl_point(
+            [first, first + t0 * dist, last + t1 * dist, last],
+            resolution,
+        ));
+        return;
+    }
+
+    let mut u = chord_length_parameterize(points);
+    let mut bezier = generate_bezier(points, &u, t0, t1, resolution);
+    let (mut error, mut split) = max_error(points, &bezier, &u);
+    if error <= tolerance * tolerance {
+        result.push(bezier);
+        return;
+    }
+
+    // the fit is close so try to improve it by reparameterization
+    let iteration_error = tolerance * tolerance * 4.0;
+    if error < iteration_error {
+        for _ in 0..MAX_REPARAMETERIZE {
+            u = reparameterize(points, &u, &bezier);
+            bezier = generate_bezier(points, &u, t0, t1, resolution);
+            (error, split) = max_error(points, &bezier, &u);
+            if error <= tolerance * tolerance {
+                result.push(bezier);
+                return;
+            }
+        }
+    }
+
+    // split at the point of maximum error and fit each part
+    let split = split.clamp(1, points.len() - 2);
+    let center = (points[split - 1] - points[split + 1]).normalize();
+    fit_cubic(&points[..=split], t0, center, tolerance, resolution, result);
+    fit_cubic(
+        &points[split..],
+        center * -1.0,
+        t1,
+        tolerance,
+        resolution,
+        result,
+    );
+}
+
+/// Solves the least-squares problem for the lengths of the two handles with
+/// fixed end points and tangent directions.
+fn generate_bezier(points: &[Point], u: &[f32], t0: Point, t1: Point, resolution: f32) -> Bezier {
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    let mut c = [[0.0f32; 2]; 2];
+    let mut x = [0.0f32; 2];
+    for (p, u) in points.iter().zip(u) {
+        let mu = 1.0 - u;
+        let b0 = mu * mu * mu;
+        let b1 = 3.0 * u * mu * mu;
+        let b2 = 3.0 * u * u * mu;
+        let b3 = u * u * u;
+        let a0 = t0 * b1;
+        let a1 = t1 * b2;
+        c[0][0] += a0.dot(&a0);
+        c[0][1] += a0.dot(&a1);
+        c[1][1] += a1.dot(&a1);
+        let tmp = *p - (first * (b0 + b1) + last * (b2 + b3));
+        x[0] += a0.dot(&tmp);
+        x[1] += a1.dot(&tmp);
+    }
+    c[1][0] = c[0][1];
+
+    let det_c0_c1 = c[0][0] * c[1][1] - c[1][0] * c[0][1];
+    let det_c0_x = c[0][0] * x[1] - c[1][0] * x[0];
+    let det_x_c1 = x[0] * c[1][1] - x[1] * c[0][1];
+    let (alpha_l, alpha_r) = if det_c0_c1.abs() > f32::EPSILON {
+        (det_x_c1 / det_c0_c1, det_c0_x / det_c0_c1)
+    } else {
+        (0.0, 0.0)
+    };
+
+    // fall back to the Wu/Barsky heuristic when the solution is degenerate
+    let seg_length = first.distance(&last);
+    let epsilon = 1.0e-6 * seg_length;
+    let (alpha_l, alpha_r) = if alpha_l < epsilon || alpha_r < epsilon {
+        (seg_length / 3.0, seg_length / 3.0)
+    } else {
+        (alpha_l, alpha_r)
+    };
+
+    Bezier::new_with_ctrl_point(
+        [first, first + t0 * alpha_l, last + t1 * alpha_r, last],
+        resolution,
+    )
+}
+
+/// Improves the parameter of each point using a Newton-Raphson step to find
+/// the closest point on the curve.
+fn reparameterize(points: &[Point], u: &[f32], bezier: &Bezier) -> Vec<f32> {
+    points
+        .iter()
+        .zip(u)
+        .map(|(p, u)| {
+            let d = bezier.point_at(*u) - *p;
+            let d1 = bezier.derivative(*u);
+            let d2 = bezier.second_derivative(*u);
+            let numerator = d.dot(&d1);
+            let denominator = d1.dot(&d1) + d.dot(&d2);
+            if denominator.abs() < f32::EPSILON {
+                *u
+            } else {
+                (u - numerator / denominator).clamp(0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+fn chord_length_parameterize(points: &[Point]) -> Vec<f32> {
+    let mut u = Vec::<f32>::with_capacity(points.len());
+    u.push(0.0);
+    for i in 1..points.len() {
+        u.push(u[i - 1] + points[i].distance(&points[i - 1]));
+    }
+    let total = u[u.len() - 1];
+    for v in u.iter_mut() {
+        *v /= total;
+    }
+    u
+}
+
+/// Gets the maximum squared distance between a point and the curve along
+/// with the index of that point.
+fn max_error(points: &[Point], bezier: &Bezier, u: &[f32]) -> (f32, usize) {
+    let mut max = 0.0;
+    let mut split = points.len() / 2;
+    for (i, (p, u)) in points.iter().zip(u).enumerate().skip(1) {
+        let d = bezier.point_at(*u) - *p;
+        let dist = d.dot(&d);
+        if dist >= max {
+            max = dist;
+            split = i;
+        }
+    }
+    (max, split)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn distance_to_curve(p: &Point, segments: &[Bezier]) -> f32 {
+        segments
+            .iter()
+            .flat_map(|b| (0..=200).map(move |i| b.point_at(i as f32 / 200.0)))
+            .map(|c| c.distance(p))
+            .fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn test_fit_within_tolerance() {
+        // a noisy sine wave
+        let points: Vec<Point> = (0..=100)
+            .map(|i| {
+                let x = i as f32 * 4.0;
+                let noise = if i % 2 == 0 { 0.3 } else { -0.3 };
+                Point {
+                    x,
+                    y: 50.0 * (x / 60.0).sin() + noise,
+                }
+            })
+            .collect();
+        let tolerance = 2.0;
+        let segments = fit_curve(&points, tolerance, 0.025);
+        assert!(!segments.is_empty());
+        assert!(segments.len() < 10);
+        for p in points.iter() {
+            // small allowance for the sampling of the curve in the test
+            assert!(distance_to_curve(p, &segments) <= tolerance + 0.1);
+        }
+        for joint in segments.windows(2) {
+            assert!(joint[0].ctrl_point(3).distance(&joint[1].ctrl_point(0)) < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_fit_splits_at_corner() {
+        let mut points: Vec<Point> = (0..=20)
+            .map(|i| Point {
+                x: i as f32 * 5.0,
+                y: 0.0,
+            })
+            .collect();
+        points.extend((1..=20).map(|i| Point {
+            x: 100.0,
+            y: i as f32 * 5.0,
+        }));
+        let segments = fit_curve(&points, 1.0, 0.025);
+        assert_eq!(2, segments.len());
+        let corner = segments[0].ctrl_point(3);
+        assert!(corner.distance(&Point { x: 100.0, y: 0.0 }) < 0.001);
+    }
+
+    #[test]
+    fn test_fit_degenerate() {
+        assert!(fit_curve(&[], 1.0, 0.025).is_empty());
+        let p = Point { x: 1.0, y: 1.0 };
+        assert!(fit_curve(&[p, p, p], 1.0, 0.025).is_empty());
+        let segments = fit_curve(&[p, Point { x: 10.0, y: 1.0 }], 1.0, 0.025);
+        assert_eq!(1, segments.len());
+    }
+}
@@ -1,4 +1,15 @@
 pub mod bezier;
+pub mod boolean;
+pub mod clothoid;
+pub mod continuity;
+pub mod dash;
+pub mod fit;
+pub mod mesh;
+pub mod polygon;
+pub mod polyline;
+pub mod ribbon;
+pub mod spline;
+pub mod triangulate;
 
 use std::ops;
 
@@ -28,6 +39,48 @@ impl Point {
         f32::sqrt((self.x - x) * (self.x - x) + (self.y - y) * (self.y - y))
     }
 
+    /// Gets the dot product of the vectors self and p
+    pub fn dot(&self, p: &Point) -> f32 {
+        self.x * p.x + self.y * p.y
+    }
+
+    /// Gets the z component of the cross product of the vectors self and p.
+    /// The value is positive when p is counter-clockwise from self in a
+    /// y-up coordinate system.
+    pub fn cross(&self, p: &Point) -> f32 {
+        self.x * p.y - self.y * p.x
+    }
+
+    /// Gets the distance from self to the closest point on the line segment
+    /// with the endpoints a and b.
+    pub fn distance_to_segment(&self, a: &Point, b: &Point) -> f32 {
+        let ab = *b - *a;
+        let len2 = ab.dot(&ab);
+        if len2 < f32::EPSILON {
+            return self.distance(a);
+        }
+        let t = ((*self - *a).dot(&ab) / len2).clamp(0.0, 1.0);
+        self.distance(&(*a + ab * t))
+    }
+
+    /// Gets the length of the vector from the origin to self
+    pub fn length(&self) -> f32 {
+        f32::sqrt(self.x * self.x + self.y * self.y)
+    }
+
+    /// Gets the unit vector in the direction of self. A zero length vector is
+    /// returned unchanged.
+    pub fn normalize(&self) -> Point {
+        let len = self.length();
+        if len < f32::EPSILON {
+            return *self;
+        }
+        Point {
+            x: self.x / len,
+            y: self.y / len,
+        }
+    }
+
     /// Gets the slope of a line segment defined by the endpoints self and p
     ///
     /// Gets the slope for 2 points where:
@@ -163,6 +216,26 @@ mod test {
         assert_eq!(0.0, p3.y);
     }
 
+    #[test]
+    fn test_normalize() {
+        let p = Point { x: 3.0, y: -4.0 };
+        assert!((p.length() - 5.0).abs() < EPSILON);
+        let n = p.normalize();
+        assert!((n.x - 0.6).abs() < EPSILON);
+        assert!((n.y + 0.8).abs() < EPSILON);
+        assert!((n.dot(&p) - 5.0).abs() < EPSILON);
+        assert_eq!(0.0, Point::default().normalize().length());
+    }
+
+    #[test]
+    fn test_distance_to_segment() {
+        let a = Point { x: 0.0, y: 0.0 };
+        let b = Point { x: 10.0, y: 0.0 };
+        assert!((Point { x: 5.0, y: 3.0 }.distance_to_segment(&a, &b) - 3.0).abs() < EPSILON);
+        assert!((Point { x: 13.0, y: 4.0 }.distance_to_segment(&a, &b) - 5.0).abs() < EPSILON);
+        assert!((Point { x: 0.0, y: 2.0 }.distance_to_segment(&a, &a) - 2.0).abs() < EPSILON);
+    }
+
     #[test]
     fn test_add() {
         let p0 = Point { x: 5., y: -5. };
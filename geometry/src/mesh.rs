@@ -0,0 +1,59 @@
+use crate::Point;
+
+/// An indexed triangle mesh. Each triangle holds three indices into the
+/// vertex buffer and is wound in the same direction as a `CounterClockwise`
+/// polygon. The texture coordinates are either empty or hold a (u, v)
+/// coordinate for each vertex.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Point>,
+    pub triangles: Vec<[u32; 3]>,
+    pub tex_coords: Vec<Point>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Mesh::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// Gets the total area of the triangles in the mesh
+    pub fn area(&self) -> f32 {
+        self.triangles
+            .iter()
+            .map(|[a, b, c]| {
+                let a = self.vertices[*a as usize];
+                let b = self.vertices[*b as usize];
+                let c = self.vertices[*c as usize];
+                (b - a).cross(&(c - a)) / 2.0
+            })
+            .sum()
+    }
+
+    pub fn has_tex_coords(&self) -> bool {
+        !self.tex_coords.is_empty() && self.tex_coords.len() == self.vertices.len()
+    }
+
+    /// Appends the vertices and triangles of another mesh to this mesh. The
+    /// texture coordinates are kept only when both meshes have them.
+    pub fn append(&mut self, other: &Mesh) {
+        if self.vertices.is_empty() {
+            self.tex_coords = other.tex_coords.clone();
+        } else if self.has_tex_coords() && other.has_tex_coords() {
+            self.tex_coords.extend_from_slice(&other.tex_coords);
+        } else {
+            self.tex_coords.clear();
+        }
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(&other.vertices);
+        self.triangles.extend(
+            other
+                .triangles
+                .iter()
+                .map(|t| [t[0] + offset, t[1] + offset, t[2] + offset]),
+        );
+    }
+}
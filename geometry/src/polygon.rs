@@ -0,0 +1,259 @@
+use crate::{Point, Rect};
+
+/// The rule used to decide whether a point is inside a polygon whose edges
+/// overlap or self intersect. `NonZero` matches `D2D1_FILL_MODE_WINDING` and
+/// `EvenOdd` matches `D2D1_FILL_MODE_ALTERNATE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    EvenOdd,
+    NonZero,
+}
+
+/// The orientation of a polygon in a y-up coordinate system. In screen
+/// coordinates where y increases downwards a `CounterClockwise` polygon
+/// appears clockwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// A closed polygon. The last point is implicitly joined to the first point
+/// and should not be repeated.
+#[derive(Debug, Clone, Default)]
+pub struct Polygon {
+    points: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Point>) -> Self {
+        Polygon { points }
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn into_points(self) -> Vec<Point> {
+        self.points
+    }
+
+    /// Gets the number of vertices in the polygon
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Gets an iterator over the edges of the polygon including the closing
+    /// edge from the last point to the first point.
+    pub fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+        self.points.iter().zip(self.points.iter().cycle().skip(1))
+    }
+
+    /// Gets the signed area of the polygon using the shoelace formula. The
+    /// area is positive for a `CounterClockwise` polygon.
+    pub fn signed_area(&self) -> f32 {
+        self.edges().map(|(a, b)| a.cross(b)).sum::<f32>() / 2.0
+    }
+
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+
+    /// Gets the centroid of the area enclosed by the polygon or None when the
+    /// polygon has no area.
+    pub fn centroid(&self) -> Option<Point> {
+        let area = self.signed_area();
+        if area.abs() < f32::EPSILON {
+            return None;
+        }
+        // offset by the first point to reduce the loss of precision for
+        // polygons far from the origin
+        let origin = *self.points.first()?;
+        let mut c = Point::default();
+        for (a, b) in self.edges() {
+            let a = *a - origin;
+            let b = *b - origin;
+            c = c + (a + b) * a.cross(&b);
+        }
+        Some(origin + c * (1.0 / (6.0 * area)))
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        if self.signed_area() < 0.0 {
+            Orientation::Clockwise
+        } else {
+            Orientation::CounterClockwise
+        }
+    }
+
+    /// Reverses the order of the vertices and the orientation of the polygon
+    pub fn reverse(&mut self) {
+        self.points.reverse();
+    }
+
+    pub fn reversed(&self) -> Polygon {
+        let mut polygon = self.clone();
+        polygon.reverse();
+        polygon
+    }
+
+    /// Gets the bounding rectangle of the polygon
+    pub fn bounds(&self) -> Option<Rect> {
+        let first = self.points.first()?;
+        let (mut min, mut max) = (*first, *first);
+        for p in self.points.iter().skip(1) {
+            min.x = min.x.min(p.x);
+            min.y = min.y.min(p.y);
+            max.x = max.x.max(p.x);
+            max.y = max.y.max(p.y);
+        }
+        Some(Rect::new(min.x, min.y, max.x - min.x, max.y - min.y))
+    }
+
+    /// Gets the winding number of the polygon around the point. The winding
+    /// number counts the signed number of times the boundary travels around
+    /// the point and is 0 for a point outside of a simple polygon.
+    pub fn winding_number(&self, p: &Point) -> i32 {
+        let mut winding = 0;
+        for (a, b) in self.edges() {
+            let side = (*b - *a).cross(&(*p - *a));
+            if a.y <= p.y {
+                if b.y > p.y && side > 0.0 {
+                    winding += 1;
+                }
+            } else if b.y <= p.y && side < 0.0 {
+                winding -= 1;
+            }
+        }
+        winding
+    }
+
+    /// Tests if the point is inside the polygon using the fill rule
+    pub fn contains(&self, p: &Point, rule: FillRule) -> bool {
+        let winding = self.winding_number(p);
+        match rule {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+        }
+    }
+}
+
+impl From<Vec<Point>> for Polygon {
+    fn from(points: Vec<Point>) -> Self {
+        Polygon { points }
+    }
+}
+
+impl FromIterator<Point> for Polygon {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        Polygon {
+            points: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a Point> for Polygon {
+    fn from_iter<I: IntoIterator<Item = &'a Point>>(iter: I) -> Self {
+        Polygon {
+            points: iter.into_iter().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn square() -> Polygon {
+        Polygon::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 10.0, y: 0.0 },
+            Point { x: 10.0, y: 10.0 },
+            Point { x: 0.0, y: 10.0 },
+        ])
+    }
+
+    /// A pentagram whose center is covered twice by its boundary
+    fn star() -> Polygon {
+        (0..5)
+            .map(|i| {
+                let a = i as f32 * 4.0 * std::f32::consts::PI / 5.0;
+                Point {
+                    x: 10.0 * a.cos(),
+                    y: 10.0 * a.sin(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_area() {
+        let mut s = square();
+        assert!((s.signed_area() - 100.0).abs() < TEST_EPSILON);
+        assert_eq!(Orientation::CounterClockwise, s.orientation());
+        s.reverse();
+        assert!((s.signed_area() + 100.0).abs() < TEST_EPSILON);
+        assert!((s.area() - 100.0).abs() < TEST_EPSILON);
+        assert_eq!(Orientation::Clockwise, s.orientation());
+    }
+
+    #[test]
+    fn test_centroid() {
+        let c = square().reversed().centroid().unwrap();
+        assert!(c.distance(&Point { x: 5.0, y: 5.0 }) < TEST_EPSILON);
+        let l = Polygon::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 20.0, y: 0.0 },
+            Point { x: 20.0, y: 10.0 },
+            Point { x: 10.0, y: 10.0 },
+            Point { x: 10.0, y: 20.0 },
+            Point { x: 0.0, y: 20.0 },
+        ]);
+        let c = l.centroid().unwrap();
+        assert!(
+            c.distance(&Point {
+                x: 25.0 / 3.0,
+                y: 25.0 / 3.0
+            }) < TEST_EPSILON
+        );
+        let line = Polygon::new(vec![Point::default(), Point { x: 1.0, y: 1.0 }]);
+        assert!(line.centroid().is_none());
+    }
+
+    #[test]
+    fn test_contains() {
+        let s = square();
+        for rule in [FillRule::EvenOdd, FillRule::NonZero] {
+            assert!(s.contains(&Point { x: 5.0, y: 5.0 }, rule));
+            assert!(s.reversed().contains(&Point { x: 5.0, y: 5.0 }, rule));
+            assert!(!s.contains(&Point { x: 15.0, y: 5.0 }, rule));
+            assert!(!s.contains(&Point { x: 5.0, y: -1.0 }, rule));
+        }
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        let star = star();
+        let center = Point::default();
+        assert_eq!(2, star.winding_number(&center).abs());
+        assert!(star.contains(&center, FillRule::NonZero));
+        assert!(!star.contains(&center, FillRule::EvenOdd));
+        // a point inside one of the points of the star
+        let tip = Point { x: 7.0, y: 0.0 };
+        assert!(star.contains(&tip, FillRule::NonZero));
+        assert!(star.contains(&tip, FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let b = star().bounds().unwrap();
+        assert!((b.x + 8.09).abs() < 0.01);
+        assert!((b.width - 18.09).abs() < 0.01);
+        assert!(Polygon::default().bounds().is_none());
+    }
+}
@@ -0,0 +1,496 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::Point;
+
+/// The fraction of a segment at either end that is not a crossing
+const CROSSING_EPSILON: f32 = 1e-4;
+
+/// An open path of straight line segments such as a flattened Bézier curve.
+#[derive(Debug, Clone, Default)]
+pub struct Polyline {
+    points: Vec<Point>,
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Point>) -> Self {
+        Polyline { points }
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn into_points(self) -> Vec<Point> {
+        self.points
+    }
+
+    /// Gets the number of points in the polyline
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Gets the arc length of the polyline
+    pub fn length(&self) -> f32 {
+        self.points.windows(2).map(|s| s[0].distance(&s[1])).sum()
+    }
+
+    /// Gets the distance along the polyline from the first point to each
+    /// point. The first value is always 0.0 and the last value is the length.
+    pub fn cumulative_distance(&self) -> Vec<f32> {
+        let mut distance = Vec::<f32>::with_capacity(self.points.len());
+        let mut total = 0.0;
+        if !self.points.is_empty() {
+            distance.push(total);
+        }
+        for s in self.points.windows(2) {
+            total += s[0].distance(&s[1]);
+            distance.push(total);
+        }
+        distance
+    }
+
+    /// Gets the point at the distance along the polyline. Distances before
+    /// the start or past the end are clamped to the first and last points.
+    pub fn point_at_distance(&self, distance: f32) -> Option<Point> {
+        self.locate(distance)
+            .map(|(idx, t)| match self.points.get(idx + 1) {
+                Some(next) => self.points[idx] + (*next - self.points[idx]) * t,
+                None => self.points[idx],
+            })
+    }
+
+    /// Gets the unit direction of the polyline at the distance along it.
+    pub fn tangent_at_distance(&self, distance: f32) -> Option<Point> {
+        if self.points.len() < 2 {
+            return None;
+        }
+        self.locate(distance).map(|(idx, _)| {
+            let idx = idx.min(self.points.len() - 2);
+            (self.points[idx + 1] - self.points[idx]).normalize()
+        })
+    }
+
+    /// Gets the part of the polyline between the start and end distances
+    /// along it. The distances are clamped to the length of the polyline.
+    pub fn slice(&self, start: f32, end: f32) -> Polyline {
+        let (Some((first, t0)), Some((last, t1))) = (self.locate(start), self.locate(end)) else {
+            return Polyline::default();
+        };
+        let at = |idx: usize, t: f32| match self.points.get(idx + 1) {
+            Some(next) => self.points[idx] + (*next - self.points[idx]) * t,
+            None => self.points[idx],
+        };
+        let mut points = vec![at(first, t0)];
+        if start < end {
+            points.extend_from_slice(&self.points[first + 1..=last]);
+            points.push(at(last, t1));
+            points.dedup_by(|a, b| a.distance(b) < f32::EPSILON);
+        }
+        Polyline { points }
+    }
+
+    /// Finds the segment index and the parameter within the segment at the
+    /// distance along the polyline.
+    fn locate(&self, distance: f32) -> Option<(usize, f32)> {
+        if self.points.is_empty() {
+            return None;
+        }
+        let mut remaining = distance.max(0.0);
+        for (idx, s) in self.points.windows(2).enumerate() {
+            let len = s[0].distance(&s[1]);
+            if remaining <= len {
+                let t = if len > 0.0 { remaining / len } else { 0.0 };
+                return Some((idx, t));
+            }
+            remaining -= len;
+        }
+        Some((self.points.len() - 1, 0.0))
+    }
+
+    /// Simplifies the polyline using the Ramer–Douglas–Peucker algorithm.
+    /// No removed point is further than the tolerance from the result.
+    pub fn simplify_rdp(&self, tolerance: f32) -> Polyline {
+        if self.points.len() < 3 {
+            return self.clone();
+        }
+        let mut keep = vec![false; self.points.len()];
+        keep[0] = true;
+        keep[self.points.len() - 1] = true;
+        let mut stack = vec![(0, self.points.len() - 1)];
+        while let Some((first, last)) = stack.pop() {
+            let mut max = 0.0;
+            let mut split = first;
+            for idx in first + 1..last {
+                let d =
+                    self.points[idx].distance_to_segment(&self.points[first], &self.points[last]);
+                if d > max {
+                    max = d;
+                    split = idx;
+                }
+            }
+            if max > tolerance {
+                keep[split] = true;
+                stack.push((first, split));
+                stack.push((split, last));
+            }
+        }
+        self.filter(&keep)
+    }
+
+    /// Simplifies the polyline using the Visvalingam–Whyatt algorithm. Points
+    /// are removed in order of the area of the triangle they form with their
+    /// neighbours until every remaining triangle has at least the minimum
+    /// area.
+    pub fn simplify_visvalingam(&self, min_area: f32) -> Polyline {
+        let n = self.points.len();
+        if n < 3 {
+            return self.clone();
+        }
+        let mut prev: Vec<usize> = (0..n).map(|i| i.wrapping_sub(1)).collect();
+        let mut next: Vec<usize> = (1..=n).collect();
+        let mut keep = vec![true; n];
+        // the version of each point is bumped when its area changes so that
+        // stale heap entries can be ignored
+        let mut version = vec![0usize; n];
+        let mut heap = BinaryHeap::<Candidate>::new();
+        for idx in 1..n - 1 {
+            heap.push(Candidate {
+                area: self.triangle_area(idx - 1, idx, idx + 1),
+                idx,
+                version: 0,
+            });
+        }
+        while let Some(c) = heap.pop() {
+            if c.version != version[c.idx] || !keep[c.idx] {
+                continue;
+            }
+            if c.area >= min_area {
+                break;
+            }
+            keep[c.idx] = false;
+            let (p, q) = (prev[c.idx], next[c.idx]);
+            next[p] = q;
+            prev[q] = p;
+            for idx in [p, q] {
+                if idx != 0 && idx != n - 1 {
+                    version[idx] += 1;
+                    // the area of a neighbour never drops below the area of the
+                    // removed point so the removal order is preserved
+                    let area = self.triangle_area(prev[idx], idx, next[idx]).max(c.area);
+                    heap.push(Candidate {
+                        area,
+                        idx,
+                        version: version[idx],
+                    });
+                }
+            }
+        }
+        self.filter(&keep)
+    }
+
+    /// Resamples the polyline with points at a fixed spacing along its
+    /// length. The last point is always included so the final spacing may be
+    /// shorter.
+    pub fn resample(&self, spacing: f32) -> Polyline {
+        let length = self.length();
+        if self.points.len() < 2 || spacing <= 0.0 || length <= 0.0 {
+            return self.clone();
+        }
+        let count = (length / spacing).ceil() as usize;
+        let mut points = Vec::<Point>::with_capacity(count + 1);
+        let mut idx = 0;
+        let mut start = 0.0;
+        for i in 0..count {
+            let d = i as f32 * spacing;
+            // advance to the segment containing the distance
+            let mut len = self.points[idx].distance(&self.points[idx + 1]);
+            while start + len < d && idx + 2 < self.points.len() {
+                start += len;
+                idx += 1;
+                len = self.points[idx].distance(&self.points[idx + 1]);
+            }
+            let t = if len > 0.0 { (d - start) / len } else { 0.0 };
+            points.push(self.points[idx] + (self.points[idx + 1] - self.points[idx]) * t);
+        }
+        points.push(self.points[self.points.len() - 1]);
+        Polyline { points }
+    }
+
+    /// Smooths the polyline using Chaikin's corner cutting algorithm. Each
+    /// iteration replaces every segment with points at 1/4 and 3/4 of its
+    /// length while keeping the first and last points.
+    pub fn chaikin(&self, iterations: usize) -> Polyline {
+        let mut points = self.points.clone();
+        for _ in 0..iterations {
+            if points.len() < 3 {
+                break;
+            }
+            let mut smooth = Vec::<Point>::with_capacity(points.len() * 2);
+            smooth.push(points[0]);
+            for s in points.windows(2) {
+                smooth.push(0.75 * s[0] + 0.25 * s[1]);
+                smooth.push(0.25 * s[0] + 0.75 * s[1]);
+            }
+            // replace the cuts at the ends with the original end points
+            smooth.remove(1);
+            smooth.pop();
+            smooth.push(points[points.len() - 1]);
+            points = smooth;
+        }
+        Polyline { points }
+    }
+
+    /// Removes the loops where the polyline crosses itself within the
+    /// maximum loop length, such as the swallowtail of an offset curve on a
+    /// bend tighter than the offset distance. Each loop is replaced by the
+    /// point where the polyline crosses itself. Longer loops are kept.
+    pub fn trim_loops(&self, max_length: f32) -> Polyline {
+        let p = &self.points;
+        if p.len() < 4 {
+            return self.clone();
+        }
+        let mut points = Vec::<Point>::with_capacity(p.len());
+        points.push(p[0]);
+        let mut idx = 0;
+        while idx + 1 < p.len() {
+            let start = points[points.len() - 1];
+            let mut length = 0.0;
+            let mut crossing = None;
+            for j in idx + 2..p.len() - 1 {
+                length += p[j - 1].distance(&p[j]);
+                if length > max_length {
+                    break;
+                }
+                if let Some(x) = segment_crossing(start, p[idx + 1], p[j], p[j + 1]) {
+                    crossing = Some((j, x));
+                    break;
+                }
+            }
+            match crossing {
+                Some((j, x)) => {
+                    points.push(x);
+                    idx = j;
+                }
+                None => {
+                    points.push(p[idx + 1]);
+                    idx += 1;
+                }
+            }
+        }
+        Polyline { points }
+    }
+
+    fn triangle_area(&self, a: usize, b: usize, c: usize) -> f32 {
+        let p = &self.points;
+        ((p[b] - p[a]).cross(&(p[c] - p[a])) / 2.0).abs()
+    }
+
+    fn filter(&self, keep: &[bool]) -> Polyline {
+        Polyline {
+            points: self
+                .points
+                .iter()
+                .zip(keep)
+                .filter(|(_, k)| **k)
+                .map(|(p, _)| *p)
+                .collect(),
+        }
+    }
+}
+
+impl From<Vec<Point>> for Polyline {
+    fn from(points: Vec<Point>) -> Self {
+        Polyline { points }
+    }
+}
+
+impl FromIterator<Point> for Polyline {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        Polyline {
+            points: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Gets the point where the segments a-b and c-d cross inside both segments.
+/// Segments that only touch at an end point do not cross.
+fn segment_crossing(a: Point, b: Point, c: Point, d: Point) -> Option<Point> {
+    let r = b - a;
+    let s = d - c;
+    let denominator = r.cross(&s);
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+    let t = (c - a).cross(&s) / denominator;
+    let u = (c - a).cross(&r) / denominator;
+    let inside = |v: f32| v > CROSSING_EPSILON && v < 1.0 - CROSSING_EPSILON;
+    (inside(t) && inside(u)).then(|| a + r * t)
+}
+
+/// A point that may be removed by the Visvalingam–Whyatt algorithm. The
+/// ordering is reversed so that the smallest area is at the top of the heap.
+struct Candidate {
+    area: f32,
+    idx: usize,
+    version: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .area
+            .total_cmp(&self.area)
+            .then_with(|| other.idx.cmp(&self.idx))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn zigzag() -> Polyline {
+        Polyline::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 10.0, y: 0.1 },
+            Point { x: 20.0, y: -0.1 },
+            Point { x: 30.0, y: 10.0 },
+            Point { x: 40.0, y: 0.0 },
+        ])
+    }
+
+    #[test]
+    fn test_length() {
+        let p = Polyline::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 3.0, y: 4.0 },
+            Point { x: 3.0, y: 10.0 },
+        ]);
+        assert!((p.length() - 11.0).abs() < TEST_EPSILON);
+        assert_eq!(vec![0.0, 5.0, 11.0], p.cumulative_distance());
+        assert!(Polyline::default().cumulative_distance().is_empty());
+    }
+
+    #[test]
+    fn test_point_at_distance() {
+        let p = Polyline::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 10.0, y: 0.0 },
+            Point { x: 10.0, y: 10.0 },
+        ]);
+        let mid = p.point_at_distance(15.0).unwrap();
+        assert!(mid.distance(&Point { x: 10.0, y: 5.0 }) < TEST_EPSILON);
+        let end = p.point_at_distance(100.0).unwrap();
+        assert!(end.distance(&Point { x: 10.0, y: 10.0 }) < TEST_EPSILON);
+        let start = p.point_at_distance(-1.0).unwrap();
+        assert!(start.distance(&Point::default()) < TEST_EPSILON);
+        let tangent = p.tangent_at_distance(15.0).unwrap();
+        assert!(tangent.distance(&Point { x: 0.0, y: 1.0 }) < TEST_EPSILON);
+        assert!(Polyline::default().point_at_distance(1.0).is_none());
+    }
+
+    #[test]
+    fn test_slice() {
+        let p = Polyline::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 10.0, y: 0.0 },
+            Point { x: 10.0, y: 10.0 },
+        ]);
+        let s = p.slice(5.0, 15.0);
+        assert_eq!(3, s.len());
+        assert!(s.points()[0].distance(&Point { x: 5.0, y: 0.0 }) < TEST_EPSILON);
+        assert!(s.points()[2].distance(&Point { x: 10.0, y: 5.0 }) < TEST_EPSILON);
+        assert!((s.length() - 10.0).abs() < TEST_EPSILON);
+        assert_eq!(2, p.slice(2.0, 4.0).len());
+        assert!((p.slice(-5.0, 50.0).length() - 20.0).abs() < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_simplify_rdp() {
+        let s = zigzag().simplify_rdp(0.5);
+        assert_eq!(4, s.len());
+        assert!(s.points()[1].distance(&Point { x: 20.0, y: -0.1 }) < TEST_EPSILON);
+        assert_eq!(zigzag().len(), zigzag().simplify_rdp(0.01).len());
+    }
+
+    #[test]
+    fn test_simplify_visvalingam() {
+        let s = zigzag().simplify_visvalingam(5.0);
+        assert_eq!(4, s.len());
+        assert!(s.points()[2].distance(&Point { x: 30.0, y: 10.0 }) < TEST_EPSILON);
+        let s = zigzag().simplify_visvalingam(1000.0);
+        assert_eq!(2, s.len());
+    }
+
+    #[test]
+    fn test_resample() {
+        let p = Polyline::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 10.0, y: 0.0 },
+            Point { x: 10.0, y: 5.0 },
+        ]);
+        let r = p.resample(4.0);
+        assert_eq!(5, r.len());
+        assert!(r.points()[3].distance(&Point { x: 10.0, y: 2.0 }) < TEST_EPSILON);
+        assert!(r.points()[4].distance(&Point { x: 10.0, y: 5.0 }) < TEST_EPSILON);
+        let d = r.cumulative_distance();
+        for w in d.windows(2).take(2) {
+            assert!((w[1] - w[0] - 4.0).abs() < TEST_EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_chaikin() {
+        let p = Polyline::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 10.0, y: 10.0 },
+            Point { x: 20.0, y: 0.0 },
+        ]);
+        let s = p.chaikin(1);
+        assert_eq!(4, s.len());
+        assert!(s.points()[0].distance(&Point { x: 0.0, y: 0.0 }) < TEST_EPSILON);
+        assert!(s.points()[1].distance(&Point { x: 7.5, y: 7.5 }) < TEST_EPSILON);
+        assert!(s.points()[3].distance(&Point { x: 20.0, y: 0.0 }) < TEST_EPSILON);
+        assert!(p.chaikin(3).length() < p.length());
+    }
+
+    #[test]
+    fn test_trim_loops() {
+        // a path that crosses itself at (10, 0) with a small loop
+        let p = Polyline::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 20.0, y: 0.0 },
+            Point { x: 20.0, y: 5.0 },
+            Point { x: 10.0, y: 5.0 },
+            Point { x: 10.0, y: -10.0 },
+            Point { x: 10.0, y: -20.0 },
+        ]);
+        let trimmed = p.trim_loops(100.0);
+        assert_eq!(4, trimmed.len());
+        assert!(trimmed.points()[1].distance(&Point { x: 10.0, y: 0.0 }) < TEST_EPSILON);
+        assert!(trimmed.points()[2].distance(&Point { x: 10.0, y: -10.0 }) < TEST_EPSILON);
+        // the loop is longer than the maximum so it is kept
+        assert_eq!(6, p.trim_loops(10.0).len());
+        // a path without crossings is unchanged
+        assert_eq!(5, zigzag().trim_loops(100.0).len());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::bezier::Bezier;
+use crate::mesh::Mesh;
+use crate::Point;
+
+/// Creates a textured triangle mesh of constant width following a compound
+/// Bézier centerline such as a road, river or rail bed.
+///
+/// The vertices alternate between the left and right edge at each point of
+/// the centerline curves, so the vertex buffer can also be drawn directly as
+/// a triangle strip. The u texture coordinate runs across the ribbon from 0.0
+/// on the left edge to 1.0 on the right edge and v is the arc length along
+/// the centerline divided by the texture length, so a texture repeats every
+/// texture length units without stretching on bends. The texture length
+/// must be a positive finite number.
+pub fn ribbon(centerline: &mut [Bezier], width: f32, texture_length: f32) -> Mesh {
+    assert!(texture_length.is_finite() && texture_length > 0.0);
+    let half_width = width / 2.0;
+    let mut mesh = Mesh::new();
+    let mut distance = 0.0;
+    let mut prev: Option<Point> = None;
+    for (idx, segment) in centerline.iter_mut().enumerate() {
+        let left = segment.offset_curve(half_width);
+        let right = segment.offset_curve(-half_width);
+        // the first point of each following segment is the joint already
+        // added as the last point of the previous segment
+        let skip = if idx == 0 { 0 } else { 1 };
+        let curve = segment.curve();
+        for i in skip..curve.len() {
+            if let Some(p) = prev {
+                distance += p.distance(&curve[i]);
+            }
+            prev = Some(curve[i]);
+            let v = distance / texture_length;
+            mesh.vertices.push(left[i]);
+            mesh.vertices.push(right[i]);
+            mesh.tex_coords.push(Point { x: 0.0, y: v });
+            mesh.tex_coords.push(Point { x: 1.0, y: v });
+        }
+    }
+
+    let rows = mesh.vertices.len() as u32 / 2;
+    for row in 0..rows.saturating_sub(1) {
+        let (l0, r0) = (row * 2, row * 2 + 1);
+        let (l1, r1) = (l0 + 2, r0 + 2);
+        mesh.triangles.push([l0, r1, r0]);
+        mesh.triangles.push([l0, l1, r1]);
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn straight(x0: f32, x1: f32) -> Bezier {
+        Bezier::new_with_ctrl_point(
+            [
+                Point { x: x0, y: 0.0 },
+                Point {
+                    x: x0 + (x1 - x0) / 3.0,
+                    y: 0.0,
+                },
+                Point {
+                    x: x1 - (x1 - x0) / 3.0,
+                    y: 0.0,
+                },
+                Point { x: x1, y: 0.0 },
+            ],
+            0.25,
+        )
+    }
+
+    #[test]
+    fn test_straight_ribbon() {
+        let mut centerline = [straight(0.0, 100.0), straight(100.0, 200.0)];
+        let mesh = ribbon(&mut centerline, 10.0, 50.0);
+        // 5 points in the first segment and 4 more in the second
+        assert_eq!(18, mesh.vertices.len());
+        assert_eq!(16, mesh.triangles.len());
+        assert!(mesh.has_tex_coords());
+        assert!((mesh.area() - 2000.0).abs() < 0.01);
+        assert!(mesh.vertices[0].distance(&Point { x: 0.0, y: -5.0 }) < TEST_EPSILON);
+        assert!(mesh.vertices[1].distance(&Point { x: 0.0, y: 5.0 }) < TEST_EPSILON);
+        let last = mesh.tex_coords.len() - 1;
+        assert!((mesh.tex_coords[last].x - 1.0).abs() < TEST_EPSILON);
+        assert!((mesh.tex_coords[last].y - 4.0).abs() < TEST_EPSILON);
+        for [a, b, c] in mesh.triangles.iter() {
+            let (a, b, c) = (
+                mesh.vertices[*a as usize],
+                mesh.vertices[*b as usize],
+                mesh.vertices[*c as usize],
+            );
+            assert!((b - a).cross(&(c - a)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_arc_length_v() {
+        let mut centerline = [Bezier::new_with_ctrl_point(
+            [
+                Point { x: 0.0, y: 0.0 },
+                Point { x: 50.0, y: 0.0 },
+                Point { x: 100.0, y: 50.0 },
+                Point { x: 100.0, y: 100.0 },
+            ],
+            0.025,
+        )];
+        let mesh = ribbon(&mut centerline, 10.0, 1.0);
+        let length = centerline[0].len();
+        let last = mesh.tex_coords.len() - 1;
+        assert!((mesh.tex_coords[last].y - length).abs() < 0.01);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::bezier::Bezier;
+use crate::Point;
+
+/// Knot intervals shorter than this are treated as coincident points.
+const MIN_KNOT_INTERVAL: f32 = 0.0001;
+
+/// The knot parameterization of a Catmull-Rom spline. The knot interval
+/// between two points is |P(i+1) - P(i)|^alpha where alpha is 0.0 for
+/// uniform, 0.5 for centripetal and 1.0 for chordal parameterization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameterization {
+    Uniform,
+    Centripetal,
+    Chordal,
+}
+
+impl Parameterization {
+    pub fn alpha(&self) -> f32 {
+        match self {
+            Parameterization::Uniform => 0.0,
+            Parameterization::Centripetal => 0.5,
+            Parameterization::Chordal => 1.0,
+        }
+    }
+
+    fn knot_interval(&self, p0: &Point, p1: &Point) -> f32 {
+        let d = p0.distance(p1).powf(self.alpha());
+        if d < MIN_KNOT_INTERVAL {
+            1.0
+        } else {
+            d
+        }
+    }
+}
+
+/// Creates a Catmull-Rom spline passing through every point and returns it
+/// as a compound Bézier curve with one segment between each pair of points.
+///
+/// The end tangents are derived from phantom points created by reflecting
+/// the second and second to last points around the first and last points.
+/// An empty vector is returned when fewer than 2 points are provided.
+pub fn catmull_rom(
+    points: &[Point],
+    parameterization: Parameterization,
+    resolution: f32,
+) -> Vec<Bezier> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+    let last = points.len() - 1;
+    let mut segments = Vec::<Bezier>::with_capacity(last);
+    for i in 0..last {
+        let p1 = points[i];
+        let p2 = points[i + 1];
+        let p0 = if i == 0 {
+            p2.reflect(p1)
+        } else {
+            points[i - 1]
+        };
+        let p3 = if i + 1 == last {
+            p1.reflect(p2)
+        } else {
+            points[i + 2]
+        };
+
+        let d0 = parameterization.knot_interval(&p0, &p1);
+        let d1 = parameterization.knot_interval(&p1, &p2);
+        let d2 = parameterization.knot_interval(&p2, &p3);
+
+        // tangents of the non-uniform Catmull-Rom spline at p1 and p2
+        let m1 = (p1 - p0) * (1.0 / d0) - (p2 - p0) * (1.0 / (d0 + d1)) + (p2 - p1) * (1.0 / d1);
+        let m2 = (p2 - p1) * (1.0 / d1) - (p3 - p1) * (1.0 / (d1 + d2)) + (p3 - p2) * (1.0 / d2);
+
+        segments.push(Bezier::new_with_ctrl_point(
+            [p1, p1 + m1 * (d1 / 3.0), p2 - m2 * (d1 / 3.0), p2],
+            resolution,
+        ));
+    }
+    segments
+}
+
+/// Creates a natural cubic spline passing through every point and returns it
+/// as a compound Bézier curve with one segment between each pair of points.
+///
+/// The spline is C2 continuous at every joint and has zero curvature at the
+/// first and last points. An empty vector is returned when fewer than 2
+/// points are provided.
+pub fn natural_cubic(points: &[Point], resolution: f32) -> Vec<Bezier> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+    let derivatives = natural_derivatives(points);
+    points
+        .windows(2)
+        .zip(derivatives.windows(2))
+        .map(|(p, d)| {
+            Bezier::new_with_ctrl_point(
+                [
+                    p[0],
+                    p[0] + d[0] * (1.0 / 3.0),
+                    p[1] - d[1] * (1.0 / 3.0),
+                    p[1],
+                ],
+                resolution,
+            )
+        })
+        .collect()
+}
+
+/// Solves the tridiagonal system for the first derivative at each point of
+/// a natural cubic spline using the Thomas algorithm.
+fn natural_derivatives(points: &[Point]) -> Vec<Point> {
+    let n = points.len();
+    let last = n - 1;
+    // sub, main and super diagonals are 1, 4, 1 except for the first and
+    // last rows which are 2, 1 and 1, 2
+    let mut diag = vec![4.0f32; n];
+    diag[0] = 2.0;
+    diag[last] = 2.0;
+    let mut rhs = Vec::<Point>::with_capacity(n);
+    rhs.push(3.0 * (points[1] - points[0]));
+    for i in 1..last {
+        rhs.push(3.0 * (points[i + 1] - points[i - 1]));
+    }
+    rhs.push(3.0 * (points[last] - points[last - 1]));
+
+    // forward elimination
+    for i in 1..n {
+        let m = 1.0 / diag[i - 1];
+        diag[i] -= m;
+        let prev = rhs[i - 1];
+        rhs[i] = rhs[i] - prev * m;
+    }
+    // back substitution
+    let mut derivatives = vec![Point::default(); n];
+    derivatives[last] = rhs[last] * (1.0 / diag[last]);
+    for i in (0..last).rev() {
+        derivatives[i] = (rhs[i] - derivatives[i + 1]) * (1.0 / diag[i]);
+    }
+    derivatives
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn points() -> Vec<Point> {
+        vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 100.0, y: 50.0 },
+            Point { x: 150.0, y: 200.0 },
+            Point { x: 300.0, y: 210.0 },
+        ]
+    }
+
+    #[test]
+    fn test_catmull_rom_interpolates() {
+        let points = points();
+        for param in [
+            Parameterization::Uniform,
+            Parameterization::Centripetal,
+            Parameterization::Chordal,
+        ] {
+            let segments = catmull_rom(&points, param, 0.1);
+            assert_eq!(3, segments.len());
+            for (i, b) in segments.iter().enumerate() {
+                assert!(b.ctrl_point(0).distance(&points[i]) < TEST_EPSILON);
+                assert!(b.ctrl_point(3).distance(&points[i + 1]) < TEST_EPSILON);
+            }
+            // the handles at each joint are collinear
+            for joint in segments.windows(2) {
+                let a = joint[0].ctrl_point(3) - joint[0].ctrl_point(2);
+                let b = joint[1].ctrl_point(1) - joint[1].ctrl_point(0);
+                assert!((a.x * b.y - a.y * b.x).abs() < TEST_EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn test_uniform_catmull_rom_tangent() {
+        let points = points();
+        let segments = catmull_rom(&points, Parameterization::Uniform, 0.1);
+        // the uniform tangent at an interior point is (P(i+1) - P(i-1)) / 2
+        let handle = segments[1].ctrl_point(1) - segments[1].ctrl_point(0);
+        let expected = (points[2] - points[0]) * (1.0 / 6.0);
+        assert!(handle.distance(&expected) < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_natural_cubic_continuity() {
+        let points = points();
+        let segments = natural_cubic(&points, 0.1);
+        assert_eq!(3, segments.len());
+        for joint in segments.windows(2) {
+            let a = joint[0].ctrl_points();
+            let b = joint[1].ctrl_points();
+            // C1: the handles are reflected around the joint
+            assert!(a[2].reflect(a[3]).distance(&b[1]) < TEST_EPSILON);
+            // C2: second derivatives match at the joint
+            let dd_a = a[1] - 2.0 * a[2] + a[3];
+            let dd_b = b[0] - 2.0 * b[1] + b[2];
+            assert!(dd_a.distance(&dd_b) < TEST_EPSILON);
+        }
+        // natural end condition, zero second derivative at the first point
+        let c = segments[0].ctrl_points();
+        let dd = c[0] - 2.0 * c[1] + c[2];
+        assert!(dd.distance(&Point::default()) < TEST_EPSILON);
+    }
+
+    #[test]
+    fn test_too_few_points() {
+        assert!(catmull_rom(&[Point::default()], Parameterization::Centripetal, 0.1).is_empty());
+        assert!(natural_cubic(&[], 0.1).is_empty());
+    }
+}
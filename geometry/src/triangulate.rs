@@ -0,0 +1,316 @@
+//! Triangulation of polygons with holes by ear clipping. Holes are first
+//! joined to the outer contour with a pair of coincident bridge edges so
+//! that a single contour can be clipped.
+
+use crate::boolean::{self, BooleanOp};
+use crate::mesh::Mesh;
+use crate::polygon::{FillRule, Orientation, Polygon};
+use crate::Point;
+
+/// Triangulates the area inside the outer contour and outside of the holes.
+/// The holes must be inside the outer contour and must not overlap each
+/// other; either orientation may be used for the contours.
+pub fn triangulate(outer: &Polygon, holes: &[Polygon]) -> Mesh {
+    let mut vertices = Vec::<Point>::new();
+    let mut ring = oriented_indices(outer, Orientation::CounterClockwise, &mut vertices);
+    let mut hole_rings: Vec<Vec<u32>> = holes
+        .iter()
+        .map(|h| oriented_indices(h, Orientation::Clockwise, &mut vertices))
+        .filter(|h| h.len() >= 3)
+        .collect();
+
+    // bridge the holes from right to left so that each bridge is visible
+    // from the holes joined before it
+    hole_rings.sort_by(|a, b| max_x(&vertices, b).total_cmp(&max_x(&vertices, a)));
+    for idx in 0..hole_rings.len() {
+        let (joined, remaining) = hole_rings.split_at(idx + 1);
+        ring = bridge_hole(&vertices, &ring, &joined[idx], remaining);
+    }
+
+    let triangles = clip_ears(&vertices, ring);
+    Mesh {
+        vertices,
+        triangles,
+        tex_coords: Vec::new(),
+    }
+}
+
+/// Triangulates the area enclosed by the contours under the fill rule. The
+/// contours may overlap, self intersect and contain holes, such as the
+/// surface polygon of a feature on a tight bend.
+pub fn triangulate_contours(contours: &[Polygon], rule: FillRule) -> Mesh {
+    let clean = boolean::boolean(contours, &[], BooleanOp::Union, rule);
+    let (outers, holes): (Vec<Polygon>, Vec<Polygon>) = clean
+        .into_iter()
+        .partition(|c| c.orientation() == Orientation::CounterClockwise);
+
+    // assign each hole to the smallest outer contour that contains it
+    let mut assigned: Vec<Vec<Polygon>> = outers.iter().map(|_| Vec::new()).collect();
+    for hole in holes {
+        let probe = hole_probe(&hole);
+        let owner = outers
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.contains(&probe, FillRule::NonZero))
+            .min_by(|(_, a), (_, b)| a.area().total_cmp(&b.area()))
+            .map(|(idx, _)| idx);
+        if let Some(owner) = owner {
+            assigned[owner].push(hole);
+        }
+    }
+
+    let mut mesh = Mesh::new();
+    for (outer, holes) in outers.iter().zip(assigned) {
+        mesh.append(&triangulate(outer, &holes));
+    }
+    mesh
+}
+
+/// Adds the points of the polygon to the vertex buffer and returns their
+/// indices in the requested orientation without repeated points.
+fn oriented_indices(
+    polygon: &Polygon,
+    orientation: Orientation,
+    vertices: &mut Vec<Point>,
+) -> Vec<u32> {
+    let start = vertices.len() as u32;
+    let mut indices = Vec::<u32>::with_capacity(polygon.len());
+    for p in polygon.points() {
+        if let Some(last) = vertices.last() {
+            if vertices.len() as u32 > start && last.distance(p) < f32::EPSILON {
+                continue;
+            }
+        }
+        indices.push(vertices.len() as u32);
+        vertices.push(*p);
+    }
+    if indices.len() > 1
+        && vertices[indices[0] as usize].distance(vertices.last().unwrap()) < f32::EPSILON
+    {
+        indices.pop();
+    }
+    if polygon.orientation() != orientation {
+        indices.reverse();
+    }
+    indices
+}
+
+fn max_x(vertices: &[Point], ring: &[u32]) -> f32 {
+    ring.iter()
+        .map(|i| vertices[*i as usize].x)
+        .fold(f32::MIN, f32::max)
+}
+
+/// Gets a point just inside the void of a clockwise hole contour
+fn hole_probe(hole: &Polygon) -> Point {
+    let (a, b) = hole.edges().next().unwrap();
+    let d = (*b - *a).normalize();
+    let offset = (a.distance(b) / 100.0).max(f32::EPSILON);
+    // the void of a clockwise contour is on the right of its edges
+    (*a + *b) * 0.5 + Point { x: d.y, y: -d.x } * offset
+}
+
+/// Joins the hole to the ring with a bridge from the rightmost vertex of the
+/// hole to the closest visible vertex of the ring.
+fn bridge_hole(vertices: &[Point], ring: &[u32], hole: &[u32], remaining: &[Vec<u32>]) -> Vec<u32> {
+    let p = |i: u32| vertices[i as usize];
+    let (m_pos, m) = hole
+        .iter()
+        .enumerate()
+        .max_by(|a, b| p(*a.1).x.total_cmp(&p(*b.1).x))
+        .map(|(pos, i)| (pos, *i))
+        .unwrap();
+    let mp = p(m);
+
+    let mut candidates: Vec<usize> = (0..ring.len()).collect();
+    candidates.sort_by(|a, b| {
+        p(ring[*a])
+            .distance(&mp)
+            .total_cmp(&p(ring[*b]).distance(&mp))
+    });
+    let visible = candidates.into_iter().find(|pos| {
+        let target = p(ring[*pos]);
+        let blocked = |r: &[u32]| {
+            (0..r.len()).any(|i| {
+                let (a, b) = (p(r[i]), p(r[(i + 1) % r.len()]));
+                !touches(&a, &b, &target) && !touches(&a, &b, &mp) && crosses(&mp, &target, &a, &b)
+            })
+        };
+        !blocked(ring) && !blocked(hole) && !remaining.iter().any(|h| blocked(h))
+    });
+    // fall back to the closest vertex when rounding hides every vertex
+    let p_pos = visible.unwrap_or(0);
+
+    let mut joined = Vec::<u32>::with_capacity(ring.len() + hole.len() + 2);
+    joined.extend_from_slice(&ring[..=p_pos]);
+    for i in 0..=hole.len() {
+        joined.push(hole[(m_pos + i) % hole.len()]);
+    }
+    joined.push(ring[p_pos]);
+    joined.extend_from_slice(&ring[p_pos + 1..]);
+    joined
+}
+
+fn touches(a: &Point, b: &Point, p: &Point) -> bool {
+    a.distance(p) < f32::EPSILON || b.distance(p) < f32::EPSILON
+}
+
+/// Tests if the segments p-q and a-b cross at a point interior to both
+fn crosses(p: &Point, q: &Point, a: &Point, b: &Point) -> bool {
+    let d1 = (*q - *p).cross(&(*a - *p));
+    let d2 = (*q - *p).cross(&(*b - *p));
+    let d3 = (*b - *a).cross(&(*p - *a));
+    let d4 = (*b - *a).cross(&(*q - *a));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Clips ears from the counter-clockwise ring until a single triangle is
+/// left.
+fn clip_ears(vertices: &[Point], mut ring: Vec<u32>) -> Vec<[u32; 3]> {
+    let p = |i: u32| vertices[i as usize];
+    let mut triangles = Vec::<[u32; 3]>::with_capacity(ring.len().saturating_sub(2));
+    let mut idx = 0;
+    let mut attempts = 0;
+    while ring.len() > 3 {
+        let n = ring.len();
+        let (a, b, c) = (ring[(idx + n - 1) % n], ring[idx % n], ring[(idx + 1) % n]);
+        let (pa, pb, pc) = (p(a), p(b), p(c));
+        let turn = (pb - pa).cross(&(pc - pb));
+        if turn.abs() <= f32::EPSILON * (pb - pa).length() * (pc - pb).length() {
+            // collinear or repeated vertex, drop it without a triangle
+            ring.remove(idx % n);
+            attempts = 0;
+            continue;
+        }
+        let is_ear = turn > 0.0
+            && !ring.iter().any(|i| {
+                let q = p(*i);
+                !touches(&pa, &pb, &q)
+                    && q.distance(&pc) >= f32::EPSILON
+                    && in_triangle(&q, &pa, &pb, &pc)
+            });
+        // after a full pass without an ear the ring is degenerate, so clip
+        // the current vertex to make progress. A reflex vertex is dropped
+        // without a triangle to keep every triangle counter-clockwise.
+        if is_ear || attempts > n {
+            if turn > 0.0 {
+                triangles.push([a, b, c]);
+            }
+            ring.remove(idx % n);
+            attempts = 0;
+        } else {
+            idx = (idx + 1) % n;
+            attempts += 1;
+        }
+    }
+    if ring.len() == 3 {
+        let (pa, pb, pc) = (p(ring[0]), p(ring[1]), p(ring[2]));
+        if (pb - pa).cross(&(pc - pa)) > 0.0 {
+            triangles.push([ring[0], ring[1], ring[2]]);
+        }
+    }
+    triangles
+}
+
+/// Tests if the point is inside or on the edge of the counter-clockwise
+/// triangle
+fn in_triangle(q: &Point, a: &Point, b: &Point, c: &Point) -> bool {
+    (*b - *a).cross(&(*q - *a)) >= 0.0
+        && (*c - *b).cross(&(*q - *b)) >= 0.0
+        && (*a - *c).cross(&(*q - *c)) >= 0.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const TEST_EPSILON: f32 = 0.001;
+
+    fn square(x: f32, y: f32, size: f32) -> Polygon {
+        Polygon::new(vec![
+            Point { x, y },
+            Point { x: x + size, y },
+            Point {
+                x: x + size,
+                y: y + size,
+            },
+            Point { x, y: y + size },
+        ])
+    }
+
+    fn assert_valid(mesh: &Mesh) {
+        for [a, b, c] in mesh.triangles.iter() {
+            let (a, b, c) = (
+                mesh.vertices[*a as usize],
+                mesh.vertices[*b as usize],
+                mesh.vertices[*c as usize],
+            );
+            assert!((b - a).cross(&(c - a)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_square() {
+        let mesh = triangulate(&square(0.0, 0.0, 10.0).reversed(), &[]);
+        assert_eq!(4, mesh.vertices.len());
+        assert_eq!(2, mesh.triangles.len());
+        assert!((mesh.area() - 100.0).abs() < TEST_EPSILON);
+        assert_valid(&mesh);
+    }
+
+    #[test]
+    fn test_concave() {
+        let l = Polygon::new(vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 20.0, y: 0.0 },
+            Point { x: 20.0, y: 10.0 },
+            Point { x: 10.0, y: 10.0 },
+            Point { x: 10.0, y: 20.0 },
+            Point { x: 0.0, y: 20.0 },
+        ]);
+        let mesh = triangulate(&l, &[]);
+        assert_eq!(4, mesh.triangles.len());
+        assert!((mesh.area() - 300.0).abs() < TEST_EPSILON);
+        assert_valid(&mesh);
+    }
+
+    #[test]
+    fn test_holes() {
+        let outer = square(0.0, 0.0, 30.0);
+        let holes = [square(5.0, 5.0, 5.0), square(20.0, 20.0, 5.0)];
+        let mesh = triangulate(&outer, &holes);
+        assert!((mesh.area() - 850.0).abs() < TEST_EPSILON);
+        assert_eq!(12, mesh.vertices.len());
+        assert_eq!(14, mesh.triangles.len());
+        assert_valid(&mesh);
+    }
+
+    #[test]
+    fn test_self_intersecting() {
+        // a ring crossing itself has no ear on one of its loops, so the
+        // clipping is forced and must not emit clockwise triangles
+        let ring = Polygon::new(vec![
+            Point { x: 10.0, y: 6.0 },
+            Point { x: 15.0, y: 17.0 },
+            Point { x: 5.0, y: 10.0 },
+            Point { x: 3.0, y: 17.0 },
+            Point { x: 14.0, y: 15.0 },
+            Point { x: 11.0, y: 17.0 },
+            Point { x: 14.0, y: 16.0 },
+        ]);
+        let mesh = triangulate(&ring, &[]);
+        assert_valid(&mesh);
+    }
+
+    #[test]
+    fn test_contours() {
+        // overlapping squares, one with a hole cut by the difference
+        let contours = [
+            square(0.0, 0.0, 10.0),
+            square(5.0, 5.0, 10.0),
+            square(11.0, 11.0, 2.0).reversed(),
+        ];
+        let mesh = triangulate_contours(&contours, FillRule::NonZero);
+        assert!((mesh.area() - 171.0).abs() < TEST_EPSILON);
+        assert_valid(&mesh);
+    }
+}